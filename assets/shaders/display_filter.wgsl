@@ -0,0 +1,106 @@
+struct DisplayFilterParams {
+    mode: u32,
+    intensity: f32,
+    texture_size: vec2<f32>,
+    color_mode: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> params: DisplayFilterParams;
+@group(1) @binding(1)
+var screen_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var screen_sampler: sampler;
+
+let MODE_CRT: u32 = 1u;
+let MODE_SCANLINES: u32 = 2u;
+let PI: f32 = 3.14159265;
+
+let COLOR_PROTANOPIA: u32 = 1u;
+let COLOR_DEUTERANOPIA: u32 = 2u;
+let COLOR_TRITANOPIA: u32 = 3u;
+
+// Barrel distortion of the tube
+fn curve(uv: vec2<f32>) -> vec2<f32> {
+    let c = uv * 2.0 - 1.0;
+    let curved = c * (1.0 + c.yx * c.yx * vec2<f32>(0.04, 0.06));
+    return curved * 0.5 + 0.5;
+}
+
+fn scanline(uv: vec2<f32>, depth: f32) -> f32 {
+    // Brightest at the center of each source row, computed per output pixel
+    let y = fract(uv.y * params.texture_size.y);
+    return mix(1.0 - depth, 1.0, sin(y * PI));
+}
+
+// Daltonization: the colors lost to the deficiency are moved into the channels still seen
+fn daltonize(color: vec3<f32>) -> vec3<f32> {
+    // Rows, applied as `v * m`
+    let rgb_to_lms = mat3x3<f32>(
+        vec3<f32>(17.8824, 43.5161, 4.11935),
+        vec3<f32>(3.45565, 27.1554, 3.86714),
+        vec3<f32>(0.0299566, 0.184309, 1.46709),
+    );
+    let lms_to_rgb = mat3x3<f32>(
+        vec3<f32>(0.0809444479, -0.130504409, 0.116721066),
+        vec3<f32>(-0.0102485335, 0.0540193266, -0.113614708),
+        vec3<f32>(-0.000365296938, -0.00412161469, 0.693511405),
+    );
+
+    var lms = color * rgb_to_lms;
+    if (params.color_mode == COLOR_PROTANOPIA) {
+        lms.x = 2.02344 * lms.y - 2.52581 * lms.z;
+    } else if (params.color_mode == COLOR_DEUTERANOPIA) {
+        lms.y = 0.494207 * lms.x + 1.24827 * lms.z;
+    } else {
+        lms.z = -0.395913 * lms.x + 0.801109 * lms.y;
+    }
+    let seen = lms * lms_to_rgb;
+
+    let err = color - seen;
+    let shift = vec3<f32>(0.0, 0.7 * err.r + err.g, 0.7 * err.r + err.b);
+    return clamp(color + shift, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn display_filter(uv: vec2<f32>, clip_position: vec4<f32>) -> vec3<f32> {
+    if (params.mode == MODE_SCANLINES) {
+        // Every other row of the window, so the lines stay put at any scale
+        let color = textureSample(screen_texture, screen_sampler, uv);
+        let odd = u32(floor(clip_position.y)) % 2u == 1u;
+        let shade = select(1.0, 1.0 - params.intensity, odd);
+        return color.rgb * shade;
+    }
+    if (params.mode != MODE_CRT) {
+        return textureSample(screen_texture, screen_sampler, uv).rgb;
+    }
+
+    let p = curve(uv);
+    if (p.x < 0.0 || p.x > 1.0 || p.y < 0.0 || p.y > 1.0) {
+        return vec3<f32>(0.0, 0.0, 0.0);
+    }
+
+    let texel = 1.0 / params.texture_size;
+    let color = textureSample(screen_texture, screen_sampler, p).rgb;
+
+    // Cheap bloom from the neighboring source pixels
+    let glow = (textureSample(screen_texture, screen_sampler, p + vec2<f32>(texel.x, 0.0)).rgb
+        + textureSample(screen_texture, screen_sampler, p - vec2<f32>(texel.x, 0.0)).rgb
+        + textureSample(screen_texture, screen_sampler, p + vec2<f32>(0.0, texel.y)).rgb
+        + textureSample(screen_texture, screen_sampler, p - vec2<f32>(0.0, texel.y)).rgb) * 0.25;
+
+    let c = p * 2.0 - 1.0;
+    let vignette = clamp(1.0 - dot(c * c, c * c) * 0.5, 0.0, 1.0);
+
+    return (color * scanline(p, 0.3) + glow * 0.2) * vignette;
+}
+
+@fragment
+fn fragment(
+    #import bevy_sprite::mesh2d_vertex_output
+) -> @location(0) vec4<f32> {
+    var color = display_filter(uv, clip_position);
+    if (params.color_mode != 0u) {
+        color = daltonize(color);
+    }
+    return vec4<f32>(color, 1.0);
+}
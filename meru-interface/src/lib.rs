@@ -157,8 +157,98 @@ pub trait EmulatorCore {
     fn set_config(&mut self, config: &Self::Config);
 
     fn exec_frame(&mut self, render_graphics: bool);
+    /// Hard reset, like turning the power off and on.
     fn reset(&mut self);
 
+    /// Seed the randomness of the machine, like the power-on contents of RAM,
+    /// so that runs are reproducible. Takes effect from the next `reset`.
+    /// Cores without any randomness ignore it.
+    fn set_rng_seed(&mut self, _seed: u64) {}
+
+    /// Whether `soft_reset` differs from `reset` on this system.
+    /// None of the bundled cores (NES, SNES, GB, GBA) implement a soft reset yet,
+    /// so the soft reset hotkey power-cycles every system for now.
+    fn supports_soft_reset() -> bool {
+        false
+    }
+    /// Soft reset through the reset line, which keeps the RAM contents
+    /// on systems that have one. Falls back to `reset` by default.
+    fn soft_reset(&mut self) {
+        self.reset();
+    }
+
+    /// Whether the core can scale its CPU clock via `set_clock_multiplier`.
+    fn supports_clock_multiplier() -> bool {
+        false
+    }
+    fn set_clock_multiplier(&mut self, _multiplier: f64) {}
+
+    /// Whether the system has a BIOS menu that `boot_to_bios` can start into.
+    fn has_bios_menu() -> bool {
+        false
+    }
+    /// Restart the machine into its BIOS menu instead of the cartridge.
+    fn boot_to_bios(&mut self) {}
+
+    /// Number of controllers while a multitap is connected,
+    /// or `None` if the system has no multitap.
+    fn multitap_controllers() -> Option<usize> {
+        None
+    }
+    /// Connect or disconnect the multitap.
+    fn set_multitap(&mut self, _enabled: bool) {}
+
+    /// Whether the core exposes its link port via `link_output` and `link_input`.
+    fn supports_link_cable() -> bool {
+        false
+    }
+    /// Bytes sent out of the link port since the last call.
+    fn link_output(&mut self) -> Vec<u8> {
+        vec![]
+    }
+    /// Bytes received from the other end of the link cable.
+    fn link_input(&mut self, _data: &[u8]) {}
+
+    /// Whether the running game is displayed in the 4 gray levels
+    /// `0xFF`, `0xAA`, `0x55` and `0x00`, so the frontend can recolor it.
+    fn is_monochrome(&self) -> bool {
+        false
+    }
+
+    /// Width / height of a single pixel on the original display.
+    fn pixel_aspect_ratio(&self) -> f64 {
+        1.0
+    }
+
+    /// Whether the core understands cheat codes passed to `set_cheats`.
+    fn supports_cheats() -> bool {
+        false
+    }
+    /// Whether `code` is a well-formed cheat code for this system
+    /// (e.g. Game Genie or Action Replay).
+    fn is_valid_cheat(_code: &str) -> bool {
+        false
+    }
+    /// Replace the active cheats. The core keeps applying them on every frame,
+    /// including after `reset`.
+    fn set_cheats(&mut self, _codes: &[String]) {}
+
+    /// Strength of the controller rumble in the current frame, `0.0`-`1.0`.
+    fn rumble(&self) -> f32 {
+        0.0
+    }
+
+    /// Frames per second of the original hardware.
+    fn frame_rate(&self) -> f64 {
+        60.0
+    }
+
+    /// Version of the `save_state` format. Bump it on incompatible changes,
+    /// so that states in an older format are refused instead of loaded.
+    fn state_version() -> u32 {
+        0
+    }
+
     fn frame_buffer(&self) -> &FrameBuffer;
     fn audio_buffer(&self) -> &AudioBuffer;
 
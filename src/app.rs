@@ -1,45 +1,141 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use bevy::{
     diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
     input::{mouse::MouseButtonInput, ButtonState},
     prelude::*,
     render::texture::{ImageSampler, ImageSettings},
-    window::{PresentMode, WindowMode},
+    window::{PresentMode, WindowId, WindowMode, WindowMoved, WindowPosition, WindowResized},
+    winit::WinitWindows,
 };
 use bevy_easings::EasingsPlugin;
-use bevy_egui::{EguiContext, EguiPlugin};
-use bevy_tiled_camera::TiledCameraPlugin;
+use bevy_egui::{egui, EguiContext, EguiPlugin};
+use bevy_tiled_camera::{TiledCamera, TiledCameraPlugin};
 use log::error;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use crate::{
-    config::{self, load_config, load_persistent_state},
-    core::{self, Emulator, GameScreen},
-    hotkey,
+    audio_viz, bezel,
+    config::{self, load_config, load_persistent_state, MenuTheme, PersistentState},
+    core::{self, Emulator, GameScreen, RngSeed},
+    display_filter, hotkey, i18n, input_overlay, library,
     menu::{self, MENU_HEIGHT, MENU_WIDTH},
+    recording,
     rewinding::{self},
+    splash,
 };
 
+/// Command line arguments: `meru [--fullscreen] [--seed N] [ROM]`
+/// or `meru --bench ROM [--frames N] [--seed N]`
+#[derive(Default)]
+struct Args {
+    rom: Option<PathBuf>,
+    fullscreen: bool,
+    /// Seed for the randomness of every game opened
+    seed: Option<u64>,
+    /// Run the ROM headless for this many frames and report the speed
+    bench_frames: Option<usize>,
+}
+
+const DEFAULT_BENCH_FRAMES: usize = 3600;
+
+/// Title of the window outside of a game
+pub const WINDOW_TITLE: &str = "MERU";
+
+fn parse_args() -> Args {
+    let mut args = Args::default();
+    let mut it = std::env::args_os().skip(1);
+    while let Some(arg) = it.next() {
+        if arg == "--fullscreen" {
+            args.fullscreen = true;
+        } else if arg == "--bench" {
+            args.bench_frames.get_or_insert(DEFAULT_BENCH_FRAMES);
+            args.rom = it.next().map(PathBuf::from);
+        } else if arg == "--frames" {
+            match it.next().and_then(|n| n.to_string_lossy().parse().ok()) {
+                Some(frames) => args.bench_frames = Some(frames),
+                None => error!("`--frames` needs a number of frames"),
+            }
+        } else if arg == "--seed" {
+            match it.next().and_then(|n| n.to_string_lossy().parse().ok()) {
+                Some(seed) => args.seed = Some(seed),
+                None => error!("`--seed` needs a number"),
+            }
+        } else if args.rom.is_none() {
+            args.rom = Some(PathBuf::from(arg));
+        } else {
+            error!("Unexpected argument: {}", arg.to_string_lossy());
+        }
+    }
+    args
+}
+
 pub fn main() -> Result<()> {
-    let config = match load_config() {
-        Ok(config) => config,
-        Err(err) => {
+    let args = parse_args();
+
+    let mut persistent_state = load_persistent_state()?;
+
+    let config = load_config(persistent_state.active_profile.as_deref())
+        .or_else(|err| {
+            error!("Load profile failed: {err}");
+            persistent_state.active_profile = None;
+            load_config(None)
+        })
+        .unwrap_or_else(|err| {
             error!("Load config failed: {err}");
             config::Config::default()
-        }
-    };
+        });
+
+    if let Some(frames) = args.bench_frames {
+        let rom = args
+            .rom
+            .ok_or_else(|| anyhow!("`--bench` needs a ROM file"))?;
+        return run_bench(&rom, frames, args.seed, &config);
+    }
+
+    let emulator = args
+        .rom
+        .and_then(|path| match Emulator::try_new(&path, &config, None) {
+            Ok(mut emulator) => {
+                if let Some(seed) = args.seed {
+                    emulator.reset_with_seed(seed);
+                }
+                persistent_state.add_recent(&path);
+                Some(emulator)
+            }
+            Err(err) => {
+                error!("Failed to open `{}`: {err}", path.display());
+                None
+            }
+        });
 
     let mut app = App::new();
     app.insert_resource(WindowDescriptor {
-        title: "MERU".to_string(),
-        resizable: false,
-        present_mode: PresentMode::Fifo,
+        title: WINDOW_TITLE.to_string(),
+        resizable: config.resizable_window,
+        present_mode: config.vsync.present_mode(),
         width: menu::MENU_WIDTH as f32,
         height: menu::MENU_HEIGHT as f32,
+        mode: if args.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        },
+        position: persistent_state
+            .window_position
+            .map_or(WindowPosition::Automatic, |(x, y)| {
+                WindowPosition::At(Vec2::new(x as f32, y as f32))
+            }),
         ..Default::default()
     })
-    .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
+    .insert_resource(ClearColor(background_color(&config)))
     .init_resource::<UiState>()
-    .init_resource::<FullscreenState>()
+    .insert_resource(FullscreenState(args.fullscreen))
+    .init_resource::<ModalOpen>()
+    .init_resource::<Muted>()
     .insert_resource(Msaa { samples: 4 })
     .insert_resource(bevy::log::LogSettings {
         level: bevy::utils::tracing::Level::WARN,
@@ -57,28 +153,94 @@ pub fn main() -> Result<()> {
     .add_plugin(menu::MenuPlugin)
     .add_plugin(core::EmulatorPlugin)
     .add_plugin(rewinding::RewindingPlugin)
+    .add_plugin(splash::SplashPlugin)
     .add_plugin(FpsPlugin)
+    .add_plugin(audio_viz::AudioVizPlugin)
+    .add_plugin(input_overlay::InputOverlayPlugin)
+    .add_plugin(library::LibraryPlugin)
+    .add_plugin(display_filter::DisplayFilterPlugin)
+    .add_plugin(bezel::BezelPlugin)
+    .add_plugin(recording::RecordingPlugin)
     .add_plugin(MessagePlugin)
     .add_event::<WindowControlEvent>()
     .add_system(window_control_event)
+    .add_system(volume_system)
+    .add_system(present_mode_system)
+    .add_system(language_system)
+    .add_system(menu_theme_system)
+    .add_system(clear_color_system)
+    .add_system(config_reload_system)
+    .add_system(fullscreen_mode_system)
+    .init_resource::<Monitors>()
+    .add_system_set(SystemSet::on_update(AppState::Menu).with_system(monitor_list_system))
+    .add_system_to_stage(CoreStage::PreUpdate, modal_open_system)
+    .add_system(modal_dim_system)
+    .add_system(window_resizable_system)
+    .add_system(lock_aspect_on_resize_system)
+    .add_system(window_geometry_system)
     .insert_resource(LastClicked(0.0))
     .add_system(process_double_click)
     .add_startup_system(setup_audio.exclusive_system())
+    .add_system(audio_device_system.exclusive_system())
     .add_startup_system(setup)
     .add_startup_stage("single-startup", SystemStage::single_threaded())
-    .add_startup_system_to_stage("single-startup", set_window_icon);
+    .add_startup_system_to_stage("single-startup", set_window_icon)
+    .add_startup_system_to_stage("single-startup", clamp_window_position);
+
+    #[cfg(feature = "link-cable")]
+    app.add_plugin(crate::link::LinkCablePlugin);
 
-    app.add_state(AppState::Menu);
+    app.add_state(if emulator.is_some() {
+        AppState::Running
+    } else if config.show_splash {
+        AppState::Splash
+    } else {
+        AppState::Menu
+    });
 
+    if let Some(emulator) = emulator {
+        app.insert_resource(emulator);
+    }
+    // Before the splash screen shows any text
+    i18n::set_language(&config.language);
     app.insert_resource(config);
-    app.insert_resource(load_persistent_state()?);
+    app.insert_resource(persistent_state);
+    app.insert_resource(RngSeed(args.seed));
 
     app.run();
     Ok(())
 }
 
+/// Run `frames` frames of the ROM as fast as possible without a window,
+/// and print the speed as JSON
+fn run_bench(rom: &Path, frames: usize, seed: Option<u64>, config: &config::Config) -> Result<()> {
+    let mut emulator = Emulator::try_new(rom, config, None)?;
+    emulator.set_benchmark();
+    if let Some(seed) = seed {
+        emulator.reset_with_seed(seed);
+    }
+
+    let start = std::time::Instant::now();
+    for _ in 0..frames {
+        emulator.step_frame(false, config);
+    }
+    let seconds = start.elapsed().as_secs_f64();
+
+    let report = serde_json::json!({
+        "core": emulator.core.core_info().abbrev,
+        "game": emulator.game_name,
+        "frames": emulator.frames(),
+        "seconds": seconds,
+        "fps": emulator.frames() as f64 / seconds,
+    });
+    println!("{report}");
+    Ok(())
+}
+
 #[derive(Component)]
-struct PixelFont;
+pub struct PixelFont;
+
+const PIXEL_FONT: &[u8] = include_bytes!("../assets/fonts/x12y16pxMaruMonica.ttf");
 
 fn setup(
     mut commands: Commands,
@@ -90,17 +252,17 @@ fn setup(
 
     let ctx = egui_ctx.ctx_mut();
 
-    let mut style = (*ctx.style()).clone();
-
-    for style in style.text_styles.iter_mut() {
-        style.1.size *= 2.0;
+    // egui's own fonts lack the glyphs of some translations
+    let mut font_defs = egui::FontDefinitions::default();
+    font_defs
+        .font_data
+        .insert("pixel".to_string(), egui::FontData::from_static(PIXEL_FONT));
+    for family in font_defs.families.values_mut() {
+        family.push("pixel".to_string());
     }
+    ctx.set_fonts(font_defs);
 
-    ctx.set_style(style);
-
-    let pixel_font =
-        Font::try_from_bytes(include_bytes!("../assets/fonts/x12y16pxMaruMonica.ttf").to_vec())
-            .unwrap();
+    let pixel_font = Font::try_from_bytes(PIXEL_FONT.to_vec()).unwrap();
 
     commands
         .spawn()
@@ -137,19 +299,355 @@ fn set_window_icon(windows: NonSend<bevy::winit::WinitWindows>) {
 #[cfg(not(target_os = "windows"))]
 fn set_window_icon() {}
 
-pub fn setup_audio(world: &mut World) {
-    let (stream, stream_handle) =
-        rodio::OutputStream::try_default().expect("No audio output device available");
+/// Move the window back onto the primary monitor when the remembered position
+/// is on no monitor anymore (e.g. the monitor was disconnected)
+fn clamp_window_position(windows: NonSend<bevy::winit::WinitWindows>) {
+    use winit::dpi::PhysicalPosition;
 
-    let sink = rodio::Sink::try_new(&stream_handle).expect("Failed to create audio sink");
+    let primary = windows
+        .get_window(bevy::window::WindowId::primary())
+        .unwrap();
 
-    world.insert_non_send_resource(stream);
-    world.insert_resource(stream_handle);
+    let pos = if let Ok(pos) = primary.outer_position() {
+        pos
+    } else {
+        return;
+    };
+
+    let on_screen = primary.available_monitors().any(|monitor| {
+        let mpos = monitor.position();
+        let msize = monitor.size();
+        pos.x >= mpos.x
+            && pos.y >= mpos.y
+            && pos.x < mpos.x + msize.width as i32
+            && pos.y < mpos.y + msize.height as i32
+    });
+    if on_screen {
+        return;
+    }
+
+    if let Some(monitor) = primary
+        .primary_monitor()
+        .or_else(|| primary.available_monitors().next())
+    {
+        let mpos = monitor.position();
+        let msize = monitor.size();
+        let wsize = primary.outer_size();
+        let x = mpos.x + (msize.width.saturating_sub(wsize.width) / 2) as i32;
+        let y = mpos.y + (msize.height.saturating_sub(wsize.height) / 2) as i32;
+        primary.set_outer_position(PhysicalPosition::new(x, y));
+    }
+}
+
+/// Names of the audio output devices of the default host
+pub fn audio_output_devices() -> Vec<String> {
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+    match rodio::cpal::default_host().output_devices() {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(err) => {
+            error!("Failed to list audio devices: {err}");
+            vec![]
+        }
+    }
+}
+
+fn open_audio_output(
+    device_name: Option<&str>,
+) -> Result<(rodio::OutputStream, rodio::OutputStreamHandle, String)> {
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+    let host = rodio::cpal::default_host();
+    let device = if let Some(device_name) = device_name {
+        host.output_devices()?
+            .find(|device| device.name().map_or(false, |name| name == device_name))
+    } else {
+        host.default_output_device()
+    }
+    .ok_or_else(|| anyhow!("No audio output device available"))?;
+
+    let name = device.name()?;
+    let (stream, stream_handle) = rodio::OutputStream::try_from_device(&device)?;
+    Ok((stream, stream_handle, name))
+}
+
+/// Current audio output. Without a device, audio goes to an idle sink.
+pub struct AudioDevice {
+    /// Device requested by the config when the output was opened, `None` for the default one
+    selected: Option<String>,
+    /// Device actually playing
+    opened: Option<String>,
+    next_poll: f64,
+}
+
+impl AudioDevice {
+    pub fn is_available(&self) -> bool {
+        self.opened.is_some()
+    }
+}
+
+/// Interval to check whether the audio device is still there
+const AUDIO_DEVICE_POLL_INTERVAL: f64 = 5.0;
+
+/// Open the configured audio device, falling back to the default one, and
+/// replace the sink. Returns the name of the opened device.
+fn reopen_audio(world: &mut World, selected: Option<String>) -> Option<String> {
+    let output = open_audio_output(selected.as_deref()).or_else(|err| {
+        if selected.is_some() {
+            error!("Failed to open audio device: {err}");
+            open_audio_output(None)
+        } else {
+            Err(err)
+        }
+    });
+
+    let (sink, opened) = match output {
+        Ok((stream, stream_handle, name)) => {
+            let sink = rodio::Sink::try_new(&stream_handle).ok();
+            world.insert_non_send_resource(stream);
+            world.insert_resource(stream_handle);
+            match sink {
+                Some(sink) => (sink, Some(name)),
+                None => (rodio::Sink::new_idle().0, None),
+            }
+        }
+        Err(err) => {
+            error!("Failed to open audio device: {err}");
+            world.remove_non_send_resource::<rodio::OutputStream>();
+            world.remove_resource::<rodio::OutputStreamHandle>();
+            (rodio::Sink::new_idle().0, None)
+        }
+    };
+
+    let volume = world.resource::<config::Config>().volume.clamp(0.0, 1.0);
+    let muted = world.get_resource::<Muted>().map_or(false, |muted| muted.0);
+    sink.set_volume(if muted { 0.0 } else { volume });
     world.insert_resource(sink);
+
+    world.insert_resource(AudioDevice {
+        selected,
+        opened: opened.clone(),
+        next_poll: 0.0,
+    });
+    opened
+}
+
+pub fn setup_audio(world: &mut World) {
+    let selected = world.resource::<config::Config>().audio_device.clone();
+    if reopen_audio(world, selected).is_none() {
+        error!("Starting without audio");
+    }
+}
+
+/// Re-open the audio output when the selected device changes or disappears
+fn audio_device_system(world: &mut World) {
+    let now = world.resource::<Time>().seconds_since_startup();
+    let selected = world.resource::<config::Config>().audio_device.clone();
+
+    let device = world.resource::<AudioDevice>();
+    let selection_changed = device.selected != selected;
+    if !selection_changed && now < device.next_poll {
+        return;
+    }
+    let prev = device.opened.clone();
+
+    if !selection_changed {
+        world.resource_mut::<AudioDevice>().next_poll = now + AUDIO_DEVICE_POLL_INTERVAL;
+
+        // Listing devices is slow on some hosts, so this is only polled occasionally
+        if prev
+            .as_ref()
+            .map_or(false, |name| audio_output_devices().contains(name))
+        {
+            return;
+        }
+    }
+
+    let opened = reopen_audio(world, selected);
+    world.resource_mut::<AudioDevice>().next_poll = now + AUDIO_DEVICE_POLL_INTERVAL;
+
+    let msg = match (&prev, &opened) {
+        (_, Some(name)) if selection_changed || prev.as_ref() != Some(name) => {
+            i18n::trf("Audio device: {}", &[name])
+        }
+        (Some(_), None) => i18n::tr("Audio device lost").to_string(),
+        _ => return,
+    };
+    world
+        .resource_mut::<Events<ShowMessage>>()
+        .send(ShowMessage(msg));
+}
+
+/// Silences the audio without touching the configured volume
+#[derive(Default)]
+pub struct Muted(pub bool);
+
+fn volume_system(
+    config: Res<config::Config>,
+    muted: Res<Muted>,
+    audio_sink: Res<rodio::Sink>,
+    app_state: Res<State<AppState>>,
+    mut prev: Local<Option<(f32, bool)>>,
+    mut message_event: EventWriter<ShowMessage>,
+) {
+    let volume = config.volume.clamp(0.0, 1.0);
+    if *prev == Some((volume, muted.0)) {
+        return;
+    }
+
+    if let Some((prev_volume, _)) = *prev {
+        if prev_volume != volume && app_state.current() == &AppState::Running {
+            message_event.send(ShowMessage(i18n::trf(
+                "Volume: {}%",
+                &[&(volume * 100.0).round()],
+            )));
+        }
+    }
+    *prev = Some((volume, muted.0));
+
+    audio_sink.set_volume(if muted.0 { 0.0 } else { volume });
+}
+
+/// Interval of checking the config file for changes
+const CONFIG_WATCH_INTERVAL: f64 = 1.0;
+
+#[derive(Default)]
+struct ConfigWatch {
+    last_check: f64,
+    modified: Option<SystemTime>,
+}
+
+/// Apply edits of the config file made while running.
+/// A broken file keeps the current config.
+fn config_reload_system(
+    time: Res<Time>,
+    mut config: ResMut<config::Config>,
+    mut watch: Local<ConfigWatch>,
+    emulator: Option<ResMut<Emulator>>,
+    app_state: Res<State<AppState>>,
+    mut message_event: EventWriter<ShowMessage>,
+    mut window_control_event: EventWriter<WindowControlEvent>,
+) {
+    let now = time.seconds_since_startup();
+    if now - watch.last_check < CONFIG_WATCH_INTERVAL {
+        return;
+    }
+    watch.last_check = now;
+
+    let modified = config.file_modified();
+    let prev = std::mem::replace(&mut watch.modified, modified);
+    if prev.is_none() || prev == modified {
+        return;
+    }
+
+    let mut new_config = match config::load_config(config.profile.as_deref()) {
+        Ok(new_config) => new_config,
+        Err(err) => {
+            error!("Failed to reload config: {err}");
+            message_event.send(ShowMessage(i18n::trf(
+                "Failed to reload config: {}",
+                &[&err],
+            )));
+            return;
+        }
+    };
+    if let Some((abbrev, game_name)) = config.game_override_target() {
+        new_config.apply_game_override(abbrev, game_name);
+    }
+
+    // Saved by MERU itself
+    if new_config == *config {
+        return;
+    }
+
+    let rescale = new_config.scaling != config.scaling;
+    *config = new_config;
+    if let Some(mut emulator) = emulator {
+        emulator.core.set_config(&config);
+    }
+    if rescale && app_state.current() == &AppState::Running {
+        window_control_event.send(WindowControlEvent::Restore);
+    }
+    message_event.send(ShowMessage(i18n::tr("Config reloaded").to_string()));
+}
+
+fn language_system(config: Res<config::Config>) {
+    if config.is_changed() {
+        i18n::set_language(&config.language);
+    }
+}
+
+/// egui style of the menu, with the text scaled up for the pixel font
+fn menu_style(theme: MenuTheme, accent: [u8; 3]) -> egui::Style {
+    let mut style = egui::Style::default();
+
+    for style in style.text_styles.iter_mut() {
+        style.1.size *= 2.0;
+    }
+
+    style.visuals = match theme {
+        MenuTheme::Dark => egui::Visuals::dark(),
+        MenuTheme::Light => egui::Visuals::light(),
+        MenuTheme::Custom => {
+            let [r, g, b] = accent;
+            let accent = egui::Color32::from_rgb(r, g, b);
+            let mut visuals = egui::Visuals::dark();
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+            visuals.widgets.hovered.bg_stroke.color = accent;
+            visuals.widgets.active.bg_fill = accent;
+            visuals
+        }
+    };
+
+    style
+}
+
+fn menu_theme_system(
+    config: Res<config::Config>,
+    mut egui_ctx: ResMut<EguiContext>,
+    mut applied: Local<Option<(MenuTheme, [u8; 3])>>,
+) {
+    let theme = (config.menu_theme, config.menu_accent_color);
+    if *applied != Some(theme) {
+        egui_ctx.ctx_mut().set_style(menu_style(theme.0, theme.1));
+        *applied = Some(theme);
+    }
+}
+
+fn background_color(config: &config::Config) -> Color {
+    let [r, g, b] = config.background_color;
+    Color::rgb_u8(r, g, b)
+}
+
+fn clear_color_system(config: Res<config::Config>, mut clear_color: ResMut<ClearColor>) {
+    let color = background_color(&config);
+    if clear_color.0 != color {
+        clear_color.0 = color;
+    }
+}
+
+fn present_mode_system(
+    config: Res<config::Config>,
+    uncapped: Res<hotkey::Uncapped>,
+    mut windows: ResMut<Windows>,
+) {
+    // Vsync would hold back the emulation while the frame limiter is off
+    let present_mode = if uncapped.0 {
+        PresentMode::Immediate
+    } else {
+        config.vsync.present_mode()
+    };
+
+    let window = windows.get_primary_mut().unwrap();
+    if window.present_mode() != present_mode {
+        window.set_present_mode(present_mode);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AppState {
+    Splash,
     Menu,
     Running,
     Rewinding,
@@ -158,6 +656,8 @@ pub enum AppState {
 #[derive(Default)]
 pub struct UiState {
     pub state_save_slot: usize,
+    /// Index of the preset last chosen by `HotKey::CycleScale`
+    pub scale_preset: Option<usize>,
 }
 
 #[derive(Component)]
@@ -166,17 +666,200 @@ pub struct ScreenSprite;
 #[derive(Default)]
 pub struct FullscreenState(pub bool);
 
+/// Tracks whether a modal dialog is on screen.
+/// Modals call `show` every frame they are drawn, and the flag drops once they stop.
+#[derive(Default)]
+pub struct ModalOpen {
+    shown: bool,
+    open: bool,
+}
+
+impl ModalOpen {
+    pub fn show(&mut self) {
+        self.shown = true;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+fn modal_open_system(mut modal_open: ResMut<ModalOpen>) {
+    modal_open.open = std::mem::take(&mut modal_open.shown);
+}
+
+/// Darkens the game while a dialog is shown over it
+#[derive(Component)]
+struct ModalDim;
+
+/// Above the bezel and the FPS display, below the messages
+const MODAL_DIM_Z: f32 = 1.5;
+
+fn modal_dim_system(
+    mut commands: Commands,
+    config: Res<config::Config>,
+    modal_open: Res<ModalOpen>,
+    app_state: Res<State<AppState>>,
+    camera: Query<&TiledCamera>,
+    mut dim: Query<(Entity, &mut Sprite), With<ModalDim>>,
+) {
+    let show =
+        app_state.current() == &AppState::Running && modal_open.is_open() && config.modal_dim > 0.0;
+    let size = camera
+        .get_single()
+        .map_or(Vec2::ZERO, |camera| camera.tile_count.as_vec2());
+    let color = Color::rgba(0.0, 0.0, 0.0, config.modal_dim.clamp(0.0, 1.0));
+
+    match (dim.get_single_mut(), show) {
+        (Ok((_, mut sprite)), true) => {
+            sprite.color = color;
+            sprite.custom_size = Some(size);
+        }
+        (Ok((entity, _)), false) => commands.entity(entity).despawn(),
+        (Err(_), true) => {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, MODAL_DIM_Z),
+                    ..Default::default()
+                })
+                .insert(ModalDim);
+        }
+        (Err(_), false) => {}
+    }
+}
+
+/// Names of the connected monitors, in the order of `Config::fullscreen_monitor`
+#[derive(Default)]
+pub struct Monitors(pub Vec<String>);
+
+fn monitor_list_system(winit_windows: NonSend<WinitWindows>, mut monitors: ResMut<Monitors>) {
+    let window = if let Some(window) = winit_windows.get_window(WindowId::primary()) {
+        window
+    } else {
+        return;
+    };
+
+    let names = window
+        .available_monitors()
+        .enumerate()
+        .map(|(i, monitor)| {
+            let name = monitor.name().unwrap_or_else(|| "Unknown".to_string());
+            format!("{}: {name}", i + 1)
+        })
+        .collect::<Vec<_>>();
+
+    if monitors.0 != names {
+        monitors.0 = names;
+    }
+}
+
+/// Monitor to go fullscreen on.
+/// A configured monitor that is no longer connected falls back to the primary one and is forgotten.
+fn fullscreen_monitor(
+    config: &mut config::Config,
+    winit_windows: &WinitWindows,
+) -> Option<winit::monitor::MonitorHandle> {
+    let window = winit_windows.get_window(WindowId::primary())?;
+
+    if let Some(index) = config.fullscreen_monitor {
+        if let Some(monitor) = window.available_monitors().nth(index) {
+            return Some(monitor);
+        }
+        warn!(
+            "Monitor #{} is not connected, using the primary monitor",
+            index + 1
+        );
+        config.fullscreen_monitor = None;
+    }
+
+    window
+        .primary_monitor()
+        .or_else(|| window.current_monitor())
+}
+
+/// Move the window onto the fullscreen monitor and make it fullscreen.
+/// Exclusive fullscreen needs a video mode of the monitor, so without one it falls back to borderless.
+fn enter_fullscreen(
+    window: &mut Window,
+    config: &mut config::Config,
+    winit_windows: &WinitWindows,
+    message_event: &mut EventWriter<ShowMessage>,
+) {
+    let monitor = fullscreen_monitor(config, winit_windows);
+
+    let mode = match config.fullscreen_mode {
+        config::FullscreenMode::Borderless => WindowMode::BorderlessFullscreen,
+        config::FullscreenMode::Exclusive => {
+            let has_video_mode = monitor
+                .as_ref()
+                .map_or(false, |monitor| monitor.video_modes().next().is_some());
+
+            if has_video_mode {
+                WindowMode::Fullscreen
+            } else {
+                error!("No video mode for exclusive fullscreen, falling back to borderless");
+                message_event.send(ShowMessage(
+                    i18n::tr("Exclusive fullscreen is not supported, using borderless").to_string(),
+                ));
+                WindowMode::BorderlessFullscreen
+            }
+        }
+    };
+
+    // Fullscreen goes to the monitor the window is on
+    if window.mode() != WindowMode::Windowed {
+        window.set_mode(WindowMode::Windowed);
+    }
+    if let Some(monitor) = monitor {
+        let pos = monitor.position();
+        window.set_position(IVec2::new(pos.x, pos.y));
+    }
+    window.set_mode(mode);
+}
+
+/// Redo the fullscreen when its mode or monitor is changed
+fn fullscreen_mode_system(
+    mut windows: ResMut<Windows>,
+    winit_windows: NonSend<WinitWindows>,
+    fullscreen_state: Res<FullscreenState>,
+    mut config: ResMut<config::Config>,
+    mut message_event: EventWriter<ShowMessage>,
+    mut last_setting: Local<Option<(config::FullscreenMode, Option<usize>)>>,
+) {
+    if *last_setting == Some((config.fullscreen_mode, config.fullscreen_monitor)) {
+        return;
+    }
+
+    if fullscreen_state.0 {
+        let window = windows.get_primary_mut().unwrap();
+        enter_fullscreen(window, &mut config, &winit_windows, &mut message_event);
+    }
+
+    *last_setting = Some((config.fullscreen_mode, config.fullscreen_monitor));
+}
+
 pub enum WindowControlEvent {
     ToggleFullscreen,
     ChangeScale(usize),
+    /// Change to the largest scale fitting the monitor
+    FitScale,
     Restore,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn window_control_event(
     mut windows: ResMut<Windows>,
+    winit_windows: NonSend<WinitWindows>,
     mut event: EventReader<WindowControlEvent>,
+    mut message_event: EventWriter<ShowMessage>,
     mut fullscreen_state: ResMut<FullscreenState>,
     mut config: ResMut<config::Config>,
+    mut persistent_state: ResMut<PersistentState>,
     app_state: Res<State<AppState>>,
     emulator: Option<Res<Emulator>>,
 ) {
@@ -189,9 +872,12 @@ fn window_control_event(
                 fullscreen_state.0 = !fullscreen_state.0;
 
                 if fullscreen_state.0 {
-                    window.set_mode(WindowMode::BorderlessFullscreen);
+                    enter_fullscreen(window, &mut config, &winit_windows, &mut message_event);
                 } else {
                     window.set_mode(WindowMode::Windowed);
+                    if let Some((x, y)) = persistent_state.window_position {
+                        window.set_position(IVec2::new(x, y));
+                    }
                 }
 
                 if let Some(emulator) = emulator.as_deref() {
@@ -201,12 +887,54 @@ fn window_control_event(
                         app_state.current(),
                         window,
                         fullscreen_state.0,
-                        config.scaling,
+                        &mut config,
+                        &winit_windows,
+                        persistent_state.window_size,
                     );
                 }
             }
-            WindowControlEvent::ChangeScale(scale) => {
-                config.scaling = *scale;
+            WindowControlEvent::ChangeScale(_) | WindowControlEvent::FitScale => {
+                config.scaling = match event {
+                    WindowControlEvent::ChangeScale(scale) => match emulator.as_deref() {
+                        Some(emulator) => {
+                            let (min_scale, max_scale) =
+                                scale_bounds(emulator, &config, &winit_windows);
+                            if *scale < min_scale {
+                                message_event.send(ShowMessage(i18n::trf(
+                                    "Scale: {}x (minimum window size)",
+                                    &[&min_scale],
+                                )));
+                                min_scale
+                            } else if *scale > max_scale {
+                                message_event.send(ShowMessage(i18n::trf(
+                                    "Scale: {}x (maximum window size)",
+                                    &[&max_scale],
+                                )));
+                                max_scale
+                            } else {
+                                *scale
+                            }
+                        }
+                        None => *scale,
+                    },
+                    _ => match emulator.as_deref() {
+                        Some(emulator) => {
+                            // Leave room for the taskbar and window decorations
+                            let scale = fit_scale(emulator, &config, &winit_windows, 0.9);
+                            message_event
+                                .send(ShowMessage(i18n::trf("Scale: fit ({}x)", &[&scale])));
+                            scale
+                        }
+                        None => continue,
+                    },
+                };
+                if let Some(emulator) = emulator.as_deref() {
+                    config
+                        .core_scaling
+                        .insert(emulator.core.core_info().abbrev.to_string(), config.scaling);
+                }
+                // An explicit scale overrides the size chosen by resizing
+                persistent_state.window_size = None;
                 if running {
                     let window = windows.get_primary_mut().unwrap();
                     restore_window(
@@ -214,7 +942,9 @@ fn window_control_event(
                         app_state.current(),
                         window,
                         fullscreen_state.0,
-                        config.scaling,
+                        &mut config,
+                        &winit_windows,
+                        None,
                     );
                 }
             }
@@ -225,27 +955,143 @@ fn window_control_event(
                     app_state.current(),
                     window,
                     fullscreen_state.0,
-                    config.scaling,
+                    &mut config,
+                    &winit_windows,
+                    persistent_state.window_size,
                 );
             }
         }
     }
 }
 
+fn window_resizable_system(mut windows: ResMut<Windows>, config: Res<config::Config>) {
+    let window = windows.get_primary_mut().unwrap();
+    if window.resizable() != config.resizable_window {
+        window.set_resizable(config.resizable_window);
+    }
+}
+
+fn lock_aspect_on_resize_system(
+    mut windows: ResMut<Windows>,
+    mut resized: EventReader<WindowResized>,
+    mut last_size: Local<Option<Vec2>>,
+    config: Res<config::Config>,
+    fullscreen_state: Res<FullscreenState>,
+    app_state: Res<State<AppState>>,
+    emulator: Option<Res<Emulator>>,
+) {
+    for ev in resized.iter() {
+        if ev.id != WindowId::primary() {
+            continue;
+        }
+
+        let size = Vec2::new(ev.width, ev.height);
+        let prev_size = last_size.replace(size).unwrap_or(size);
+
+        if !config.resizable_window
+            || !config.lock_aspect_on_resize
+            || fullscreen_state.0
+            || app_state.current() == &AppState::Menu
+        {
+            continue;
+        }
+
+        let emulator = if let Some(emulator) = &emulator {
+            emulator
+        } else {
+            continue;
+        };
+
+        let fb = emulator.core.frame_buffer();
+        if fb.width == 0 || fb.height == 0 {
+            continue;
+        }
+        let aspect = fb.width as f32 * emulator.pixel_aspect(&config) / fb.height as f32;
+        let aspect = if config.screen_rotation.is_sideways() {
+            1.0 / aspect
+        } else {
+            aspect
+        };
+
+        // Keep the dimension the user is dragging and fit the other one
+        let (width, height) = if (size.x - prev_size.x).abs() >= (size.y - prev_size.y).abs() {
+            (size.x, (size.x / aspect).round())
+        } else {
+            ((size.y * aspect).round(), size.y)
+        };
+
+        if (width - size.x).abs() >= 1.0 || (height - size.y).abs() >= 1.0 {
+            let window = windows.get_primary_mut().unwrap();
+            window.set_resolution(width, height);
+            *last_size = Some(Vec2::new(width, height));
+        }
+    }
+}
+
+/// Remember the position and the user chosen size of the window in windowed mode
+fn window_geometry_system(
+    mut moved: EventReader<WindowMoved>,
+    mut resized: EventReader<WindowResized>,
+    mut persistent_state: ResMut<PersistentState>,
+    fullscreen_state: Res<FullscreenState>,
+    config: Res<config::Config>,
+    app_state: Res<State<AppState>>,
+    emulator: Option<Res<Emulator>>,
+) {
+    for ev in moved.iter() {
+        if ev.id == WindowId::primary() && !fullscreen_state.0 {
+            persistent_state.window_position = Some((ev.position.x, ev.position.y));
+        }
+    }
+
+    for ev in resized.iter() {
+        if ev.id != WindowId::primary()
+            || fullscreen_state.0
+            || app_state.current() != &AppState::Running
+        {
+            continue;
+        }
+        let emulator = if let Some(emulator) = &emulator {
+            emulator
+        } else {
+            continue;
+        };
+
+        // Sizes computed from the scaling are not remembered,
+        // so that they keep following the scaling and the running core
+        let (width, height) = window_size(emulator, app_state.current(), &config);
+        persistent_state.window_size =
+            if (ev.width - width).abs() >= 1.0 || (ev.height - height).abs() >= 1.0 {
+                Some((ev.width, ev.height))
+            } else {
+                None
+            };
+    }
+}
+
 struct LastClicked(f64);
 
 fn process_double_click(
     time: Res<Time>,
+    config: Res<config::Config>,
+    mut egui_ctx: ResMut<EguiContext>,
     mut last_clicked: ResMut<LastClicked>,
     mut mouse_button_event: EventReader<MouseButtonInput>,
     mut window_control_event: EventWriter<WindowControlEvent>,
 ) {
+    // Clicks on the menu and other egui windows are meant for their widgets
+    let on_egui = egui_ctx.ctx_mut().is_pointer_over_area();
+
     for ev in mouse_button_event.iter() {
+        if !config.double_click_fullscreen || on_egui {
+            continue;
+        }
+
         if ev.button == MouseButton::Left && ev.state == ButtonState::Pressed {
             let cur = time.seconds_since_startup();
             let diff = cur - last_clicked.0;
 
-            if diff < 0.25 {
+            if diff < config.double_click_seconds {
                 window_control_event.send(WindowControlEvent::ToggleFullscreen);
                 last_clicked.0 = cur - 1.0;
             } else {
@@ -255,21 +1101,125 @@ fn process_double_click(
     }
 }
 
+/// Window size computed from the scaling
+fn window_size(emulator: &Emulator, app_state: &AppState, config: &config::Config) -> (f32, f32) {
+    if matches!(app_state, AppState::Menu) {
+        (MENU_WIDTH as f32, MENU_HEIGHT as f32)
+    } else {
+        game_window_size(emulator, config, config.scaling)
+    }
+}
+
+fn game_window_size(emulator: &Emulator, config: &config::Config, scale: usize) -> (f32, f32) {
+    let scale = scale as f32;
+    let width =
+        (emulator.core.frame_buffer().width as f32 * emulator.pixel_aspect(config) * scale).round();
+    let height = emulator.core.frame_buffer().height as f32 * scale;
+    if config.screen_rotation.is_sideways() {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
+/// Smallest and largest sizes of the game window, the largest capped to the monitor
+fn window_size_bounds(
+    config: &config::Config,
+    winit_windows: &WinitWindows,
+) -> ((f32, f32), (f32, f32)) {
+    let min = (
+        config.min_window_size[0] as f32,
+        config.min_window_size[1] as f32,
+    );
+    let mut max = config
+        .max_window_size
+        .map_or((f32::INFINITY, f32::INFINITY), |[width, height]| {
+            (width as f32, height as f32)
+        });
+    if let Some(monitor) = winit_windows
+        .get_window(WindowId::primary())
+        .and_then(|window| window.current_monitor())
+    {
+        let monitor_size = monitor.size().to_logical::<f32>(monitor.scale_factor());
+        max = (
+            max.0.min(monitor_size.width),
+            max.1.min(monitor_size.height),
+        );
+    }
+    (min, max)
+}
+
+/// Smallest and largest scales keeping the game window within its size bounds.
+/// The largest wins when they conflict.
+fn scale_bounds(
+    emulator: &Emulator,
+    config: &config::Config,
+    winit_windows: &WinitWindows,
+) -> (usize, usize) {
+    let ((min_width, min_height), (max_width, max_height)) =
+        window_size_bounds(config, winit_windows);
+    let (width, height) = game_window_size(emulator, config, 1);
+    if width < 1.0 || height < 1.0 {
+        return (1, usize::MAX);
+    }
+    let max_scale = ((max_width / width).min(max_height / height).floor() as usize).max(1);
+    let min_scale = ((min_width / width).max(min_height / height).ceil() as usize).max(1);
+    (min_scale.min(max_scale), max_scale)
+}
+
+/// Largest scale at which the game window takes at most `fill` of the monitor it is on
+pub fn fit_scale(
+    emulator: &Emulator,
+    config: &config::Config,
+    winit_windows: &WinitWindows,
+    fill: f32,
+) -> usize {
+    let monitor = if let Some(monitor) = winit_windows
+        .get_window(WindowId::primary())
+        .and_then(|window| window.current_monitor())
+    {
+        monitor
+    } else {
+        return config.scaling;
+    };
+    let monitor_size = monitor.size().to_logical::<f32>(monitor.scale_factor());
+
+    let (width, height) = window_size(emulator, &AppState::Running, config);
+    if width < 1.0 || height < 1.0 {
+        return config.scaling;
+    }
+    let scale = config.scaling as f32;
+    let fit = (monitor_size.width * fill / (width / scale))
+        .min(monitor_size.height * fill / (height / scale));
+    (fit.floor() as usize).max(1)
+}
+
 fn restore_window(
     emulator: &Emulator,
     app_state: &AppState,
     window: &mut Window,
     fullscreen: bool,
-    scaling: usize,
+    config: &mut config::Config,
+    winit_windows: &WinitWindows,
+    remembered_size: Option<(f32, f32)>,
 ) {
-    let (width, height) = if matches!(app_state, AppState::Menu) {
-        (MENU_WIDTH as f32, MENU_HEIGHT as f32)
-    } else {
-        let scale = scaling as f32;
-        (
-            emulator.core.frame_buffer().width as f32 * scale,
-            emulator.core.frame_buffer().height as f32 * scale,
-        )
+    let in_game = !matches!(app_state, AppState::Menu);
+    let (width, height) = match remembered_size {
+        Some((width, height)) if in_game && config.resizable_window => {
+            let ((min_width, min_height), (max_width, max_height)) =
+                window_size_bounds(config, winit_windows);
+            (
+                width.max(min_width).min(max_width),
+                height.max(min_height).min(max_height),
+            )
+        }
+        _ if in_game => {
+            let (min_scale, max_scale) = scale_bounds(emulator, config, winit_windows);
+            // Keep the setting in line with the size actually used
+            config.scaling = config.scaling.clamp(min_scale, max_scale);
+            game_window_size(emulator, config, config.scaling)
+        }
+        _ => window_size(emulator, app_state, config),
     };
 
     if !fullscreen {
@@ -281,12 +1231,53 @@ struct FpsPlugin;
 
 impl Plugin for FpsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(AppState::Running).with_system(setup_fps_system))
+        app.init_resource::<FpsStats>()
+            .add_system_set(SystemSet::on_enter(AppState::Running).with_system(setup_fps_system))
             .add_system_set(SystemSet::on_exit(AppState::Running).with_system(exit_fps_system))
             .add_system_set(SystemSet::on_update(AppState::Running).with_system(fps_system));
     }
 }
 
+/// Session minimum/average/maximum FPS
+pub struct FpsStats {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: usize,
+}
+
+impl Default for FpsStats {
+    fn default() -> Self {
+        Self {
+            min: f64::INFINITY,
+            max: 0.0,
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl FpsStats {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn push(&mut self, fps: f64) {
+        self.min = self.min.min(fps);
+        self.max = self.max.max(fps);
+        self.sum += fps;
+        self.count += 1;
+    }
+
+    fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct FpsText;
 
@@ -334,15 +1325,52 @@ fn exit_fps_system(
     commands.entity(fps_text_bg.single()).despawn();
 }
 
-#[allow(clippy::type_complexity)]
+/// Core frames run per second, relative to the frame rate of the original hardware
+#[derive(Default)]
+struct SpeedMeter {
+    start: f64,
+    start_frames: usize,
+    speed: f64,
+}
+
+impl SpeedMeter {
+    const INTERVAL: f64 = 0.5;
+
+    fn restart(&mut self, now: f64, core_frames: usize) {
+        self.start = now;
+        self.start_frames = core_frames;
+    }
+
+    fn update(&mut self, now: f64, core_frames: usize, frame_rate: f64) -> f64 {
+        // A new game starts counting from zero again
+        if core_frames < self.start_frames {
+            self.restart(now, core_frames);
+        }
+
+        let elapsed = now - self.start;
+        if elapsed >= Self::INTERVAL {
+            let frames = (core_frames - self.start_frames) as f64;
+            self.speed = frames / elapsed / frame_rate * 100.0;
+            self.restart(now, core_frames);
+        }
+        self.speed
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn fps_system(
     config: Res<config::Config>,
     diagnostics: ResMut<Diagnostics>,
-    is_turbo: Res<hotkey::IsTurbo>,
+    (is_turbo, turbo_ramp): (Res<hotkey::IsTurbo>, Res<hotkey::TurboRamp>),
+    is_slow_motion: Res<hotkey::IsSlowMotion>,
+    paused: Res<hotkey::Paused>,
+    uncapped: Res<hotkey::Uncapped>,
     emulator: Option<Res<Emulator>>,
+    mut fps_stats: ResMut<FpsStats>,
+    (time, mut speed_meter): (Res<Time>, Local<SpeedMeter>),
     mut ps: ParamSet<(
         Query<(&mut Text, &mut Visibility, &mut Transform), With<FpsText>>,
-        Query<(&mut Visibility, &mut Transform), With<FpsTextBg>>,
+        Query<(&mut Sprite, &mut Visibility, &mut Transform), With<FpsTextBg>>,
     )>,
 ) {
     let emulator = if let Some(emulator) = emulator {
@@ -358,31 +1386,90 @@ fn fps_system(
     let (mut text, mut visibility, mut transform) = p0.single_mut();
     visibility.is_visible = config.show_fps;
     let fps_diag = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS).unwrap();
-    let fps = fps_diag.value().unwrap_or(0.0)
-        * if is_turbo.0 {
-            config.frame_skip_on_turbo as f64
-        } else {
-            1.0
-        };
-    let fps = format!("{fps:5.02}");
-    text.sections[0].value = fps.chars().take(5).collect();
-    *transform = Transform::from_xyz((screen_width / 2 - 30) as _, (screen_height / 2) as _, 2.0);
+    let slow_motion = is_slow_motion.0 && !is_turbo.0 && !uncapped.0;
+    let fps = if slow_motion {
+        // Paced by real time rather than by the display
+        emulator.core.frame_rate() * config.slow_motion_speed.clamp(0.05, 1.0) as f64
+    } else {
+        fps_diag.value().unwrap_or(0.0)
+            * if is_turbo.0 || turbo_ramp.is_active(&config) || uncapped.0 {
+                emulator.turbo_frames() as f64
+            } else {
+                1.0
+            }
+    };
+    if fps > 0.0 && !paused.0 {
+        fps_stats.push(fps);
+    }
+
+    let mut lines = vec![if paused.0 {
+        "PAUSED".to_string()
+    } else if uncapped.0 || slow_motion {
+        // Emulated speed relative to the original hardware, as in the SPD line
+        let speed = fps / emulator.core.frame_rate() * 100.0;
+        format!("{:4.0}%", speed.min(9999.0))
+    } else {
+        format!("{fps:5.02}").chars().take(5).collect::<String>()
+    }];
+    if paused.0 {
+        // Time spent paused doesn't count as slowdown
+        speed_meter.restart(time.seconds_since_startup(), emulator.core_frames());
+    } else if config.show_emulation_speed {
+        let speed = speed_meter.update(
+            time.seconds_since_startup(),
+            emulator.core_frames(),
+            emulator.core.frame_rate(),
+        );
+        lines.push(format!("SPD{:4.0}%", speed.min(9999.0)));
+    }
+    if config.show_fps_stats && fps_stats.count == 0 {
+        // No samples yet since the statistics were reset
+        for label in ["MIN", "AVG", "MAX"] {
+            lines.push(format!("{label}{:>5}", "---"));
+        }
+    } else if config.show_fps_stats {
+        lines.push(format!("MIN{:5.1}", fps_stats.min.min(999.9)));
+        lines.push(format!("AVG{:5.1}", fps_stats.average().min(999.9)));
+        lines.push(format!("MAX{:5.1}", fps_stats.max.min(999.9)));
+    }
+
+    let width = (lines.iter().map(|line| line.len()).max().unwrap() * 6 + 2) as f32;
+    let height = (lines.len() * 16) as f32;
+    let screen_width = screen_width as f32;
+    let screen_height = screen_height as f32;
+
+    // Top left corner of the counter
+    let left = match config.fps_position {
+        config::FpsPosition::TopLeft | config::FpsPosition::BottomLeft => -screen_width / 2.0,
+        config::FpsPosition::TopRight | config::FpsPosition::BottomRight => {
+            screen_width / 2.0 - width
+        }
+    };
+    let top = match config.fps_position {
+        config::FpsPosition::TopLeft | config::FpsPosition::TopRight => screen_height / 2.0,
+        config::FpsPosition::BottomLeft | config::FpsPosition::BottomRight => {
+            -screen_height / 2.0 + height
+        }
+    };
+
+    let [r, g, b] = config.fps_color;
+    text.sections[0].value = lines.join("\n");
+    text.sections[0].style.color = Color::rgb_u8(r, g, b);
+    *transform = Transform::from_xyz(left + 2.0, top, 2.0);
 
     let mut p1 = ps.p1();
-    let (mut visibility, mut transform) = p1.single_mut();
+    let (mut sprite, mut visibility, mut transform) = p1.single_mut();
     visibility.is_visible = config.show_fps;
-    *transform = Transform::from_xyz(
-        (screen_width / 2 - 16) as _,
-        (screen_height / 2 - 8) as _,
-        1.0,
-    );
+    sprite.custom_size = Some(Vec2::new(width, height));
+    *transform = Transform::from_xyz(left + width / 2.0, top - height / 2.0, 1.0);
 }
 
 struct MessagePlugin;
 
 impl Plugin for MessagePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(message_event_system.label("message_event"))
+        app.init_resource::<MessageLog>()
+            .add_system(message_event_system.label("message_event"))
             .add_system(message_update_system.after("message_event"))
             .add_event::<ShowMessage>();
     }
@@ -390,6 +1477,35 @@ impl Plugin for MessagePlugin {
 
 pub struct ShowMessage(pub String);
 
+const MESSAGE_LOG_LIMIT: usize = 50;
+
+/// Recent messages with the time they were shown, oldest first
+#[derive(Default)]
+pub struct MessageLog(VecDeque<(chrono::DateTime<chrono::Local>, String)>);
+
+impl MessageLog {
+    fn push(&mut self, msg: &str) {
+        self.0.push_back((chrono::Local::now(), msg.to_string()));
+        while self.0.len() > MESSAGE_LOG_LIMIT {
+            self.0.pop_front();
+        }
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &(chrono::DateTime<chrono::Local>, String)> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
 #[derive(Component)]
 struct MessageText {
     start: f64,
@@ -398,12 +1514,23 @@ struct MessageText {
 fn message_event_system(
     mut commands: Commands,
     time: Res<Time>,
+    config: Res<config::Config>,
     screen: Option<Res<GameScreen>>,
     images: Res<Assets<Image>>,
     mut event: EventReader<ShowMessage>,
+    mut message_log: ResMut<MessageLog>,
     pixel_font: Query<&Handle<Font>, With<PixelFont>>,
     mut messages: Query<(Entity, &Transform), With<MessageText>>,
 ) {
+    // Messages are logged even when there is no screen to show them on
+    let msgs = event
+        .iter()
+        .map(|ShowMessage(msg)| msg.clone())
+        .collect::<Vec<_>>();
+    for msg in &msgs {
+        message_log.push(msg);
+    }
+
     let image = if let Some(screen) = screen {
         images.get(&screen.0).unwrap()
     } else {
@@ -413,13 +1540,15 @@ fn message_event_system(
     let screen_height = image.size()[1] as f32;
 
     let pixel_font = pixel_font.single();
+    let font_size = config.message_font_size;
+    let line_height = font_size + 4.0;
 
-    for ShowMessage(msg) in event.iter() {
+    for msg in msgs {
         for (entity, trans) in messages.iter_mut() {
             use bevy_easings::*;
 
             commands.entity(entity).insert(trans.ease_to(
-                Transform::from_xyz(0.0, 20.0, 0.0) * *trans,
+                Transform::from_xyz(0.0, line_height, 0.0) * *trans,
                 EaseFunction::CubicInOut,
                 EasingType::Once {
                     duration: std::time::Duration::from_millis(100),
@@ -433,13 +1562,13 @@ fn message_event_system(
                     msg,
                     TextStyle {
                         font: pixel_font.clone(),
-                        font_size: 16.0,
+                        font_size,
                         color: Color::WHITE,
                     },
                 ),
                 transform: Transform::from_xyz(
                     -screen_width / 2.0 + 2.0,
-                    -screen_height / 2.0 + 20.0,
+                    -screen_height / 2.0 + line_height,
                     2.0,
                 ),
                 ..Default::default()
@@ -448,13 +1577,19 @@ fn message_event_system(
                 start: time.seconds_since_startup(),
             })
             .with_children(|parent| {
+                // The pixel font is half as wide as it is high, except for full-width glyphs
+                let columns = msg
+                    .chars()
+                    .map(|c| if c.is_ascii() { 1 } else { 2 })
+                    .sum::<usize>();
+                let width = screen_width.max(columns as f32 * font_size / 2.0 + 4.0);
                 parent.spawn_bundle(SpriteBundle {
                     sprite: Sprite {
                         color: Color::rgba(0.0, 0.0, 0.0, 0.75),
-                        custom_size: Some(Vec2::new(screen_width, 16.0)),
+                        custom_size: Some(Vec2::new(width, font_size)),
                         ..Default::default()
                     },
-                    transform: Transform::from_xyz(screen_width / 2.0 - 2.0, -8.0, -1.0),
+                    transform: Transform::from_xyz(width / 2.0 - 2.0, -font_size / 2.0, -1.0),
                     ..Default::default()
                 });
             });
@@ -464,10 +1599,11 @@ fn message_event_system(
 fn message_update_system(
     mut commands: Commands,
     time: Res<Time>,
+    config: Res<config::Config>,
     messages: Query<(Entity, &MessageText), With<MessageText>>,
 ) {
     for (entity, msg) in messages.iter() {
-        if time.seconds_since_startup() - msg.start > 3.0 {
+        if time.seconds_since_startup() - msg.start > config.message_duration_secs as f64 {
             commands.entity(entity).despawn_recursive();
         }
     }
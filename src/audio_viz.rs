@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use meru_interface::AudioSample;
+use std::{collections::VecDeque, f32::consts::PI};
+
+use crate::{app::AppState, config::Config, core::Emulator};
+
+const FFT_SIZE: usize = 512;
+const BAR_NUM: usize = 16;
+const BAR_WIDTH: f32 = 4.0;
+const BAR_MAX_HEIGHT: f32 = 32.0;
+
+pub struct AudioVizPlugin;
+
+impl Plugin for AudioVizPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioVizBuffer>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Running).with_system(setup_audio_viz_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Running).with_system(exit_audio_viz_system),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Running).with_system(audio_viz_system));
+    }
+}
+
+/// Recent mono samples sent to the audio sink
+#[derive(Default)]
+pub struct AudioVizBuffer(VecDeque<f32>);
+
+impl AudioVizBuffer {
+    pub fn push_samples(&mut self, samples: &[AudioSample]) {
+        for sample in samples {
+            let mono = (sample.left as f32 + sample.right as f32) / (2.0 * i16::MAX as f32);
+            self.0.push_back(mono);
+        }
+        while self.0.len() > FFT_SIZE {
+            self.0.pop_front();
+        }
+    }
+}
+
+#[derive(Component)]
+struct AudioVizBar(usize);
+
+fn setup_audio_viz_system(mut commands: Commands) {
+    for i in 0..BAR_NUM {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.4, 0.9, 0.6, 0.75),
+                    custom_size: Some(Vec2::new(BAR_WIDTH - 1.0, 1.0)),
+                    ..Default::default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(AudioVizBar(i));
+    }
+}
+
+fn exit_audio_viz_system(mut commands: Commands, bars: Query<Entity, With<AudioVizBar>>) {
+    for entity in bars.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn audio_viz_system(
+    config: Res<Config>,
+    emulator: Option<Res<Emulator>>,
+    buffer: Res<AudioVizBuffer>,
+    mut levels: Local<Vec<f32>>,
+    mut bars: Query<(&AudioVizBar, &mut Sprite, &mut Transform, &mut Visibility)>,
+) {
+    let emulator = if let Some(emulator) = emulator {
+        emulator
+    } else {
+        return;
+    };
+
+    let screen_width = emulator.core.frame_buffer().width as f32;
+    let screen_height = emulator.core.frame_buffer().height as f32;
+
+    let spectrum = if config.show_audio_viz && buffer.0.len() == FFT_SIZE {
+        spectrum(&buffer.0)
+    } else {
+        vec![0.0; BAR_NUM]
+    };
+
+    // Rise immediately, fall slowly
+    levels.resize(BAR_NUM, 0.0);
+    for (level, target) in levels.iter_mut().zip(spectrum) {
+        *level = if target > *level {
+            target
+        } else {
+            *level * 0.85 + target * 0.15
+        };
+    }
+
+    let left = screen_width / 2.0 - BAR_NUM as f32 * BAR_WIDTH - 2.0;
+
+    for (bar, mut sprite, mut transform, mut visibility) in bars.iter_mut() {
+        visibility.is_visible = config.show_audio_viz;
+
+        let height = (levels[bar.0] * BAR_MAX_HEIGHT).max(1.0);
+        sprite.custom_size = Some(Vec2::new(BAR_WIDTH - 1.0, height));
+        *transform = Transform::from_xyz(
+            left + (bar.0 as f32 + 0.5) * BAR_WIDTH,
+            -screen_height / 2.0 + 2.0 + height / 2.0,
+            1.5,
+        );
+    }
+}
+
+/// Levels (0.0 - 1.0) of logarithmically spaced frequency bands
+fn spectrum(samples: &VecDeque<f32>) -> Vec<f32> {
+    let n = samples.len();
+
+    // Hann window
+    let mut buf = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let w = 0.5 - 0.5 * (2.0 * PI * i as f32 / (n - 1) as f32).cos();
+            (s * w, 0.0)
+        })
+        .collect::<Vec<_>>();
+
+    fft(&mut buf);
+
+    let bins = n / 2;
+    let magnitude = |i: usize| {
+        let (re, im) = buf[i];
+        (re * re + im * im).sqrt() * 4.0 / n as f32
+    };
+
+    (0..BAR_NUM)
+        .map(|band| {
+            let lo = (bins as f32).powf(band as f32 / BAR_NUM as f32) as usize;
+            let hi = ((bins as f32).powf((band + 1) as f32 / BAR_NUM as f32) as usize).max(lo + 1);
+            let mag = (lo..hi.min(bins)).map(&magnitude).fold(0.0, f32::max);
+            let db = 20.0 * mag.max(1e-6).log10();
+            ((db + 60.0) / 60.0).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// In-place radix-2 FFT. `buf.len()` must be a power of two.
+fn fft(buf: &mut [(f32, f32)]) {
+    let n = buf.len();
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (s, c) = (angle * k as f32).sin_cos();
+                let (ar, ai) = buf[start + k];
+                let (br, bi) = buf[start + k + len / 2];
+                let (tr, ti) = (br * c - bi * s, br * s + bi * c);
+                buf[start + k] = (ar + tr, ai + ti);
+                buf[start + k + len / 2] = (ar - tr, ai - ti);
+            }
+        }
+        len <<= 1;
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_tiled_camera::TiledCamera;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    app::{AppState, ScreenSprite, ShowMessage},
+    config::Config,
+    core::Emulator,
+    i18n::tr,
+};
+
+pub struct BezelPlugin;
+
+impl Plugin for BezelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bezel>()
+            .add_system_to_stage(CoreStage::PostUpdate, bezel_system.label("bezel"));
+    }
+}
+
+/// Bezel image currently loaded from `Config::bezel_image`
+#[derive(Default)]
+struct Bezel {
+    path: Option<PathBuf>,
+    size: Vec2,
+    /// Top left and bottom right corners of the transparent area in image pixels,
+    /// where the screen is put
+    cutout: Option<(Vec2, Vec2)>,
+}
+
+/// Drawn over the game screen, so the screen shows through its transparent cutout
+#[derive(Component)]
+struct BezelSprite;
+
+/// Above the screen and its display filter, below the FPS counter and other overlays
+const BEZEL_Z: f32 = 0.75;
+
+fn load_bezel(path: &Path) -> Result<(Image, Option<(Vec2, Vec2)>)> {
+    let image = image::open(path)?.into_rgba8();
+
+    // Bounding box of the fully transparent pixels
+    let mut cutout: Option<(UVec2, UVec2)> = None;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] == 0 {
+            let pos = UVec2::new(x, y);
+            cutout = Some(match cutout {
+                Some((min, max)) => (min.min(pos), max.max(pos + 1)),
+                None => (pos, pos + 1),
+            });
+        }
+    }
+
+    let ret = Image::new(
+        Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        image.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    Ok((ret, cutout.map(|(min, max)| (min.as_vec2(), max.as_vec2()))))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bezel_system(
+    mut commands: Commands,
+    config: Res<Config>,
+    app_state: Res<State<AppState>>,
+    emulator: Option<Res<Emulator>>,
+    mut bezel: ResMut<Bezel>,
+    mut images: ResMut<Assets<Image>>,
+    camera: Query<&TiledCamera>,
+    mut bezel_sprite: Query<(Entity, &mut Sprite, &mut Visibility), With<BezelSprite>>,
+    mut screen_sprite: Query<&mut Transform, (With<ScreenSprite>, Without<BezelSprite>)>,
+    mut message_event: EventWriter<ShowMessage>,
+) {
+    if bezel.path != config.bezel_image {
+        bezel.path = config.bezel_image.clone();
+        for (entity, _, _) in bezel_sprite.iter() {
+            commands.entity(entity).despawn();
+        }
+
+        if let Some(path) = &bezel.path {
+            match load_bezel(path) {
+                Ok((image, cutout)) => {
+                    bezel.size = image.size();
+                    bezel.cutout = cutout;
+                    if cutout.is_none() {
+                        warn!("Bezel image `{}` has no transparent area", path.display());
+                    }
+                    commands
+                        .spawn_bundle(SpriteBundle {
+                            texture: images.add(image),
+                            transform: Transform::from_xyz(0.0, 0.0, BEZEL_Z),
+                            ..Default::default()
+                        })
+                        .insert(BezelSprite);
+                }
+                Err(err) => {
+                    warn!("Failed to load bezel image `{}`: {err}", path.display());
+                    message_event.send(ShowMessage(tr("Failed to load bezel image").to_string()));
+                }
+            }
+        }
+        return;
+    }
+
+    let (_, mut sprite, mut visibility) = if let Ok(bezel_sprite) = bezel_sprite.get_single_mut() {
+        bezel_sprite
+    } else {
+        return;
+    };
+
+    // Rewinding shows its own previews in place of the screen
+    visibility.is_visible = app_state.current() == &AppState::Running;
+    if !visibility.is_visible {
+        return;
+    }
+
+    // Fit into the area shown by the camera, which covers the whole window
+    let view = if let Ok(camera) = camera.get_single() {
+        camera.tile_count.as_vec2()
+    } else {
+        return;
+    };
+    let fit = (view / bezel.size).min_element();
+    sprite.custom_size = Some(bezel.size * fit);
+
+    let (emulator, (cutout_min, cutout_max)) = match (emulator, bezel.cutout) {
+        (Some(emulator), Some(cutout)) => (emulator, cutout),
+        _ => return,
+    };
+
+    // Put the screen into the cutout. Image rows go down, world coordinates go up.
+    let cutout_size = (cutout_max - cutout_min) * fit;
+    let cutout_center =
+        ((cutout_min + cutout_max) / 2.0 - bezel.size / 2.0) * fit * Vec2::new(1.0, -1.0);
+    let fb = emulator.core.frame_buffer();
+    for mut transform in screen_sprite.iter_mut() {
+        // The horizontal scale holds the pixel aspect set by the screen layout
+        let mut screen_size = Vec2::new(fb.width as f32 * transform.scale.x, fb.height as f32);
+        if config.screen_rotation.is_sideways() {
+            screen_size = Vec2::new(screen_size.y, screen_size.x);
+        }
+        if screen_size.min_element() < 1.0 {
+            continue;
+        }
+        let scale = (cutout_size / screen_size).min_element();
+        transform.scale = Vec3::new(transform.scale.x * scale, scale, 1.0);
+        transform.translation.x = cutout_center.x;
+        transform.translation.y = cutout_center.y;
+    }
+}
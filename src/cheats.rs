@@ -0,0 +1,48 @@
+use anyhow::Result;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::file::{atomic_write_file, get_save_dir};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cheat {
+    pub code: String,
+    #[serde(default)]
+    pub description: String,
+    pub enabled: bool,
+}
+
+/// Normalize a code as typed by the user
+pub fn normalize_code(code: &str) -> String {
+    code.split_whitespace().collect::<String>().to_uppercase()
+}
+
+fn get_cheat_file_path(core_abbrev: &str, name: &str, save_dir: &Path) -> Result<PathBuf> {
+    Ok(get_save_dir(core_abbrev, save_dir)?.join(format!("{name}.cheats.json")))
+}
+
+pub fn load_cheats(core_abbrev: &str, name: &str, save_dir: &Path) -> Result<Vec<Cheat>> {
+    let path = get_cheat_file_path(core_abbrev, name, save_dir)?;
+
+    Ok(if path.is_file() {
+        info!("Loading cheats: `{}`", path.display());
+        serde_json::from_slice(&fs::read(path)?)?
+    } else {
+        vec![]
+    })
+}
+
+pub fn save_cheats(core_abbrev: &str, name: &str, cheats: &[Cheat], save_dir: &Path) -> Result<()> {
+    let path = get_cheat_file_path(core_abbrev, name, save_dir)?;
+    if cheats.is_empty() {
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    atomic_write_file(&path, &serde_json::to_vec_pretty(cheats)?)
+}
@@ -1,18 +1,25 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
+use bevy::window::PresentMode;
 use directories::ProjectDirs;
 use enum_iterator::Sequence;
-use log::info;
+use log::{error, info};
 use meru_interface::EmulatorCore;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
-    collections::{BTreeMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, VecDeque},
     fmt::Display,
     fs,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-use crate::{core::Emulator, hotkey::HotKeys, input::KeyConfig};
+use crate::{
+    core::Emulator,
+    hotkey::HotKeys,
+    input::{gamepad_only_controller, KeyConfig},
+    library::Library,
+};
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, Sequence)]
 pub enum SystemKey {
@@ -40,6 +47,181 @@ impl Display for SystemKey {
 
 pub type SystemKeys = KeyConfig<SystemKey>;
 
+/// How to resolve simultaneous opposite directions
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SocdMode {
+    Neutral,
+    LastInput,
+    FirstInput,
+}
+
+/// Corner of the screen the FPS counter is shown at
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FpsPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Post-processing applied to the game screen
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum DisplayFilter {
+    None,
+    Crt,
+    Scanlines,
+}
+
+/// Color correction for color vision deficiencies, applied after `DisplayFilter`
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ColorFilter {
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+/// Look of the menu
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum MenuTheme {
+    Dark,
+    Light,
+    /// Dark with `Config::menu_accent_color` on selections and highlights
+    Custom,
+}
+
+/// Colors for monochrome Game Boy games
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GbPalette {
+    /// Leave the core output as is
+    Original,
+    Grayscale,
+    Green,
+    Pocket,
+    Custom,
+}
+
+impl GbPalette {
+    /// Colors from the lightest to the darkest
+    pub fn colors(&self, custom: &[[u8; 3]; 4]) -> Option<[[u8; 3]; 4]> {
+        Some(match self {
+            GbPalette::Original => return None,
+            GbPalette::Grayscale => [[0xFF; 3], [0xAA; 3], [0x55; 3], [0x00; 3]],
+            GbPalette::Green => [
+                [0x9B, 0xBC, 0x0F],
+                [0x8B, 0xAC, 0x0F],
+                [0x30, 0x62, 0x30],
+                [0x0F, 0x38, 0x0F],
+            ],
+            GbPalette::Pocket => [
+                [0xC4, 0xCF, 0xA1],
+                [0x8B, 0x95, 0x6D],
+                [0x4D, 0x53, 0x3C],
+                [0x1F, 0x1F, 0x1F],
+            ],
+            GbPalette::Custom => *custom,
+        })
+    }
+}
+
+/// Presentation of frames to the window
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Vsync {
+    /// Wait for vertical blank
+    Fifo,
+    /// Replace the queued frame, low latency without tearing where supported
+    Mailbox,
+    /// No vsync, may tear
+    Immediate,
+}
+
+impl Vsync {
+    pub fn present_mode(&self) -> PresentMode {
+        match self {
+            Vsync::Fifo => PresentMode::Fifo,
+            Vsync::Mailbox => PresentMode::Mailbox,
+            Vsync::Immediate => PresentMode::Immediate,
+        }
+    }
+}
+
+/// Window scale chosen by `HotKey::CycleScale`
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ScalePreset {
+    Scale(usize),
+    /// Largest scale fitting the monitor
+    Fit,
+}
+
+/// How the window covers the monitor in fullscreen
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FullscreenMode {
+    /// Borderless window of the monitor size
+    Borderless,
+    /// Exclusive fullscreen in the highest resolution and refresh rate of the monitor.
+    /// Falls back to `Borderless` when the monitor reports no video mode.
+    Exclusive,
+}
+
+/// Sampling of the game screen when it is scaled
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+
+/// Shape of the game screen
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum AspectRatio {
+    /// Square pixels
+    Pixel,
+    /// Pixel shape of the original display
+    Core,
+    /// Fill the whole window
+    Stretch,
+}
+
+/// Clockwise rotation of the game screen
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ScreenRotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl ScreenRotation {
+    pub fn quarter_turns(self) -> usize {
+        match self {
+            ScreenRotation::Deg0 => 0,
+            ScreenRotation::Deg90 => 1,
+            ScreenRotation::Deg180 => 2,
+            ScreenRotation::Deg270 => 3,
+        }
+    }
+
+    /// Width and height of the screen are swapped
+    pub fn is_sideways(self) -> bool {
+        self.quarter_turns() % 2 == 1
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum RewindAudio {
+    Mute,
+    Reverse,
+}
+
+/// Sound while fast-forwarding
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TurboAudio {
+    Mute,
+    /// Played faster, raising the pitch
+    SpedUp,
+    /// Slices of the audio at the original pitch, skipping the rest
+    PitchCorrected,
+}
+
 impl Default for SystemKeys {
     fn default() -> Self {
         use meru_interface::key_assign::*;
@@ -55,22 +237,154 @@ impl Default for SystemKeys {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub save_dir: PathBuf,
+    pub screenshot_dir: PathBuf,
+    pub recording_dir: PathBuf,
+    /// Length of the clip saved by the GIF hotkey, 0 to stop keeping frames
+    pub gif_duration_secs: f32,
+    /// Directories scanned for the game library
+    pub rom_dirs: Vec<PathBuf>,
+    /// Code of one of `i18n::LANGUAGES`
+    pub language: String,
+    pub menu_theme: MenuTheme,
+    pub menu_accent_color: [u8; 3],
+    pub show_splash: bool,
+    pub volume: f32,
+    /// Name of the audio output device, `None` for the system default
+    pub audio_device: Option<String>,
+    /// Amount of audio queued ahead of playback
+    pub audio_latency_ms: u32,
     pub show_fps: bool,
-    pub frame_skip_on_turbo: usize,
+    /// Time on-screen messages stay visible
+    pub message_duration_secs: f32,
+    pub message_font_size: f32,
+    pub show_fps_stats: bool,
+    /// Show the emulation speed relative to the original hardware with the FPS
+    pub show_emulation_speed: bool,
+    pub fps_position: FpsPosition,
+    pub fps_color: [u8; 3],
+    pub show_audio_viz: bool,
+    pub input_overlay: bool,
+    /// Emulated frames per displayed frame while fast-forwarding, `0.0` for unlimited
+    pub turbo_speed: f32,
+    /// Time to reach `turbo_speed` from normal speed and back, `0` to switch instantly
+    pub turbo_ramp_ms: u32,
+    /// Emulated frames per displayed frame while slow motion is held
+    pub slow_motion_speed: f32,
+    pub no_rewind_during_turbo: bool,
+    pub turbo_audio: TurboAudio,
+    /// Log a hash of the machine state at debug level, to compare runs
+    pub log_state_hashes: bool,
+    pub state_hash_interval_frames: usize,
+    /// Fast-forward only while the key is held, otherwise the key toggles it
+    pub hold_for_turbo: bool,
+    /// Releasing the rewind key resumes from the selected point,
+    /// otherwise pressing it again does
+    pub hold_for_rewind: bool,
+    pub pause_on_modal: bool,
+    /// Darkness of the overlay over the game while a dialog is open, `0.0`-`1.0`
+    pub modal_dim: f32,
+    /// Ask before resetting from the hotkeys
+    pub confirm_reset: bool,
     pub scaling: usize,
+    pub scale_presets: Vec<ScalePreset>,
+    /// Last window scale used for each core
+    pub core_scaling: BTreeMap<String, usize>,
+    /// Scaling down stops before the game window gets smaller than this
+    pub min_window_size: [usize; 2],
+    /// Scaling up stops before the game window gets larger than this,
+    /// `None` for the size of the monitor. Never exceeds the monitor.
+    pub max_window_size: Option<[usize; 2]>,
+    pub vsync: Vsync,
+    pub fullscreen_mode: FullscreenMode,
+    /// Index of the monitor to go fullscreen on, `None` for the primary monitor
+    pub fullscreen_monitor: Option<usize>,
+    /// Double-clicking the window toggles fullscreen
+    pub double_click_fullscreen: bool,
+    /// Longest interval between the clicks of a double-click
+    pub double_click_seconds: f64,
+    pub resizable_window: bool,
+    pub lock_aspect_on_resize: bool,
+    /// Only scale the screen by whole numbers, leaving black bars around it
+    pub integer_scale: bool,
+    pub aspect_ratio: AspectRatio,
+    pub screen_rotation: ScreenRotation,
+    /// Turn the directional buttons along with the screen
+    pub rotate_input: bool,
+    pub texture_filter: TextureFilter,
+    pub gb_palette: GbPalette,
+    pub gb_custom_palette: [[u8; 3]; 4],
+    pub auto_brightness: bool,
+    pub auto_brightness_strength: f64,
+    /// Mix each frame with the previous one
+    pub frame_blend: bool,
+    pub display_filter: DisplayFilter,
+    /// Darkness of the lines of `DisplayFilter::Scanlines`, `0.0`-`1.0`
+    pub scanline_intensity: f32,
+    /// Image drawn around the game screen, with a transparent cutout for it
+    pub bezel_image: Option<PathBuf>,
+    pub color_filter: ColorFilter,
+    /// Color of the window area outside of the game screen
+    pub background_color: [u8; 3],
     pub auto_state_save_rate: usize,   // byte/s
     pub auto_state_save_limit: usize,  // byte
     pub minimum_auto_save_span: usize, // frames
+    pub rewind_buffer_seconds: u32,
+    /// Number of state slots shown and cycled through. Saves in higher slots are kept.
+    pub state_slot_count: usize,
+    /// Saving into a labeled slot keeps its label, otherwise the label is cleared
+    pub keep_state_labels: bool,
+    /// Compress the state slots on disk
+    pub compress_states: bool,
+    /// Compress the rewind snapshots in memory, trading CPU time for a longer history
+    pub compress_rewind_states: bool,
+    /// Interval of the rolling autosave, `None` to disable it
+    pub autosave_interval_seconds: Option<u32>,
+    /// Load the last saved slot of the game when it's opened
+    pub resume_on_load: bool,
+    /// Pair of slots flipped between by `HotKey::QuickSwap`
+    pub quick_swap_slots: [usize; 2],
+    pub rewind_audio: RewindAudio,
+    pub input_delay_frames: u32,
+    pub socd_mode: SocdMode,
+    /// Stick values below this read as neutral
+    pub stick_deadzone: f32,
+    /// Forward the rumble of the game to the first player's gamepad
+    pub rumble_enabled: bool,
+    /// Scale of the rumble, `0.0`-`1.0`
+    pub rumble_strength: f32,
+    /// Gamepad driving each player, `None` reads the pads in the key config as is
+    pub player_gamepads: Vec<Option<usize>>,
+    pub boot_to_bios: bool,
+    pub boot_to_bios_games: BTreeSet<String>,
+    pub multitap_games: BTreeSet<String>,
+    /// ROM to run on the other end of the link cable, per game
+    pub link_partners: BTreeMap<String, PathBuf>,
+    /// Core to use for file extensions supported by multiple cores
+    pub core_selection: BTreeMap<String, String>,
+    /// Core chosen for ROMs whose system could not be detected, per ROM hash
+    pub rom_cores: BTreeMap<String, String>,
     pub hotkeys: HotKeys,
     pub system_keys: SystemKeys,
 
+    /// Profile this config is saved to, `None` for the default profile
+    #[serde(skip)]
+    pub profile: Option<String>,
+    #[serde(skip)]
+    game_override: Option<GameOverrideState>,
+
     #[serde(default)]
     core_configs: BTreeMap<String, Value>,
     #[serde(default)]
     key_configs: BTreeMap<String, meru_interface::KeyConfig>,
+    #[serde(default)]
+    clock_multipliers: BTreeMap<String, f64>,
+    /// Autofire rate in presses per second of each button, per core and controller
+    #[serde(default)]
+    autofire: BTreeMap<String, Vec<BTreeMap<String, f32>>>,
 }
 
 impl Default for Config {
@@ -90,31 +404,149 @@ impl Default for Config {
         fs::create_dir_all(&save_dir).unwrap();
         fs::create_dir_all(&state_dir).unwrap();
 
+        let (screenshot_dir, recording_dir) = if let Ok(project_dirs) = project_dirs() {
+            (
+                project_dirs.config_dir().join("screenshots"),
+                project_dirs.config_dir().join("recordings"),
+            )
+        } else {
+            (PathBuf::from("screenshots"), PathBuf::from("recordings"))
+        };
+
         Self {
             save_dir,
+            screenshot_dir,
+            recording_dir,
+            gif_duration_secs: 5.0,
+            rom_dirs: vec![],
+            language: "en".to_string(),
+            menu_theme: MenuTheme::Dark,
+            menu_accent_color: [0x90, 0x60, 0xe0],
+            show_splash: false,
+            volume: 1.0,
+            audio_device: None,
+            audio_latency_ms: 67,
             show_fps: false,
-            frame_skip_on_turbo: 4,
+            message_duration_secs: 3.0,
+            message_font_size: 16.0,
+            show_fps_stats: false,
+            show_emulation_speed: false,
+            fps_position: FpsPosition::TopRight,
+            fps_color: [255, 255, 255],
+            show_audio_viz: false,
+            input_overlay: false,
+            turbo_speed: 4.0,
+            turbo_ramp_ms: 0,
+            slow_motion_speed: 0.25,
+            no_rewind_during_turbo: true,
+            turbo_audio: TurboAudio::PitchCorrected,
+            log_state_hashes: false,
+            state_hash_interval_frames: 60,
+            hold_for_turbo: true,
+            hold_for_rewind: false,
+            pause_on_modal: true,
+            modal_dim: 0.5,
+            confirm_reset: false,
             scaling: 2,
+            scale_presets: vec![
+                ScalePreset::Scale(1),
+                ScalePreset::Scale(2),
+                ScalePreset::Scale(3),
+                ScalePreset::Fit,
+            ],
+            core_scaling: BTreeMap::new(),
+            min_window_size: [0, 0],
+            max_window_size: None,
+            vsync: Vsync::Fifo,
+            fullscreen_mode: FullscreenMode::Borderless,
+            fullscreen_monitor: None,
+            double_click_fullscreen: true,
+            double_click_seconds: 0.25,
+            resizable_window: false,
+            lock_aspect_on_resize: false,
+            integer_scale: true,
+            aspect_ratio: AspectRatio::Pixel,
+            screen_rotation: ScreenRotation::Deg0,
+            rotate_input: false,
+            texture_filter: TextureFilter::Nearest,
+            gb_palette: GbPalette::Original,
+            gb_custom_palette: [
+                [0xE0, 0xF8, 0xD0],
+                [0x88, 0xC0, 0x70],
+                [0x34, 0x68, 0x56],
+                [0x08, 0x18, 0x20],
+            ],
+            auto_brightness: false,
+            auto_brightness_strength: 0.5,
+            frame_blend: false,
+            display_filter: DisplayFilter::None,
+            scanline_intensity: 0.5,
+            bezel_image: None,
+            color_filter: ColorFilter::None,
+            background_color: [0, 0, 0],
             auto_state_save_rate: 128 * 1024,          // 128KB/s
             auto_state_save_limit: 1024 * 1024 * 1024, // 1GB
             minimum_auto_save_span: 60,
+            rewind_buffer_seconds: 60,
+            state_slot_count: 10,
+            keep_state_labels: false,
+            compress_states: true,
+            compress_rewind_states: false,
+            autosave_interval_seconds: None,
+            resume_on_load: false,
+            quick_swap_slots: [0, 1],
+            rewind_audio: RewindAudio::Mute,
+            input_delay_frames: 0,
+            socd_mode: SocdMode::Neutral,
+            stick_deadzone: 0.15,
+            rumble_enabled: true,
+            rumble_strength: 1.0,
+            player_gamepads: vec![],
+            boot_to_bios: false,
+            boot_to_bios_games: BTreeSet::new(),
+            multitap_games: BTreeSet::new(),
+            link_partners: BTreeMap::new(),
+            core_selection: BTreeMap::new(),
+            rom_cores: BTreeMap::new(),
             system_keys: SystemKeys::default(),
             hotkeys: HotKeys::default(),
+            profile: None,
+            game_override: None,
             core_configs: BTreeMap::new(),
             key_configs: BTreeMap::new(),
+            autofire: BTreeMap::new(),
+            clock_multipliers: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
     pub fn save(&self) -> Result<()> {
-        let s = serde_json::to_string_pretty(self)?;
-        let path = config_path()?;
+        // Per-game settings are saved separately
+        let s = if self.game_override.is_some() {
+            let mut base = self.clone();
+            base.clear_game_override();
+            serde_json::to_string_pretty(&base)?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        let path = config_path(self.profile.as_deref())?;
         fs::write(&path, s)?;
         info!("Saved config file: {:?}", path.display());
         Ok(())
     }
 
+    /// Modification time of the config file, to notice edits made outside of MERU
+    pub fn file_modified(&self) -> Option<SystemTime> {
+        let path = config_path(self.profile.as_deref()).ok()?;
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Number of frames of audio to keep queued in the sink
+    pub fn audio_queue_frames(&self, frame_rate: f64) -> usize {
+        ((self.audio_latency_ms as f64 * frame_rate / 1000.0).round() as usize).max(1)
+    }
+
     pub fn core_config<T: EmulatorCore>(&self) -> T::Config {
         if let Some(config) = self.core_configs.get(T::core_info().abbrev) {
             serde_json::from_value(config.clone()).unwrap()
@@ -130,42 +562,451 @@ impl Config {
         );
     }
 
+    pub fn state_slot_count(&self) -> usize {
+        self.state_slot_count.clamp(1, MAX_STATE_SLOTS)
+    }
+
+    /// Slot of the quick swap pair other than `slot`
+    pub fn quick_swap_target(&self, slot: usize) -> usize {
+        let [a, b] = self.quick_swap_slots;
+        let target = if slot == a { b } else { a };
+        target.min(self.state_slot_count() - 1)
+    }
+
+    pub fn player_gamepad(&self, player: usize) -> Option<usize> {
+        self.player_gamepads.get(player).copied().flatten()
+    }
+
+    pub fn set_player_gamepad(&mut self, player: usize, gamepad: Option<usize>) {
+        if self.player_gamepads.len() <= player {
+            self.player_gamepads.resize(player + 1, None);
+        }
+        self.player_gamepads[player] = gamepad;
+        while self.player_gamepads.last() == Some(&None) {
+            self.player_gamepads.pop();
+        }
+    }
+
     pub fn key_config(&mut self, abbrev: &str) -> &meru_interface::KeyConfig {
-        self.key_configs
+        let key_config = self
+            .key_configs
             .entry(abbrev.to_string())
-            .or_insert_with(|| Emulator::default_key_config(abbrev))
+            .or_insert_with(|| Emulator::default_key_config(abbrev));
+
+        // Players added by a multitap get pad 1's layout on their own gamepad
+        let count = Emulator::multitap_controllers(abbrev).unwrap_or(0);
+        while key_config.controllers.len() < count {
+            let controller =
+                gamepad_only_controller(&key_config.controllers[0], key_config.controllers.len());
+            key_config.controllers.push(controller);
+        }
+
+        key_config
     }
 
     pub fn set_key_config(&mut self, abbrev: &str, key_config: meru_interface::KeyConfig) {
         self.key_configs.insert(abbrev.to_string(), key_config);
     }
+
+    pub fn autofire_rates(&self, abbrev: &str) -> &[BTreeMap<String, f32>] {
+        self.autofire
+            .get(abbrev)
+            .map_or(&[], |rates| rates.as_slice())
+    }
+
+    pub fn autofire_rate(&self, abbrev: &str, controller: usize, button: &str) -> Option<f32> {
+        self.autofire_rates(abbrev)
+            .get(controller)?
+            .get(button)
+            .copied()
+    }
+
+    pub fn set_autofire_rate(
+        &mut self,
+        abbrev: &str,
+        controller: usize,
+        button: &str,
+        rate: Option<f32>,
+    ) {
+        let rates = self.autofire.entry(abbrev.to_string()).or_default();
+        if rates.len() <= controller {
+            rates.resize(controller + 1, BTreeMap::new());
+        }
+        if let Some(rate) = rate {
+            rates[controller].insert(button.to_string(), rate);
+        } else {
+            rates[controller].remove(button);
+        }
+    }
+
+    pub fn should_boot_to_bios(&self, game_name: &str) -> bool {
+        self.boot_to_bios || self.boot_to_bios_games.contains(game_name)
+    }
+
+    pub fn clock_multiplier(&self, abbrev: &str) -> f64 {
+        self.clock_multipliers.get(abbrev).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_clock_multiplier(&mut self, abbrev: &str, multiplier: f64) {
+        self.clock_multipliers
+            .insert(abbrev.to_string(), multiplier);
+    }
 }
 
+/// Settings of a single game layered over the profile. Unset fields follow the profile.
+#[derive(Default, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameOverride {
+    scaling: Option<usize>,
+    aspect_ratio: Option<AspectRatio>,
+    screen_rotation: Option<ScreenRotation>,
+    integer_scale: Option<bool>,
+    display_filter: Option<DisplayFilter>,
+    gb_palette: Option<GbPalette>,
+    gb_custom_palette: Option<[[u8; 3]; 4]>,
+    /// Only the buttons listed here are replaced
+    key_config: Option<meru_interface::KeyConfig>,
+    /// Only the hotkeys listed here are replaced
+    hotkeys: Option<HotKeys>,
+}
+
+#[derive(PartialEq, Clone)]
+struct GameOverrideState {
+    abbrev: String,
+    game_name: String,
+    /// Profile values of the overridden settings, `None` if the game has no overrides
+    base: Option<GameOverride>,
+}
+
+macro_rules! override_fields {
+    ($m:ident) => {
+        $m!(
+            scaling,
+            aspect_ratio,
+            screen_rotation,
+            integer_scale,
+            display_filter,
+            gb_palette,
+            gb_custom_palette
+        )
+    };
+}
+
+/// Replace the buttons listed in `src`, returning their previous assignments
+fn merge_key_config(
+    dest: &mut meru_interface::KeyConfig,
+    src: &meru_interface::KeyConfig,
+) -> meru_interface::KeyConfig {
+    let mut prev = meru_interface::KeyConfig {
+        controllers: vec![],
+    };
+    for (i, buttons) in src.controllers.iter().enumerate() {
+        let mut prev_buttons = vec![];
+        if let Some(dest_buttons) = dest.controllers.get_mut(i) {
+            for (name, assign) in buttons {
+                if let Some((_, dest_assign)) = dest_buttons.iter_mut().find(|(n, _)| n == name) {
+                    prev_buttons
+                        .push((name.clone(), std::mem::replace(dest_assign, assign.clone())));
+                }
+            }
+        }
+        prev.controllers.push(prev_buttons);
+    }
+    prev
+}
+
+fn diff_key_config(
+    current: &meru_interface::KeyConfig,
+    base: &meru_interface::KeyConfig,
+) -> Option<meru_interface::KeyConfig> {
+    let controllers = current
+        .controllers
+        .iter()
+        .enumerate()
+        .map(|(i, buttons)| {
+            let base_buttons = base.controllers.get(i);
+            buttons
+                .iter()
+                .filter(|(name, assign)| {
+                    base_buttons
+                        .and_then(|b| b.iter().find(|(n, _)| n == name))
+                        .map_or(true, |(_, a)| a != assign)
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    controllers
+        .iter()
+        .any(|buttons| !buttons.is_empty())
+        .then(|| meru_interface::KeyConfig { controllers })
+}
+
+/// Replace the hotkeys listed in `src`, returning their previous assignments
+fn merge_hotkeys(dest: &mut HotKeys, src: &HotKeys) -> HotKeys {
+    KeyConfig(
+        src.0
+            .iter()
+            .filter_map(|(key, assign)| {
+                let dest_assign = dest.key_assign_mut(key)?;
+                Some((*key, std::mem::replace(dest_assign, assign.clone())))
+            })
+            .collect(),
+    )
+}
+
+fn diff_hotkeys(current: &HotKeys, base: &HotKeys) -> Option<HotKeys> {
+    let diff = current
+        .0
+        .iter()
+        .filter(|(key, assign)| base.key_assign(key) != Some(assign))
+        .cloned()
+        .collect::<Vec<_>>();
+    (!diff.is_empty()).then(|| KeyConfig(diff))
+}
+
+impl Config {
+    /// Apply the settings saved for the game, restoring the profile's ones of the previous game
+    pub fn apply_game_override(&mut self, abbrev: &str, game_name: &str) {
+        self.clear_game_override();
+
+        let game_override = match load_game_override(abbrev, game_name) {
+            Ok(game_override) => game_override,
+            Err(err) => {
+                error!("Failed to load game settings: {err}");
+                None
+            }
+        };
+        let base = game_override.map(|game_override| self.overlay(abbrev, &game_override));
+
+        self.game_override = Some(GameOverrideState {
+            abbrev: abbrev.to_string(),
+            game_name: game_name.to_string(),
+            base,
+        });
+    }
+
+    /// Go back to the profile's settings
+    pub fn clear_game_override(&mut self) {
+        if let Some(state) = self.game_override.take() {
+            if let Some(base) = state.base {
+                self.overlay(&state.abbrev, &base);
+            }
+        }
+    }
+
+    pub fn is_game_override_loaded(&self, abbrev: &str, game_name: &str) -> bool {
+        self.game_override.as_ref().map_or(false, |state| {
+            state.abbrev == abbrev && state.game_name == game_name
+        })
+    }
+
+    /// Core and game whose settings are loaded
+    pub fn game_override_target(&self) -> Option<(&str, &str)> {
+        self.game_override
+            .as_ref()
+            .map(|state| (state.abbrev.as_str(), state.game_name.as_str()))
+    }
+
+    pub fn has_game_override(&self) -> bool {
+        self.game_override
+            .as_ref()
+            .map_or(false, |state| state.base.is_some())
+    }
+
+    /// Save the settings differing from the profile as the current game's
+    pub fn save_game_override(&mut self) -> Result<()> {
+        let (abbrev, game_name) = match &self.game_override {
+            Some(state) => (state.abbrev.clone(), state.game_name.clone()),
+            None => bail!("No game is running"),
+        };
+
+        let mut base = self.clone();
+        base.clear_game_override();
+
+        let mut game_override = GameOverride::default();
+        macro_rules! diff {
+            ($($field:ident),*) => {
+                $(
+                    game_override.$field =
+                        (self.$field != base.$field).then(|| self.$field.clone());
+                )*
+            };
+        }
+        override_fields!(diff);
+        game_override.key_config =
+            diff_key_config(&self.key_config(&abbrev).clone(), base.key_config(&abbrev));
+        game_override.hotkeys = diff_hotkeys(&self.hotkeys, &base.hotkeys);
+
+        save_game_override(&abbrev, &game_name, &game_override)?;
+
+        // Re-apply, so that the override becomes the base of the next changes
+        *self = base;
+        self.apply_game_override(&abbrev, &game_name);
+        Ok(())
+    }
+
+    pub fn delete_game_override(&mut self) -> Result<()> {
+        if let Some(state) = &self.game_override {
+            let (abbrev, game_name) = (state.abbrev.clone(), state.game_name.clone());
+            let path = game_override_path(&abbrev, &game_name)?;
+            if path.is_file() {
+                fs::remove_file(path)?;
+            }
+            self.apply_game_override(&abbrev, &game_name);
+        }
+        Ok(())
+    }
+
+    /// Apply the fields set in `game_override`, returning the previous values of them
+    fn overlay(&mut self, abbrev: &str, game_override: &GameOverride) -> GameOverride {
+        let mut prev = GameOverride::default();
+        macro_rules! overlay {
+            ($($field:ident),*) => {
+                $(
+                    if let Some(value) = &game_override.$field {
+                        prev.$field = Some(std::mem::replace(&mut self.$field, value.clone()));
+                    }
+                )*
+            };
+        }
+        override_fields!(overlay);
+
+        if let Some(key_config) = &game_override.key_config {
+            self.key_config(abbrev);
+            let dest = self.key_configs.get_mut(abbrev).unwrap();
+            prev.key_config = Some(merge_key_config(dest, key_config));
+        }
+        if let Some(hotkeys) = &game_override.hotkeys {
+            prev.hotkeys = Some(merge_hotkeys(&mut self.hotkeys, hotkeys));
+        }
+        prev
+    }
+}
+
+fn game_override_path(abbrev: &str, game_name: &str) -> Result<PathBuf> {
+    let dir = project_dirs()?.config_dir().join("games").join(abbrev);
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(format!("{game_name}.json")))
+}
+
+fn load_game_override(abbrev: &str, game_name: &str) -> Result<Option<GameOverride>> {
+    let path = game_override_path(abbrev, game_name)?;
+    Ok(if path.is_file() {
+        info!("Loading game settings: `{}`", path.display());
+        Some(serde_json::from_str(&fs::read_to_string(path)?)?)
+    } else {
+        None
+    })
+}
+
+fn save_game_override(abbrev: &str, game_name: &str, game_override: &GameOverride) -> Result<()> {
+    let path = game_override_path(abbrev, game_name)?;
+    fs::write(&path, serde_json::to_string_pretty(game_override)?)?;
+    info!("Saved game settings: `{}`", path.display());
+    Ok(())
+}
+
+pub const MAX_STATE_SLOTS: usize = 100;
+
 fn project_dirs() -> Result<ProjectDirs> {
     let ret = ProjectDirs::from("", "", "meru")
         .ok_or_else(|| anyhow!("Cannot find project directory"))?;
     Ok(ret)
 }
 
-fn config_path() -> Result<PathBuf> {
+fn profiles_dir() -> Result<PathBuf> {
+    let dir = project_dirs()?.config_dir().join("profiles");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// The default profile lives in `config.json`, named ones in `profiles/`
+fn config_path(profile: Option<&str>) -> Result<PathBuf> {
+    if let Some(profile) = profile {
+        if !is_valid_profile_name(profile) {
+            bail!("Invalid profile name: `{profile}`");
+        }
+        return Ok(profiles_dir()?.join(format!("{profile}.json")));
+    }
+
     let project_dirs = project_dirs()?;
     let config_dir = project_dirs.config_dir();
     fs::create_dir_all(config_dir)?;
     Ok(config_dir.join("config.json"))
 }
 
-pub fn load_config() -> Result<Config> {
-    let ret = if let Ok(s) = std::fs::read_to_string(config_path()?) {
+/// Profile names become file names, so only allow a safe subset
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name.trim() == name
+        && !name.eq_ignore_ascii_case("default")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+}
+
+/// Names of the saved profiles other than the default one
+pub fn list_profiles() -> Result<Vec<String>> {
+    let mut ret = vec![];
+    for entry in fs::read_dir(profiles_dir()?)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "json") {
+            if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                if is_valid_profile_name(name) {
+                    ret.push(name.to_string());
+                }
+            }
+        }
+    }
+    ret.sort();
+    Ok(ret)
+}
+
+pub fn delete_profile(profile: &str) -> Result<()> {
+    fs::remove_file(config_path(Some(profile))?)?;
+    info!("Deleted profile: `{profile}`");
+    Ok(())
+}
+
+pub fn load_config(profile: Option<&str>) -> Result<Config> {
+    let path = config_path(profile)?;
+    let mut ret: Config = if let Ok(s) = std::fs::read_to_string(&path) {
         serde_json::from_str(&s).map_err(|e| anyhow!("{}", e))?
+    } else if profile.is_some() {
+        bail!("Profile not found: `{}`", path.display());
     } else {
         Config::default()
     };
+    ret.hotkeys.fill_missing(HotKeys::default());
+    ret.system_keys.fill_missing(SystemKeys::default());
+    ret.profile = profile.map(|s| s.to_string());
     Ok(ret)
 }
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct PersistentState {
     pub recent: VecDeque<PathBuf>,
+    /// Profile loaded on startup, `None` for the default one
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    /// Last position of the window in windowed mode
+    #[serde(default)]
+    pub window_position: Option<(i32, i32)>,
+    /// Window size chosen by resizing, `None` while it follows the scaling
+    #[serde(default)]
+    pub window_size: Option<(f32, f32)>,
+    /// Result of the last scan of the ROM directories
+    #[serde(default)]
+    pub library: Library,
+    /// Paths of the games pinned to the top of the recent files and the library
+    #[serde(default)]
+    pub favorites: Vec<String>,
+    /// Slot each game was last saved into
+    #[serde(default)]
+    pub last_state_slots: BTreeMap<String, usize>,
 }
 
 impl Drop for PersistentState {
@@ -175,6 +1016,8 @@ impl Drop for PersistentState {
     }
 }
 
+const MAX_RECENT_FILES: usize = 10;
+
 impl PersistentState {
     pub fn add_recent(&mut self, path: impl AsRef<Path>) {
         let path = path.as_ref().to_owned();
@@ -182,10 +1025,42 @@ impl PersistentState {
             self.recent.retain(|p| p != &path);
         }
         self.recent.push_front(path);
-        while self.recent.len() > 20 {
-            self.recent.pop_back();
+        while self.recent.len() > MAX_RECENT_FILES {
+            // Favorites never drop off
+            match self.recent.iter().rposition(|p| !self.is_favorite(p)) {
+                Some(i) => {
+                    self.recent.remove(i);
+                }
+                None => break,
+            }
+        }
+    }
+
+    pub fn last_state_slot(&self, game_name: &str) -> Option<usize> {
+        self.last_state_slots.get(game_name).copied()
+    }
+
+    pub fn set_last_state_slot(&mut self, game_name: &str, slot: usize) {
+        self.last_state_slots.insert(game_name.to_string(), slot);
+    }
+
+    pub fn is_favorite(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref().to_string_lossy();
+        self.favorites.iter().any(|p| *p == path)
+    }
+
+    pub fn toggle_favorite(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref().to_string_lossy().to_string();
+        if self.favorites.contains(&path) {
+            self.favorites.retain(|p| p != &path);
+        } else {
+            self.favorites.push(path);
         }
     }
+
+    pub fn remove_recent(&mut self, path: impl AsRef<Path>) {
+        self.recent.retain(|p| p != path.as_ref());
+    }
 }
 
 fn persistent_state_path() -> Result<PathBuf> {
@@ -1,11 +1,17 @@
 use anyhow::{anyhow, bail, Result};
 use bevy::{
     prelude::*,
-    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    window::WindowCloseRequested,
+    winit::WinitWindows,
 };
 use bevy_tiled_camera::{TiledCamera, TiledCameraBundle};
+use chrono::Local;
 use meru_interface::{
-    AudioBuffer, ConfigUi, CoreInfo, EmulatorCore, FrameBuffer, InputData, KeyConfig,
+    AudioBuffer, AudioSample, ConfigUi, CoreInfo, EmulatorCore, FrameBuffer, InputData, KeyConfig,
 };
 use std::{
     collections::VecDeque,
@@ -13,15 +19,28 @@ use std::{
     io::{Seek, SeekFrom},
     marker::PhantomData,
     path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 use crate::{
-    app::{AppState, ScreenSprite, WindowControlEvent},
-    config::Config,
-    file::{load_backup, load_state, save_backup, save_state},
+    app::{
+        fit_scale, AppState, AudioDevice, ModalOpen, ScreenSprite, ShowMessage, WindowControlEvent,
+        WINDOW_TITLE,
+    },
+    audio_viz::AudioVizBuffer,
+    cheats::{load_cheats, normalize_code, save_cheats, Cheat},
+    config::{AspectRatio, Config, PersistentState, RewindAudio, TextureFilter, TurboAudio},
+    file::{
+        compress_state, decompress_state, delete_state, load_backup, load_state, load_state_file,
+        load_state_meta, load_state_thumbnail, save_backup, save_state, save_state_file,
+        save_state_meta, save_state_thumbnail, state_date, tag_state, untag_state, StateMeta,
+    },
     hotkey,
-    input::InputState,
+    i18n::{tr, trf},
+    input::{player_input, resolve_socd, rotate_directions, AutofireState, InputState, SocdState},
     menu::EguiUi,
+    movie::{movie_file_path, state_hash, Movie},
+    recording::{record_audio, record_frame, AudioRecorder, Recorder},
     rewinding::AutoSavedState,
 };
 
@@ -79,47 +98,141 @@ impl EmulatorCores {
         }
         dispatch_enum!(EmulatorCores, self, core, core_info(core))
     }
+
+    /// Whether `data` has the header of a ROM for this core's system
+    fn matches_header(&self, data: &[u8]) -> bool {
+        match self {
+            EmulatorCores::Nes(_) => data.starts_with(b"NES\x1a"),
+            EmulatorCores::Snes(_) => {
+                // Skip the 512-byte header of copier dumps
+                let data = &data[data.len() % 0x400..];
+                // LoROM and HiROM headers, with the checksum and its complement
+                [0x7fc0, 0xffc0].iter().any(|&base| {
+                    data.get(base + 0x1c..base + 0x20).map_or(false, |b| {
+                        u16::from_le_bytes([b[0], b[1]]) ^ u16::from_le_bytes([b[2], b[3]])
+                            == 0xffff
+                    })
+                })
+            }
+            EmulatorCores::GameBoy(_) => {
+                let checksum = data.get(0x134..0x14d).map(|header| {
+                    header
+                        .iter()
+                        .fold(0u8, |acc, &b| acc.wrapping_sub(b).wrapping_sub(1))
+                });
+                data.get(0x104..0x134) == Some(&GB_NINTENDO_LOGO[..])
+                    && data.get(0x14d).copied() == checksum
+            }
+            EmulatorCores::GameBoyAdvance(_) => {
+                let checksum = data.get(0xa0..0xbd).map(|header| {
+                    header
+                        .iter()
+                        .fold(0u8, |acc, &b| acc.wrapping_sub(b))
+                        .wrapping_sub(0x19)
+                });
+                data.get(0xb2) == Some(&0x96) && data.get(0xbd).copied() == checksum
+            }
+        }
+    }
+}
+
+const GB_NINTENDO_LOGO: [u8; 48] = [
+    0xce, 0xed, 0x66, 0x66, 0xcc, 0x0d, 0x00, 0x0b, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0c, 0x00, 0x0d,
+    0x00, 0x08, 0x11, 0x1f, 0x88, 0x89, 0x00, 0x0e, 0xdc, 0xcc, 0x6e, 0xe6, 0xdd, 0xdd, 0xd9, 0x99,
+    0xbb, 0xbb, 0x67, 0x63, 0x6e, 0x0e, 0xec, 0xcc, 0xdd, 0xdc, 0x99, 0x9f, 0xbb, 0xb9, 0x33, 0x3e,
+];
+
+/// Error for a ROM whose system can be told neither by its header nor by its extension
+#[derive(Debug)]
+pub struct UnknownSystem {
+    /// Abbreviations of the cores supporting the extension of the ROM
+    pub candidates: Vec<&'static str>,
+}
+
+impl std::fmt::Display for UnknownSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot detect the system of the ROM")
+    }
+}
+
+impl std::error::Error for UnknownSystem {}
+
+/// Core for a ROM opened without choosing one.
+/// Its header is trusted first, then its extension.
+fn detect_core(ext: &str, data: &[u8], config: &Config) -> Result<&'static EmulatorCores> {
+    let by_header = EMULATOR_CORES
+        .iter()
+        .filter(|core| core.matches_header(data))
+        .collect::<Vec<_>>();
+    if let [core] = by_header[..] {
+        return Ok(core);
+    }
+
+    let by_ext = EMULATOR_CORES
+        .iter()
+        .filter(|core| core.core_info().file_extensions.contains(&ext))
+        .collect::<Vec<_>>();
+    if let Some(abbrev) = config.core_selection.get(ext) {
+        if let Some(core) = by_ext.iter().find(|core| core.core_info().abbrev == abbrev) {
+            return Ok(*core);
+        }
+    }
+    if let [core] = by_ext[..] {
+        return Ok(core);
+    }
+
+    Err(UnknownSystem {
+        candidates: by_ext.iter().map(|core| core.core_info().abbrev).collect(),
+    }
+    .into())
 }
 
-fn make_core_from_data<T: EmulatorCore + Into<EmulatorEnum>, F: FnMut() -> Result<Vec<u8>>>(
+fn make_core_from_data<T: EmulatorCore + Into<EmulatorEnum>>(
     _: &PhantomData<T>,
     name: &str,
-    ext: &str,
-    mut data: F,
+    data: &[u8],
     config: &Config,
-) -> Option<Result<EmulatorEnum>> {
+) -> Result<EmulatorEnum> {
     let core_info = <T as EmulatorCore>::core_info();
-    if !core_info.file_extensions.contains(&ext) {
-        None?;
+    let backup = load_backup(core_info.abbrev, name, &config.save_dir)?;
+    let mut core = T::try_from_file(data, backup.as_deref(), &config.core_config::<T>())?;
+    if T::supports_clock_multiplier() {
+        core.set_clock_multiplier(config.clock_multiplier(core_info.abbrev));
     }
-
-    let mut f = || {
-        let backup = load_backup(core_info.abbrev, name, &config.save_dir)?;
-        let data = data()?;
-        let core = T::try_from_file(&data, backup.as_deref(), &config.core_config::<T>())?;
-        Ok(core.into())
-    };
-    Some(f())
+    if T::has_bios_menu() && config.should_boot_to_bios(name) {
+        core.boot_to_bios();
+    }
+    if T::multitap_controllers().is_some() && config.multitap_games.contains(name) {
+        core.set_multitap(true);
+    }
+    Ok(core.into())
 }
 
 impl EmulatorEnum {
     pub fn try_new(
         name: &str,
         ext: &str,
-        mut data: impl FnMut() -> Result<Vec<u8>>,
+        data: &[u8],
+        rom_hash: &str,
         config: &Config,
+        core_abbrev: Option<&str>,
     ) -> Result<Self> {
-        for core in EMULATOR_CORES {
-            if let Some(ret) = dispatch_enum!(
-                EmulatorCores,
-                core,
-                core,
-                make_core_from_data(core, name, ext, &mut data, config)
-            ) {
-                return ret;
-            }
-        }
-        bail!("No supported core");
+        let core_abbrev =
+            core_abbrev.or_else(|| config.rom_cores.get(rom_hash).map(|s| s.as_str()));
+
+        let core = match core_abbrev {
+            Some(abbrev) => EMULATOR_CORES
+                .iter()
+                .find(|core| core.core_info().abbrev == abbrev)
+                .ok_or_else(|| anyhow!("No such core: {abbrev}"))?,
+            None => detect_core(ext, data, config)?,
+        };
+        dispatch_enum!(
+            EmulatorCores,
+            core,
+            core,
+            make_core_from_data(core, name, data, config)
+        )
     }
 
     pub fn core_info(&self) -> &CoreInfo {
@@ -140,6 +253,9 @@ impl EmulatorEnum {
     pub fn set_config(&mut self, config: &Config) {
         fn set_config<T: EmulatorCore>(core: &mut T, config: &Config) {
             core.set_config(&config.core_config::<T>());
+            if T::supports_clock_multiplier() {
+                core.set_clock_multiplier(config.clock_multiplier(T::core_info().abbrev));
+            }
         }
         dispatch_enum!(EmulatorEnum, self, core, set_config(core.as_mut(), config));
     }
@@ -148,10 +264,64 @@ impl EmulatorEnum {
         dispatch_enum!(EmulatorEnum, self, core, core.reset());
     }
 
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        dispatch_enum!(EmulatorEnum, self, core, core.set_rng_seed(seed));
+    }
+
+    pub fn supports_soft_reset(&self) -> bool {
+        fn supports_soft_reset<T: EmulatorCore>(_: &T) -> bool {
+            T::supports_soft_reset()
+        }
+        dispatch_enum!(EmulatorEnum, self, core, supports_soft_reset(core.as_ref()))
+    }
+
+    pub fn soft_reset(&mut self) {
+        dispatch_enum!(EmulatorEnum, self, core, core.soft_reset());
+    }
+
     pub fn exec_frame(&mut self, render_graphics: bool) {
         dispatch_enum!(EmulatorEnum, self, core, core.exec_frame(render_graphics));
     }
 
+    pub fn is_monochrome(&self) -> bool {
+        dispatch_enum!(EmulatorEnum, self, core, core.is_monochrome())
+    }
+
+    pub fn pixel_aspect_ratio(&self) -> f64 {
+        dispatch_enum!(EmulatorEnum, self, core, core.pixel_aspect_ratio())
+    }
+
+    pub fn supports_cheats(&self) -> bool {
+        fn supports_cheats<T: EmulatorCore>(_: &T) -> bool {
+            T::supports_cheats()
+        }
+        dispatch_enum!(EmulatorEnum, self, core, supports_cheats(core.as_ref()))
+    }
+
+    pub fn is_valid_cheat(&self, code: &str) -> bool {
+        fn is_valid_cheat<T: EmulatorCore>(_: &T, code: &str) -> bool {
+            T::is_valid_cheat(code)
+        }
+        dispatch_enum!(
+            EmulatorEnum,
+            self,
+            core,
+            is_valid_cheat(core.as_ref(), code)
+        )
+    }
+
+    pub fn set_cheats(&mut self, codes: &[String]) {
+        dispatch_enum!(EmulatorEnum, self, core, core.set_cheats(codes));
+    }
+
+    pub fn rumble(&self) -> f32 {
+        dispatch_enum!(EmulatorEnum, self, core, core.rumble())
+    }
+
+    pub fn frame_rate(&self) -> f64 {
+        dispatch_enum!(EmulatorEnum, self, core, core.frame_rate())
+    }
+
     pub fn frame_buffer(&self) -> &FrameBuffer {
         dispatch_enum!(EmulatorEnum, self, core, core.frame_buffer())
     }
@@ -164,6 +334,23 @@ impl EmulatorEnum {
         dispatch_enum!(EmulatorEnum, self, core, core.set_input(input));
     }
 
+    #[cfg(feature = "link-cable")]
+    pub fn link_output(&mut self) -> Vec<u8> {
+        dispatch_enum!(EmulatorEnum, self, core, core.link_output())
+    }
+
+    #[cfg(feature = "link-cable")]
+    pub fn link_input(&mut self, data: &[u8]) {
+        dispatch_enum!(EmulatorEnum, self, core, core.link_input(data));
+    }
+
+    pub fn state_version(&self) -> u32 {
+        fn state_version<T: EmulatorCore>(_: &T) -> u32 {
+            T::state_version()
+        }
+        dispatch_enum!(EmulatorEnum, self, core, state_version(core.as_ref()))
+    }
+
     pub fn save_state(&self) -> Vec<u8> {
         dispatch_enum!(EmulatorEnum, self, core, core.save_state())
     }
@@ -177,34 +364,78 @@ impl EmulatorEnum {
 pub struct Emulator {
     pub core: EmulatorEnum,
     pub game_name: String,
+    /// Identifies the ROM regardless of its file name
+    pub rom_hash: String,
     pub auto_saved_states: VecDeque<AutoSavedState>,
     total_auto_saved_size: usize,
     prev_auto_saved_frame: usize,
     prev_backup_saved_frame: usize,
+    /// Backup RAM as it is on disk, to detect changes
+    saved_backup: Option<Vec<u8>>,
+    rewind_audio: Vec<i16>,
     save_dir: PathBuf,
     frames: usize,
+    turbo_frac: f32,
+    turbo_frames: usize,
+    /// Frames run by the core, including every fast-forwarded one
+    core_frames: usize,
+    slow_motion_frac: f64,
+    /// State right before the last state load
+    undo_load: Option<Vec<u8>>,
+    /// Not run yet since the game was opened
+    just_opened: bool,
+    /// States before each frame advance while paused, for stepping back
+    step_history: VecDeque<AutoSavedState>,
+    prev_autosave_frame: usize,
+    cheats: Vec<Cheat>,
+    /// Buttons held by the player, before autofire
+    input: InputData,
+    autofire: AutofireState,
+    /// Buttons fed to the core on the last frame
+    fed_input: InputData,
+    movie: Option<MovieMode>,
+    /// Movie events to show to the user
+    movie_messages: Vec<String>,
+    /// Run for benchmarking: no rewind history and no backup RAM written
+    benchmark: bool,
+}
+
+/// Movie being recorded or played back
+pub enum MovieMode {
+    Recording {
+        movie: Movie,
+        path: PathBuf,
+    },
+    Playing {
+        movie: Movie,
+        frame: usize,
+        desynced: bool,
+    },
 }
 
 impl Drop for Emulator {
     fn drop(&mut self) {
-        if let Some(ram) = self.core.backup() {
-            if let Err(err) = save_backup(
-                self.core.core_info().abbrev,
-                &self.game_name,
-                &ram,
-                &self.save_dir,
-            ) {
-                error!("Failed to save backup ram: {err}");
-            }
-        } else {
-            info!("No backup RAM to save");
+        if let Err(err) = self.save_backup() {
+            error!("Failed to save backup ram: {err}");
+        }
+        if let Err(err) = self.stop_movie() {
+            error!("Failed to save movie: {err}");
         }
     }
 }
 
+/// Minimum interval between backup RAM writes, to avoid thrashing the disk
+const BACKUP_FLUSH_INTERVAL_FRAMES: usize = 5 * 60;
+
+/// Frame advances that can be stepped back exactly, before falling back to rewind snapshots
+const STEP_HISTORY_LIMIT: usize = 600;
+
+/// Slot reserved for the rolling autosave, out of reach of manual slot selection
+pub const AUTOSAVE_SLOT: usize = usize::MAX;
+
 pub const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "7z", "rar"];
 
-fn is_archive_file(path: &Path) -> bool {
+pub fn is_archive_file(path: &Path) -> bool {
     path.extension().map_or(false, |ext| {
         let ext = ext.to_string_lossy();
         ARCHIVE_EXTENSIONS.iter().any(|r| *r == ext.as_ref())
@@ -213,31 +444,66 @@ fn is_archive_file(path: &Path) -> bool {
 
 fn try_make_emulator(
     path: &Path,
-    mut data: impl FnMut() -> Result<Vec<u8>>,
+    data: impl FnOnce() -> Result<Vec<u8>>,
     config: &Config,
+    core_abbrev: Option<&str>,
 ) -> Result<Emulator> {
+    // Files without an extension are detected by their header
     let ext = path
         .extension()
-        .ok_or_else(|| anyhow!("Cannot detect file type"))?
-        .to_string_lossy();
+        .map_or_else(String::new, |ext| ext.to_string_lossy().to_string());
 
     let name = path
         .file_stem()
         .ok_or_else(|| anyhow!("Invalid file name"))?
         .to_string_lossy();
 
-    let core = EmulatorEnum::try_new(&name, &ext, &mut data, config)?;
+    let data = data()?;
+    let rom_hash = rom_hash(&data);
+    let core = EmulatorEnum::try_new(&name, &ext, &data, &rom_hash, config, core_abbrev)?;
+    let saved_backup = core.backup();
 
-    Ok(Emulator {
+    let cheats =
+        load_cheats(core.core_info().abbrev, &name, &config.save_dir).unwrap_or_else(|err| {
+            error!("Failed to load cheats: {err}");
+            vec![]
+        });
+
+    let mut ret = Emulator {
         core,
         game_name: name.to_string(),
+        rom_hash,
         auto_saved_states: VecDeque::new(),
         total_auto_saved_size: 0,
         prev_auto_saved_frame: 0,
         prev_backup_saved_frame: 0,
+        saved_backup,
+        rewind_audio: vec![],
         save_dir: config.save_dir.clone(),
         frames: 0,
-    })
+        turbo_frac: 0.0,
+        turbo_frames: 0,
+        core_frames: 0,
+        slow_motion_frac: 0.0,
+        undo_load: None,
+        just_opened: true,
+        step_history: VecDeque::new(),
+        prev_autosave_frame: 0,
+        cheats,
+        input: InputData::default(),
+        autofire: AutofireState::default(),
+        fed_input: InputData::default(),
+        movie: None,
+        movie_messages: vec![],
+        benchmark: false,
+    };
+    ret.apply_cheats();
+    Ok(ret)
+}
+
+/// Key of the ROM in `Config::rom_cores`
+fn rom_hash(data: &[u8]) -> String {
+    format!("{:016x}", state_hash(data))
 }
 
 fn config_ui<T: EmulatorCore>(_: &PhantomData<T>, ui: &mut EguiUi, config: &mut Config) {
@@ -263,6 +529,30 @@ impl Emulator {
         }
     }
 
+    pub fn supports_link_cable(abbrev: &str) -> bool {
+        fn supports_link_cable<T: EmulatorCore>(_: &PhantomData<T>) -> bool {
+            T::supports_link_cable()
+        }
+        for core in EMULATOR_CORES.iter() {
+            if core.core_info().abbrev == abbrev {
+                return dispatch_enum!(EmulatorCores, core, core, supports_link_cable(core));
+            }
+        }
+        false
+    }
+
+    pub fn multitap_controllers(abbrev: &str) -> Option<usize> {
+        fn multitap_controllers<T: EmulatorCore>(_: &PhantomData<T>) -> Option<usize> {
+            T::multitap_controllers()
+        }
+        for core in EMULATOR_CORES.iter() {
+            if core.core_info().abbrev == abbrev {
+                return dispatch_enum!(EmulatorCores, core, core, multitap_controllers(core));
+            }
+        }
+        None
+    }
+
     pub fn default_key_config(abbrev: &str) -> KeyConfig {
         fn default_key_config<T: EmulatorCore>(_: &PhantomData<T>) -> KeyConfig {
             T::default_key_config()
@@ -275,29 +565,55 @@ impl Emulator {
         panic!();
     }
 
-    pub fn try_new(path: &Path, config: &Config) -> Result<Self> {
+    pub fn supports_clock_multiplier(abbrev: &str) -> bool {
+        fn supports_clock_multiplier<T: EmulatorCore>(_: &PhantomData<T>) -> bool {
+            T::supports_clock_multiplier()
+        }
+        EMULATOR_CORES
+            .iter()
+            .find(|core| core.core_info().abbrev == abbrev)
+            .map_or(false, |core| {
+                dispatch_enum!(EmulatorCores, core, core, supports_clock_multiplier(core))
+            })
+    }
+
+    pub fn has_bios_menu(abbrev: &str) -> bool {
+        fn has_bios_menu<T: EmulatorCore>(_: &PhantomData<T>) -> bool {
+            T::has_bios_menu()
+        }
+        EMULATOR_CORES
+            .iter()
+            .find(|core| core.core_info().abbrev == abbrev)
+            .map_or(false, |core| {
+                dispatch_enum!(EmulatorCores, core, core, has_bios_menu(core))
+            })
+    }
+
+    /// Cores which can open the file judging from its extension
+    pub fn candidate_cores(path: &Path) -> Vec<&'static CoreInfo> {
+        let ext = if let Some(ext) = path.extension() {
+            ext.to_string_lossy()
+        } else {
+            return vec![];
+        };
+
+        Self::core_infos()
+            .into_iter()
+            .filter(|core_info| core_info.file_extensions.contains(&ext.as_ref()))
+            .collect()
+    }
+
+    pub fn try_new(path: &Path, config: &Config, core_abbrev: Option<&str>) -> Result<Self> {
         if is_archive_file(path) {
-            let mut f = File::open(path)?;
-
-            let files = compress_tools::list_archive_files(&mut f)?;
-
-            for path in files {
-                let res = try_make_emulator(
-                    Path::new(&path),
-                    || {
-                        let mut data = vec![];
-                        f.seek(SeekFrom::Start(0))?;
-                        compress_tools::uncompress_archive_file(&mut f, &mut data, &path)?;
-                        Ok(data)
-                    },
-                    config,
-                );
-                if res.is_ok() {
-                    return res;
+            let mut last_err = None;
+            for entry in Self::archive_roms(path)? {
+                match Self::try_new_from_archive(path, &entry, config, core_abbrev) {
+                    Ok(ret) => return Ok(ret),
+                    Err(err) => last_err = Some(err),
                 }
             }
 
-            bail!("File does not contain a supported file");
+            Err(last_err.unwrap_or_else(|| anyhow!("File does not contain a supported file")))
         } else {
             try_make_emulator(
                 path,
@@ -306,55 +622,746 @@ impl Emulator {
                     Ok(data)
                 },
                 config,
+                core_abbrev,
             )
         }
     }
 
+    /// Files in the archive that some core can open.
+    /// Entries without a known extension are extracted and judged by their header.
+    pub fn archive_roms(path: &Path) -> Result<Vec<String>> {
+        let mut f = File::open(path)?;
+        let files = compress_tools::list_archive_files(&mut f)?;
+        let mut ret = vec![];
+        for file in files {
+            if file.ends_with('/') {
+                continue;
+            }
+            if !Self::candidate_cores(Path::new(&file)).is_empty() {
+                ret.push(file);
+                continue;
+            }
+            let mut data = vec![];
+            f.seek(SeekFrom::Start(0))?;
+            if compress_tools::uncompress_archive_file(&mut f, &mut data, &file).is_ok()
+                && EMULATOR_CORES.iter().any(|core| core.matches_header(&data))
+            {
+                ret.push(file);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Open `entry` of the archive, extracted in memory
+    pub fn try_new_from_archive(
+        path: &Path,
+        entry: &str,
+        config: &Config,
+        core_abbrev: Option<&str>,
+    ) -> Result<Self> {
+        let mut f = File::open(path)?;
+        try_make_emulator(
+            Path::new(entry),
+            || {
+                let mut data = vec![];
+                f.seek(SeekFrom::Start(0))?;
+                if let Err(err) = compress_tools::uncompress_archive_file(&mut f, &mut data, entry)
+                {
+                    // compress_tools does not tell whether an entry is encrypted,
+                    // so only libarchive's message hints at it
+                    let msg = err.to_string().to_lowercase();
+                    if msg.contains("passphrase") || msg.contains("encrypt") {
+                        bail!("Password-protected archives are not supported");
+                    }
+                    warn!("Failed to extract `{entry}`: {err}");
+                    bail!("Cannot extract `{entry}`. The archive may be damaged or password-protected");
+                }
+                Ok(data)
+            },
+            config,
+            core_abbrev,
+        )
+    }
+
+    /// Keep benchmark runs from measuring rewind snapshots or touching the save files
+    pub fn set_benchmark(&mut self) {
+        self.benchmark = true;
+    }
+
+    /// Power cycle the machine
     pub fn reset(&mut self) {
         self.core.reset();
+        self.after_reset();
     }
 
-    pub fn save_backup(&mut self) -> Result<()> {
-        if let Some(ram) = self.core.backup() {
-            save_backup(
-                self.core.core_info().abbrev,
-                &self.game_name,
-                &ram,
-                &self.save_dir,
-            )?;
+    /// Power cycle the machine with a known seed, for reproducible runs
+    pub fn reset_with_seed(&mut self, seed: u64) {
+        self.core.set_rng_seed(seed);
+        self.reset();
+    }
+
+    /// Press the reset button of the machine.
+    /// Same as `reset` on systems without a distinct soft reset.
+    pub fn soft_reset(&mut self) {
+        self.core.soft_reset();
+        self.after_reset();
+    }
+
+    fn after_reset(&mut self) {
+        self.undo_load = None;
+        self.interrupt_movie();
+        // In case the core drops them on reset
+        self.apply_cheats();
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    /// Register a new enabled cheat, rejecting codes the core does not understand
+    pub fn add_cheat(&mut self, code: &str, description: &str) -> Result<()> {
+        let code = normalize_code(code);
+        if !self.core.supports_cheats() {
+            bail!("Cheats are not supported for this system");
+        }
+        if !self.core.is_valid_cheat(&code) {
+            bail!("Invalid cheat code: {code}");
         }
+        if self.cheats.iter().any(|cheat| cheat.code == code) {
+            bail!("Cheat already registered: {code}");
+        }
+
+        self.cheats.push(Cheat {
+            code,
+            description: description.trim().to_string(),
+            enabled: true,
+        });
+        self.update_cheats()
+    }
+
+    pub fn set_cheat_enabled(&mut self, ix: usize, enabled: bool) -> Result<()> {
+        self.cheats[ix].enabled = enabled;
+        self.update_cheats()
+    }
+
+    pub fn remove_cheat(&mut self, ix: usize) -> Result<()> {
+        self.cheats.remove(ix);
+        self.update_cheats()
+    }
 
+    fn update_cheats(&mut self) -> Result<()> {
+        self.apply_cheats();
+        save_cheats(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            &self.cheats,
+            &self.save_dir,
+        )
+    }
+
+    fn apply_cheats(&mut self) {
+        let codes = self
+            .cheats
+            .iter()
+            .filter(|cheat| cheat.enabled)
+            .map(|cheat| cheat.code.clone())
+            .collect::<Vec<_>>();
+        self.core.set_cheats(&codes);
+    }
+
+    /// Swap back the state from before the last state load.
+    /// Undoing again redoes the load.
+    pub fn undo_load(&mut self) -> Result<bool> {
+        let data = if let Some(data) = self.undo_load.take() {
+            data
+        } else {
+            return Ok(false);
+        };
+        let current = self.core.save_state();
+        self.core.load_state(&data)?;
+        self.undo_load = Some(current);
+        self.interrupt_movie();
+        Ok(true)
+    }
+
+    /// Write the backup RAM to disk if it changed since the last write
+    pub fn save_backup(&mut self) -> Result<()> {
         self.prev_backup_saved_frame = self.frames;
+        if self.benchmark {
+            return Ok(());
+        }
+
+        let ram = match self.core.backup() {
+            // Never overwrite an existing save with nothing
+            Some(ram) if !ram.is_empty() => ram,
+            _ => return Ok(()),
+        };
+        if self.saved_backup.as_ref() == Some(&ram) {
+            return Ok(());
+        }
+
+        save_backup(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            &ram,
+            &self.save_dir,
+        )?;
+        self.saved_backup = Some(ram);
         Ok(())
     }
 
-    pub fn push_auto_save(&mut self) {
-        let saved_state = AutoSavedState {
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn core_frames(&self) -> usize {
+        self.core_frames
+    }
+
+    /// Number of frames run in the last fast-forward tick
+    pub fn turbo_frames(&self) -> usize {
+        self.turbo_frames
+    }
+
+    /// Horizontal stretch of the screen for the configured aspect ratio
+    pub fn pixel_aspect(&self, config: &Config) -> f32 {
+        match config.aspect_ratio {
+            AspectRatio::Core => self.core.pixel_aspect_ratio() as f32,
+            AspectRatio::Pixel | AspectRatio::Stretch => 1.0,
+        }
+    }
+
+    /// Number of controllers connected for the current game
+    pub fn controller_count(&self, config: &Config) -> usize {
+        let abbrev = self.core.core_info().abbrev;
+        match Self::multitap_controllers(abbrev) {
+            Some(count) if config.multitap_games.contains(&self.game_name) => count,
+            _ => Self::default_key_config(abbrev).controllers.len(),
+        }
+    }
+
+    fn push_step_history(&mut self) {
+        self.step_history.push_back(AutoSavedState {
             data: self.core.save_state(),
             thumbnail: frame_buffer_to_image(self.core.frame_buffer()),
+            audio: vec![],
+            frame: self.frames,
+            core_frame: self.core_frames,
+        });
+        if self.step_history.len() > STEP_HISTORY_LIMIT {
+            self.step_history.pop_front();
+        }
+    }
+
+    /// Undo the last frame advance, or go back to the latest rewind snapshot
+    /// if there is none. Returns the screen of the restored state.
+    pub fn step_back(&mut self) -> Result<Option<Image>> {
+        let state = match self.step_history.pop_back() {
+            Some(state) => state,
+            None => match self.auto_saved_states.pop_back() {
+                Some(state) => state,
+                None => return Ok(None),
+            },
+        };
+        self.load_auto_saved_state(&state)?;
+        Ok(Some(state.thumbnail))
+    }
+
+    /// Go back to a rewind snapshot, along with the frame count it was taken at
+    pub fn load_auto_saved_state(&mut self, state: &AutoSavedState) -> Result<()> {
+        self.core.load_state(&decompress_state(&state.data)?)?;
+        self.frames = state.frame;
+        self.core_frames = state.core_frame;
+        self.prev_auto_saved_frame = self.prev_auto_saved_frame.min(state.frame);
+        // Audio since the snapshot belongs to the undone timeline
+        self.rewind_audio.clear();
+        self.interrupt_movie();
+        Ok(())
+    }
+
+    /// State data for a rewind snapshot
+    fn rewind_state_data(&self, config: &Config) -> Vec<u8> {
+        let data = self.core.save_state();
+        if !config.compress_rewind_states {
+            return data;
+        }
+        compress_state(&data).unwrap_or_else(|err| {
+            error!("Failed to compress state: {err}");
+            data
+        })
+    }
+
+    pub fn push_auto_save(&mut self, config: &Config) {
+        let saved_state = AutoSavedState {
+            data: self.rewind_state_data(config),
+            thumbnail: frame_buffer_to_image(self.core.frame_buffer()),
+            audio: std::mem::take(&mut self.rewind_audio),
+            frame: self.frames,
+            core_frame: self.core_frames,
         };
         self.auto_saved_states.push_back(saved_state);
     }
 
-    pub fn save_state_slot(&self, slot: usize, config: &Config) -> Result<()> {
-        let data = self.core.save_state();
-        save_state(
-            self.core.core_info().abbrev,
-            &self.game_name,
-            slot,
-            &data,
-            &config.save_dir,
-        )
+    /// Set the buttons held for the following frames
+    pub fn set_input(&mut self, input: &InputData) {
+        self.input = InputData {
+            controllers: input.controllers.clone(),
+        };
+    }
+
+    /// Feed the held buttons to the core for one frame, pulsing autofire buttons.
+    /// During movie playback the recorded buttons are fed instead.
+    fn feed_input(&mut self, config: &Config) {
+        let recorded = match &self.movie {
+            Some(MovieMode::Playing { movie, frame, .. }) => {
+                let input = movie.input(*frame);
+                if input.is_none() {
+                    self.movie = None;
+                    self.movie_messages.push(tr("Movie finished").to_string());
+                }
+                input
+            }
+            _ => None,
+        };
+
+        let input = recorded.unwrap_or_else(|| {
+            let rates = config.autofire_rates(self.core.core_info().abbrev);
+            self.autofire
+                .apply(&self.input, rates, self.core.frame_rate())
+        });
+
+        if let Some(MovieMode::Recording { movie, .. }) = &mut self.movie {
+            if let Err(err) = movie.push_input(&input) {
+                error!("Failed to record movie: {err}");
+                self.movie = None;
+                self.movie_messages
+                    .push(tr("Failed to record movie").to_string());
+            }
+        }
+
+        self.core.set_input(&input);
+        self.fed_input = input;
+    }
+
+    /// Run a single frame of the core with the current input
+    fn exec_frame(&mut self, render_graphics: bool, config: &Config) {
+        self.feed_input(config);
+        self.core.exec_frame(render_graphics);
+        self.core_frames += 1;
+
+        match &mut self.movie {
+            Some(MovieMode::Recording { movie, .. }) => {
+                let frames = movie.frames();
+                movie.push_hash(frames, || self.core.save_state());
+            }
+            Some(MovieMode::Playing {
+                movie,
+                frame,
+                desynced,
+            }) => {
+                *frame += 1;
+                if !*desynced && movie.check_hash(*frame, || self.core.save_state()) == Some(true) {
+                    *desynced = true;
+                    self.movie_messages
+                        .push(trf("Movie desynced at frame {}", &[&*frame]));
+                }
+            }
+            None => {}
+        }
+
+        if config.log_state_hashes
+            && self.core_frames % config.state_hash_interval_frames.max(1) == 0
+        {
+            debug!(
+                "{} frame {}: state hash {:016x}",
+                self.game_name,
+                self.core_frames,
+                state_hash(&self.core.save_state())
+            );
+        }
+    }
+
+    pub fn fed_input(&self) -> &InputData {
+        &self.fed_input
+    }
+
+    /// Run a single frame, keeping the frame count and rewind history up to date
+    pub fn step_frame(&mut self, render_graphics: bool, config: &Config) {
+        self.exec_frame(render_graphics, config);
+        self.frames += 1;
+        if self.benchmark {
+            return;
+        }
+
+        if config.rewind_audio == RewindAudio::Reverse {
+            self.record_rewind_audio();
+        }
+
+        self.auto_save_if_needed(config);
+    }
+
+    fn auto_save_if_needed(&mut self, config: &Config) {
+        let frame_rate = self.core.frame_rate();
+        let elapsed = self.frames as f64 / frame_rate;
+        let need_more = self.total_auto_saved_size
+            < (elapsed * config.auto_state_save_rate as f64).floor() as usize;
+        let enough_span = self.prev_auto_saved_frame + config.minimum_auto_save_span < self.frames;
+
+        if need_more && enough_span {
+            let saved_state = AutoSavedState {
+                data: self.rewind_state_data(config),
+                thumbnail: frame_buffer_to_image(self.core.frame_buffer()),
+                audio: std::mem::take(&mut self.rewind_audio),
+                frame: self.frames,
+                core_frame: self.core_frames,
+            };
+
+            let state_size = saved_state.size();
+            self.total_auto_saved_size += state_size;
+            self.prev_auto_saved_frame = self.frames;
+
+            self.auto_saved_states.push_back(saved_state);
+            if self.auto_saved_states.len() * state_size > config.auto_state_save_limit {
+                self.auto_saved_states.pop_front();
+            }
+
+            // Evict snapshots older than the rewind buffer length, in emulated time
+            let keep_frames = (config.rewind_buffer_seconds as f64 * frame_rate) as usize;
+            while self.auto_saved_states.front().map_or(false, |state| {
+                state.core_frame + keep_frames < self.core_frames
+            }) {
+                self.auto_saved_states.pop_front();
+            }
+        }
+    }
+
+    fn record_rewind_audio(&mut self) {
+        let audio_buffer = self.core.audio_buffer();
+        self.rewind_audio.extend(
+            audio_buffer
+                .samples
+                .iter()
+                .flat_map(|sample| [sample.left, sample.right]),
+        );
+
+        // Keep at most 10 seconds of audio between two snapshots
+        let limit = audio_buffer.sample_rate as usize * audio_buffer.channels as usize * 10;
+        if self.rewind_audio.len() > limit {
+            let excess = self.rewind_audio.len() - limit;
+            self.rewind_audio.drain(..excess);
+        }
+    }
+
+    /// State tagged with the core and its format version, for writing to disk
+    fn tagged_state(&self) -> Vec<u8> {
+        tag_state(
+            self.core.core_info().abbrev,
+            self.core.state_version(),
+            &self.core.save_state(),
+        )
+    }
+
+    /// Load a state read from disk, refusing one made by another core or format version.
+    /// The machine is left as it was if the core fails to load it.
+    fn load_tagged_state(&mut self, data: &[u8]) -> Result<()> {
+        let data = untag_state(
+            self.core.core_info().abbrev,
+            self.core.state_version(),
+            data,
+        )?;
+        let current = self.core.save_state();
+        if let Err(err) = self.core.load_state(data) {
+            self.core.load_state(&current)?;
+            return Err(err);
+        }
+        self.undo_load = Some(current);
+        self.interrupt_movie();
+        Ok(())
+    }
+
+    pub fn save_state_slot(&self, slot: usize, config: &Config) -> Result<()> {
+        let mut data = self.tagged_state();
+        if config.compress_states {
+            data = compress_state(&data)?;
+        }
+        save_state(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &data,
+            &config.save_dir,
+        )?;
+
+        save_state_thumbnail(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            self.core.frame_buffer(),
+            &config.save_dir,
+        )?;
+
+        let mut meta = self.state_meta(slot, config).unwrap_or_default();
+        if !config.keep_state_labels {
+            meta.label = None;
+        }
+        self.fill_state_meta(&mut meta);
+        save_state_meta(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &meta,
+            &config.save_dir,
+        )
+    }
+
+    fn fill_state_meta(&self, meta: &mut StateMeta) {
+        meta.rom_name = Some(self.game_name.clone());
+        meta.rom_hash = Some(self.rom_hash.clone());
+        meta.core = Some(self.core.core_info().system_name.to_string());
+        meta.saved_at = Some(Local::now().format("%Y-%m-%d %H:%M").to_string());
+        meta.version = Some(env!("CARGO_PKG_VERSION").to_string());
+    }
+
+    /// Export the current state to a file of the user's choice
+    pub fn save_state_file(&self, path: &Path) -> Result<()> {
+        let mut meta = StateMeta::default();
+        self.fill_state_meta(&mut meta);
+        save_state_file(path, &self.tagged_state(), &meta)
+    }
+
+    /// Import a state exported by `save_state_file`.
+    /// Returns the ROM name recorded in the file if it differs from the running game.
+    pub fn load_state_file(&mut self, path: &Path) -> Result<Option<String>> {
+        let (data, meta) = load_state_file(path)?;
+        let system_name = self.core.core_info().system_name;
+        if let Some(core) = meta.core.as_ref().filter(|core| *core != system_name) {
+            bail!("State is for another system: {core}");
+        }
+
+        self.load_tagged_state(&data)?;
+
+        Ok(meta.rom_name.filter(|rom_name| *rom_name != self.game_name))
+    }
+
+    pub fn autosave(&mut self, config: &Config) -> Result<()> {
+        self.save_state_slot(AUTOSAVE_SLOT, config)?;
+        self.prev_autosave_frame = self.frames;
+        Ok(())
+    }
+
+    pub fn has_autosave(&self, config: &Config) -> bool {
+        matches!(
+            state_date(
+                self.core.core_info().abbrev,
+                &self.game_name,
+                AUTOSAVE_SLOT,
+                &config.save_dir,
+            ),
+            Ok(Some(_))
+        )
+    }
+
+    pub fn has_state_slot(&self, slot: usize, config: &Config) -> bool {
+        matches!(
+            state_date(
+                self.core.core_info().abbrev,
+                &self.game_name,
+                slot,
+                &config.save_dir,
+            ),
+            Ok(Some(_))
+        )
+    }
+
+    /// Whether the slot was saved with another ROM of the same name, such as another revision
+    pub fn state_rom_mismatch(&self, slot: usize, config: &Config) -> bool {
+        self.state_meta(slot, config)
+            .ok()
+            .and_then(|meta| meta.rom_hash)
+            .map_or(false, |rom_hash| rom_hash != self.rom_hash)
+    }
+
+    pub fn delete_state_slot(&self, slot: usize, config: &Config) -> Result<()> {
+        delete_state(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &config.save_dir,
+        )
+    }
+
+    pub fn state_thumbnail(
+        &self,
+        slot: usize,
+        config: &Config,
+    ) -> Result<Option<image::RgbaImage>> {
+        load_state_thumbnail(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &config.save_dir,
+        )
+    }
+
+    pub fn state_meta(&self, slot: usize, config: &Config) -> Result<StateMeta> {
+        load_state_meta(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &config.save_dir,
+        )
+    }
+
+    /// Slot number and its label, for messages
+    pub fn state_slot_title(&self, slot: usize, config: &Config) -> String {
+        match self
+            .state_meta(slot, config)
+            .ok()
+            .and_then(|meta| meta.label)
+        {
+            Some(label) => format!("#{slot} \"{label}\""),
+            None => format!("#{slot}"),
+        }
+    }
+
+    pub fn set_state_label(
+        &self,
+        slot: usize,
+        label: Option<String>,
+        config: &Config,
+    ) -> Result<()> {
+        let mut meta = self.state_meta(slot, config)?;
+        meta.label = label;
+        save_state_meta(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &meta,
+            &config.save_dir,
+        )
+    }
+
+    pub fn load_state_slot(&mut self, slot: usize, config: &Config) -> Result<()> {
+        let data = load_state(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &config.save_dir,
+        )?;
+        self.load_tagged_state(&decompress_state(&data)?)
+    }
+
+    /// Load the slot the game was last saved into, if there is one.
+    /// Returns the slot on success.
+    fn resume_last_state(
+        &mut self,
+        persistent_state: &PersistentState,
+        config: &Config,
+    ) -> Result<Option<usize>> {
+        let slot = match persistent_state.last_state_slot(&self.game_name) {
+            Some(slot) => slot,
+            None => return Ok(None),
+        };
+        let saved = state_date(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            slot,
+            &config.save_dir,
+        )?;
+        if saved.is_none() {
+            return Ok(None);
+        }
+        self.load_state_slot(slot, config)?;
+        Ok(Some(slot))
+    }
+
+    pub fn movie_mode(&self) -> Option<&MovieMode> {
+        self.movie.as_ref()
+    }
+
+    /// Start logging the input of every frame from the current state
+    pub fn start_movie_recording(&mut self, config: &Config) -> Result<()> {
+        if self.movie.is_some() {
+            bail!("A movie is already running");
+        }
+        let path = movie_file_path(&self.game_name, &config.recording_dir)?;
+        let movie = Movie::new(
+            self.core.core_info().abbrev,
+            &self.game_name,
+            self.tagged_state(),
+        );
+        self.movie = Some(MovieMode::Recording { movie, path });
+        Ok(())
+    }
+
+    /// Load the initial state of the movie and replay its inputs instead of the live ones
+    pub fn play_movie(&mut self, path: &Path) -> Result<()> {
+        if matches!(self.movie, Some(MovieMode::Recording { .. })) {
+            bail!("Stop recording the movie first");
+        }
+
+        let movie = Movie::load(path)?;
+        if movie.core() != self.core.core_info().abbrev {
+            bail!("Movie is for another system: {}", movie.core());
+        }
+        if movie.game_name() != self.game_name {
+            self.movie_messages.push(trf(
+                "Warning: movie was recorded with {}",
+                &[&movie.game_name()],
+            ));
+        }
+
+        // Movies recorded before states were tagged are loaded as they are
+        let initial_state = untag_state(
+            self.core.core_info().abbrev,
+            self.core.state_version(),
+            movie.initial_state(),
+        )?;
+        self.core.load_state(initial_state)?;
+        self.movie = Some(MovieMode::Playing {
+            movie,
+            frame: 0,
+            desynced: false,
+        });
+        Ok(())
+    }
+
+    /// Stop the movie, returning the path of the saved recording if it was being recorded
+    pub fn stop_movie(&mut self) -> Result<Option<PathBuf>> {
+        match self.movie.take() {
+            Some(MovieMode::Recording { movie, path }) => {
+                movie.save(&path)?;
+                info!("Saved movie: `{}`", path.display());
+                Ok(Some(path))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Called whenever the machine jumps to another state, which a movie can't follow.
+    /// Playback stops, and a recording is saved up to the jump.
+    fn interrupt_movie(&mut self) {
+        match &self.movie {
+            Some(MovieMode::Playing { .. }) => {
+                self.movie = None;
+                self.movie_messages.push(tr("Movie stopped").to_string());
+            }
+            Some(MovieMode::Recording { .. }) => match self.stop_movie() {
+                Ok(_) => self
+                    .movie_messages
+                    .push(tr("Movie recording stopped and saved").to_string()),
+                Err(err) => {
+                    error!("Failed to save movie: {err}");
+                    self.movie_messages
+                        .push(tr("Failed to save movie").to_string());
+                }
+            },
+            None => {}
+        }
     }
 
-    pub fn load_state_slot(&mut self, slot: usize, config: &Config) -> Result<()> {
-        let data = load_state(
-            self.core.core_info().abbrev,
-            &self.game_name,
-            slot,
-            &config.save_dir,
-        )?;
-        self.core.load_state(&data)
+    pub fn take_movie_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.movie_messages)
     }
 }
 
@@ -363,6 +1370,11 @@ pub struct EmulatorPlugin;
 impl Plugin for EmulatorPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<InputData>()
+            .init_resource::<InputDelay>()
+            .init_resource::<SocdState>()
+            .init_resource::<AutoBrightness>()
+            .init_resource::<FrameBlend>()
+            .init_resource::<TurboAudioBuffer>()
             .add_system_set(
                 SystemSet::on_update(AppState::Running)
                     .with_system(emulator_input_system.label("input")),
@@ -378,38 +1390,229 @@ impl Plugin for EmulatorPlugin {
                     .with_system(emulator_system)
                     .after("input"),
             )
+            .add_system_set(SystemSet::on_update(AppState::Running).with_system(autosave_system))
             .add_system_set(
-                SystemSet::on_exit(AppState::Running).with_system(exit_emulator_system),
-            );
+                SystemSet::on_update(AppState::Running).with_system(movie_message_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Running)
+                    .with_system(screen_layout_system)
+                    .with_system(texture_filter_system),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Running).with_system(exit_emulator_system))
+            .add_system(save_on_close_system)
+            .insert_non_send_resource(Rumble::default())
+            .add_system(rumble_system);
+    }
+}
+
+/// Force feedback effect playing the rumble of the core
+#[derive(Default)]
+struct Rumble {
+    /// Effect for the gamepad, `None` if the gamepad has no force feedback
+    effect: Option<(usize, Option<gilrs::ff::Effect>)>,
+    playing: bool,
+}
+
+fn rumble_effect(gilrs: &mut gilrs::Gilrs, pad: usize) -> Option<gilrs::ff::Effect> {
+    use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Repeat};
+
+    let (id, _) = gilrs
+        .gamepads()
+        .find(|(id, gamepad)| usize::from(*id) == pad && gamepad.is_ff_supported())?;
+
+    EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Strong {
+                magnitude: u16::MAX,
+            },
+            ..Default::default()
+        })
+        .repeat(Repeat::Infinitely)
+        .gamepads(&[id])
+        .finish(gilrs)
+        .map_err(|err| error!("Failed to make rumble effect: {err}"))
+        .ok()
+}
+
+/// Forward the rumble of the core to the first player's gamepad.
+/// It stops while the emulation is not running (paused, in the menu or without a ROM),
+/// so that the gamepad does not keep buzzing.
+fn rumble_system(
+    config: Res<Config>,
+    app_state: Res<State<AppState>>,
+    paused: Res<hotkey::Paused>,
+    emulator: Option<Res<Emulator>>,
+    gamepads: Res<Gamepads>,
+    gilrs: Option<NonSendMut<gilrs::Gilrs>>,
+    mut rumble: NonSendMut<Rumble>,
+) {
+    let intensity = match &emulator {
+        Some(emulator)
+            if config.rumble_enabled && app_state.current() == &AppState::Running && !paused.0 =>
+        {
+            (emulator.core.rumble() * config.rumble_strength).clamp(0.0, 1.0)
+        }
+        _ => 0.0,
+    };
+
+    if intensity <= 0.0 {
+        if rumble.playing {
+            if let Some((_, Some(effect))) = &rumble.effect {
+                if let Err(err) = effect.stop() {
+                    error!("Failed to stop rumble: {err}");
+                }
+            }
+            rumble.playing = false;
+        }
+        return;
+    }
+
+    let pad = if let Some(pad) = config
+        .player_gamepad(0)
+        .or_else(|| gamepads.iter().next().map(|pad| pad.id))
+    {
+        pad
+    } else {
+        return;
+    };
+
+    if rumble.effect.as_ref().map(|(id, _)| *id) != Some(pad) {
+        // Without gilrs, gamepads are not supported at all
+        let mut gilrs = if let Some(gilrs) = gilrs {
+            gilrs
+        } else {
+            return;
+        };
+        rumble.playing = false;
+        rumble.effect = Some((pad, rumble_effect(&mut gilrs, pad)));
+    }
+
+    let effect = if let Some((_, Some(effect))) = &rumble.effect {
+        effect
+    } else {
+        return;
+    };
+    if let Err(err) = effect.set_gain(intensity) {
+        error!("Failed to set rumble strength: {err}");
+    }
+    if !rumble.playing {
+        if let Err(err) = effect.play() {
+            error!("Failed to start rumble: {err}");
+        }
+        rumble.playing = true;
     }
 }
 
+/// Inputs waiting to be fed to the core when `input_delay_frames` is set
+#[derive(Default)]
+pub struct InputDelay(VecDeque<InputData>);
+
+#[allow(clippy::too_many_arguments)]
 pub fn emulator_input_system(
     mut config: ResMut<Config>,
     emulator: Res<Emulator>,
     input_keycode: Res<Input<KeyCode>>,
     input_gamepad_button: Res<Input<GamepadButton>>,
     input_gamepad_axis: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
     mut input: ResMut<InputData>,
+    mut input_delay: ResMut<InputDelay>,
+    mut socd_state: ResMut<SocdState>,
+    modal_open: Res<ModalOpen>,
 ) {
-    *input = config
-        .key_config(emulator.core.core_info().abbrev)
-        .input(&InputState::new(
-            &input_keycode,
-            &input_gamepad_button,
-            &input_gamepad_axis,
-        ));
+    if config.pause_on_modal && modal_open.is_open() {
+        return;
+    }
+
+    let delay = config.input_delay_frames as usize;
+    let controller_count = emulator.controller_count(&config);
+
+    let player_gamepads = config.player_gamepads.clone();
+    let mut current = player_input(
+        config.key_config(emulator.core.core_info().abbrev),
+        InputState::new(&input_keycode, &input_gamepad_button, &input_gamepad_axis)
+            .with_deadzone(config.stick_deadzone),
+        &player_gamepads,
+        &gamepads,
+    );
+    current.controllers.truncate(controller_count);
+    if config.rotate_input {
+        rotate_directions(&mut current, config.screen_rotation);
+    }
+    resolve_socd(&mut current, config.socd_mode, &mut socd_state);
+    input_delay.0.push_back(current);
+
+    if input_delay.0.len() > delay {
+        while input_delay.0.len() > delay + 1 {
+            input_delay.0.pop_front();
+        }
+        *input = input_delay.0.pop_front().unwrap();
+    } else {
+        // Not enough history yet. Feed released buttons.
+        let controllers = input_delay
+            .0
+            .back()
+            .unwrap()
+            .controllers
+            .iter()
+            .map(|keys| keys.iter().map(|(key, _)| (key.clone(), false)).collect())
+            .collect();
+        *input = InputData { controllers };
+    }
 }
 
 pub struct GameScreen(pub Handle<Image>);
 
+/// Seed given by `--seed`, applied to every game opened
+pub struct RngSeed(pub Option<u64>);
+
+#[allow(clippy::too_many_arguments)]
 fn setup_emulator_system(
     mut windows: ResMut<Windows>,
     mut commands: Commands,
-    emulator: Res<Emulator>,
+    mut emulator: ResMut<Emulator>,
+    mut config: ResMut<Config>,
     mut images: ResMut<Assets<Image>>,
     mut event: EventWriter<WindowControlEvent>,
+    winit_windows: NonSend<WinitWindows>,
+    persistent_state: Res<PersistentState>,
+    mut message_event: EventWriter<ShowMessage>,
 ) {
+    let abbrev = emulator.core.core_info().abbrev;
+    if !config.is_game_override_loaded(abbrev, &emulator.game_name) {
+        // The scale of the core belongs to the profile, under the game's settings
+        config.clear_game_override();
+        config.scaling = match config.core_scaling.get(abbrev) {
+            Some(scaling) => *scaling,
+            None => {
+                // First game of the core. Fit the monitor with some room to spare.
+                let scaling = fit_scale(&emulator, &config, &winit_windows, 0.6);
+                config.core_scaling.insert(abbrev.to_string(), scaling);
+                scaling
+            }
+        };
+        config.apply_game_override(abbrev, &emulator.game_name);
+    }
+
+    // Entered again after the menu too, so only resume a game that was just opened.
+    // A game already resumed from the autosave is left alone.
+    if std::mem::take(&mut emulator.just_opened)
+        && config.resume_on_load
+        && emulator.undo_load.is_none()
+    {
+        match emulator.resume_last_state(&persistent_state, &config) {
+            Ok(Some(slot)) => {
+                message_event.send(ShowMessage(trf("Resumed from slot #{}", &[&slot])));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                message_event.send(ShowMessage(trf("Failed to load state: {}", &[&err])));
+                error!("Failed to resume state: {err}");
+            }
+        }
+    }
+
     let width = emulator.core.frame_buffer().width.max(1) as u32;
     let height = emulator.core.frame_buffer().height.max(1) as u32;
     let img = Image::new(
@@ -432,8 +1635,15 @@ fn setup_emulator_system(
         .insert(ScreenSprite);
 
     commands.insert_resource(GameScreen(texture));
+    commands.insert_resource(InputDelay::default());
 
+    // Every way of opening a game goes through here
     let window = windows.get_primary_mut().unwrap();
+    window.set_title(format!(
+        "{WINDOW_TITLE} — {} ({})",
+        emulator.game_name,
+        abbrev.to_uppercase()
+    ));
     window.set_cursor_lock_mode(true);
     window.set_cursor_visibility(false);
 
@@ -454,22 +1664,83 @@ fn resume_emulator_system(
 fn exit_emulator_system(
     mut windows: ResMut<Windows>,
     mut commands: Commands,
+    config: Res<Config>,
+    mut emulator: ResMut<Emulator>,
     screen_entity: Query<Entity, With<ScreenSprite>>,
 ) {
     let window = windows.get_primary_mut().unwrap();
+    window.set_title(WINDOW_TITLE.to_string());
     window.set_cursor_lock_mode(false);
     window.set_cursor_visibility(true);
 
     commands.entity(screen_entity.single()).despawn();
+
+    if let Err(err) = emulator.save_backup() {
+        error!("Failed to save backup ram: {err}");
+    }
+    if config.autosave_interval_seconds.is_some() {
+        if let Err(err) = emulator.autosave(&config) {
+            error!("Failed to autosave: {err}");
+        }
+    }
+}
+
+fn autosave_system(config: Res<Config>, mut emulator: ResMut<Emulator>) {
+    let interval = if let Some(interval) = config.autosave_interval_seconds {
+        (interval as f64 * emulator.core.frame_rate()) as usize
+    } else {
+        return;
+    };
+
+    if emulator.prev_autosave_frame + interval <= emulator.frames {
+        if let Err(err) = emulator.autosave(&config) {
+            error!("Failed to autosave: {err}");
+        }
+        // Don't retry every frame on failure
+        emulator.prev_autosave_frame = emulator.frames;
+    }
+}
+
+fn save_on_close_system(
+    mut close_requested: EventReader<WindowCloseRequested>,
+    config: Res<Config>,
+    app_state: Res<State<AppState>>,
+    emulator: Option<ResMut<Emulator>>,
+) {
+    let mut emulator = match emulator {
+        Some(emulator) if close_requested.iter().count() > 0 => emulator,
+        _ => return,
+    };
+
+    if let Err(err) = emulator.save_backup() {
+        error!("Failed to save backup ram: {err}");
+    }
+    // Leaving `Running` has already written the autosave
+    if config.autosave_interval_seconds.is_some() && app_state.current() != &AppState::Menu {
+        if let Err(err) = emulator.autosave(&config) {
+            error!("Failed to autosave: {err}");
+        }
+    }
 }
 
-struct AudioSource {
+pub struct AudioSource {
     sample_rate: u32,
     channels: u16,
     data: Vec<i16>,
     cursor: usize,
 }
 
+impl AudioSource {
+    pub fn new(sample_rate: u32, channels: u16, data: Vec<i16>) -> Self {
+        Self {
+            sample_rate,
+            channels,
+            data,
+            cursor: 0,
+        }
+    }
+}
+
 impl Iterator for AudioSource {
     type Item = i16;
 
@@ -501,70 +1772,144 @@ impl rodio::Source for AudioSource {
     }
 }
 
+fn movie_message_system(
+    emulator: Option<ResMut<Emulator>>,
+    mut message_event: EventWriter<ShowMessage>,
+) {
+    if let Some(mut emulator) = emulator {
+        for message in emulator.take_movie_messages() {
+            message_event.send(ShowMessage(message));
+        }
+    }
+}
+
+/// Time spent on emulation per tick when fast-forwarding at unlimited speed
+const TURBO_FRAME_BUDGET: Duration = Duration::from_millis(12);
+
 #[allow(clippy::too_many_arguments)]
 fn emulator_system(
-    mut commands: Commands,
     screen: Res<GameScreen>,
-    camera: Query<(Entity, &TiledCamera)>,
     config: Res<Config>,
     mut emulator: ResMut<Emulator>,
     mut images: ResMut<Assets<Image>>,
     input: Res<InputData>,
-    audio_sink: ResMut<rodio::Sink>,
-    is_turbo: Res<hotkey::IsTurbo>,
+    (audio_sink, mut turbo_audio): (ResMut<rodio::Sink>, ResMut<TurboAudioBuffer>),
+    (is_turbo, turbo_ramp): (Res<hotkey::IsTurbo>, Res<hotkey::TurboRamp>),
+    (is_slow_motion, time): (Res<hotkey::IsSlowMotion>, Res<Time>),
+    mut audio_viz: ResMut<AudioVizBuffer>,
+    (mut auto_brightness, mut frame_blend): (ResMut<AutoBrightness>, ResMut<FrameBlend>),
+    modal_open: Res<ModalOpen>,
+    (paused, mut frame_advance, mut step_back): (
+        Res<hotkey::Paused>,
+        ResMut<hotkey::FrameAdvance>,
+        ResMut<hotkey::StepBack>,
+    ),
+    mut message_event: EventWriter<ShowMessage>,
+    (mut recorder, mut audio_recorder): (ResMut<Option<Recorder>>, ResMut<Option<AudioRecorder>>),
+    uncapped: Res<hotkey::Uncapped>,
+    audio_device: Res<AudioDevice>,
 ) {
-    emulator.core.set_input(&*input);
+    if config.pause_on_modal && modal_open.is_open() {
+        return;
+    }
+
+    if paused.0 {
+        if std::mem::take(&mut step_back.0) {
+            match emulator.step_back() {
+                Ok(Some(thumbnail)) => {
+                    let image = images.get_mut(&screen.0).unwrap();
+                    if image.size() == thumbnail.size() {
+                        image.data = thumbnail.data;
+                        apply_gb_palette(image, &emulator.core, &config);
+                    }
+                }
+                Ok(None) => message_event.send(ShowMessage(tr("No more rewind data").to_string())),
+                Err(err) => {
+                    error!("Failed to step back: {err}");
+                    message_event.send(ShowMessage(tr("Failed to step back").to_string()));
+                }
+            }
+        }
+
+        if !std::mem::take(&mut frame_advance.0) {
+            return;
+        }
+
+        emulator.push_step_history();
+        emulator.set_input(&input);
+        emulator.step_frame(true, &config);
+        record_frame(&mut recorder, &emulator.core);
+
+        let fb = emulator.core.frame_buffer();
+        let image = images.get_mut(&screen.0).unwrap();
+        copy_frame_buffer(image, fb);
+        apply_gb_palette(image, &emulator.core, &config);
+        frame_blend.apply(image, &config);
+        auto_brightness.apply(image, fb, &config);
+        return;
+    }
+
+    // Stepping back is only exact between frame advances
+    emulator.step_history.clear();
+
+    emulator.set_input(&input);
 
     let push_audio_queue = |audio_buffer: &AudioBuffer| {
-        let source = AudioSource {
-            sample_rate: audio_buffer.sample_rate,
-            channels: audio_buffer.channels,
-            data: audio_buffer
+        let source = AudioSource::new(
+            audio_buffer.sample_rate,
+            audio_buffer.channels,
+            audio_buffer
                 .samples
                 .iter()
                 .flat_map(|sample| [sample.left, sample.right])
                 .collect(),
-            cursor: 0,
-        };
-        audio_sink.append(source);
+        );
+        // Nothing drains the idle sink used without an audio device
+        if audio_device.is_available() {
+            audio_sink.append(source);
+        }
     };
 
-    if !is_turbo.0 {
-        if audio_sink.len() as u32 > 4 {
+    let queue_frames = config.audio_queue_frames(emulator.core.frame_rate());
+
+    if is_slow_motion.0 && !is_turbo.0 && !uncapped.0 {
+        // Paced by real time instead of the audio queue. Audio is muted rather
+        // than stretched, since a stretched stream would crackle at this rate.
+        let speed = config.slow_motion_speed.clamp(0.05, 1.0) as f64;
+        emulator.slow_motion_frac += time.delta_seconds_f64() * speed * emulator.core.frame_rate();
+        // Don't try to catch up after a hitch
+        emulator.slow_motion_frac = emulator.slow_motion_frac.min(2.0);
+        let mut stepped = false;
+        while emulator.slow_motion_frac >= 1.0 {
+            emulator.slow_motion_frac -= 1.0;
+            emulator.step_frame(true, &config);
+            record_frame(&mut recorder, &emulator.core);
+            stepped = true;
+        }
+        if stepped {
+            let fb = emulator.core.frame_buffer();
+            let image = images.get_mut(&screen.0).unwrap();
+            copy_frame_buffer(image, fb);
+            apply_gb_palette(image, &emulator.core, &config);
+            frame_blend.apply(image, &config);
+            auto_brightness.apply(image, fb, &config);
+        }
+    } else if !is_turbo.0 && !turbo_ramp.is_active(&config) && !uncapped.0 {
+        if audio_sink.len() > queue_frames {
             // execution too fast. wait 1 frame.
             return;
         }
 
-        let mut exec_frame = |render_graphics| {
-            emulator.core.exec_frame(render_graphics);
-            emulator.frames += 1;
-
-            // FIXME
-            let elapsed = emulator.frames as f64 / 60.0;
-            let need_more = emulator.total_auto_saved_size
-                < (elapsed * config.auto_state_save_rate as f64).floor() as usize;
-            let enough_span =
-                emulator.prev_auto_saved_frame + config.minimum_auto_save_span < emulator.frames;
-
-            if need_more && enough_span {
-                let saved_state = AutoSavedState {
-                    data: emulator.core.save_state(),
-                    thumbnail: frame_buffer_to_image(emulator.core.frame_buffer()),
-                };
-
-                let state_size = saved_state.size();
-                emulator.total_auto_saved_size += state_size;
-                emulator.prev_auto_saved_frame = emulator.frames;
-
-                emulator.auto_saved_states.push_back(saved_state);
-                if emulator.auto_saved_states.len() * state_size > config.auto_state_save_limit {
-                    emulator.auto_saved_states.pop_front();
-                }
-            }
+        let mut exec_frame = |render_graphics: bool| {
+            // Recording needs every frame rendered
+            emulator.step_frame(render_graphics || recorder.is_some(), &config);
             push_audio_queue(emulator.core.audio_buffer());
+            record_audio(&mut audio_recorder, emulator.core.audio_buffer());
+            record_frame(&mut recorder, &emulator.core);
         };
 
-        if audio_sink.len() < 2 {
+        // Without audio, the pace is kept by vsync alone
+        if audio_device.is_available() && audio_sink.len() < (queue_frames / 2).max(2) {
             // execution too slow. run 2 frame for supply enough audio samples.
             exec_frame(false);
         }
@@ -574,38 +1919,152 @@ fn emulator_system(
         let fb = emulator.core.frame_buffer();
         let image = images.get_mut(&screen.0).unwrap();
         copy_frame_buffer(image, fb);
+        apply_gb_palette(image, &emulator.core, &config);
+        frame_blend.apply(image, &config);
+        auto_brightness.apply(image, fb, &config);
     } else {
-        for i in 0..config.frame_skip_on_turbo {
-            emulator.core.exec_frame(i == 0);
+        let start = Instant::now();
+        let count = if !uncapped.0 && config.turbo_speed > 0.0 {
+            emulator.turbo_frac += turbo_ramp.speed(&config);
+            let count = emulator.turbo_frac.floor();
+            emulator.turbo_frac -= count;
+            count as usize
+        } else {
+            usize::MAX
+        };
+
+        let mut i = 0;
+        while i < count && (count != usize::MAX || start.elapsed() < TURBO_FRAME_BUDGET) {
+            emulator.exec_frame(i == 0 || recorder.is_some(), &config);
+            turbo_audio.push(emulator.core.audio_buffer());
+            record_frame(&mut recorder, &emulator.core);
+            i += 1;
+        }
+        emulator.turbo_frames = i;
+
+        // The audio of the whole tick fits in a single frame. Keep the sink from
+        // running dry without letting it fall behind the fast-forwarded game.
+        if let Some(audio_buffer) = turbo_audio.take(config.turbo_audio, i) {
             if audio_sink.len() < 2 {
-                push_audio_queue(emulator.core.audio_buffer());
+                push_audio_queue(&audio_buffer);
             }
+            // The recording keeps every tick, even those the sink has no room for
+            record_audio(&mut audio_recorder, &audio_buffer);
         }
         // Update texture
         let fb = emulator.core.frame_buffer();
         let image = images.get_mut(&screen.0).unwrap();
         copy_frame_buffer(image, fb);
+        apply_gb_palette(image, &emulator.core, &config);
+        frame_blend.apply(image, &config);
+        auto_brightness.apply(image, fb, &config);
         emulator.frames += 1;
+
+        if !config.no_rewind_during_turbo {
+            emulator.auto_save_if_needed(&config);
+        }
     }
 
-    {
-        let camera = camera.single();
-        let image = images.get(&screen.0).unwrap();
-        let image_size = image.size();
-        let width = image_size[0] as u32;
-        let height = image_size[1] as u32;
-
-        if (camera.1.tile_count.x, camera.1.tile_count.y) != (width, height) {
-            commands.entity(camera.0).despawn();
-            commands.spawn_bundle(
-                TiledCameraBundle::pixel_cam([width, height]).with_pixels_per_tile([1, 1]),
-            );
+    if config.show_audio_viz {
+        audio_viz.push_samples(&emulator.core.audio_buffer().samples);
+    }
+
+    if emulator.prev_backup_saved_frame + BACKUP_FLUSH_INTERVAL_FRAMES <= emulator.frames {
+        if let Err(err) = emulator.save_backup() {
+            error!("Failed to save backup ram: {err}");
         }
     }
+}
+
+/// Fit the game screen into the window following the scaling settings.
+/// The camera shows more pixels than the screen has to leave black bars around it.
+fn screen_layout_system(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    config: Res<Config>,
+    emulator: Res<Emulator>,
+    camera: Query<(Entity, &TiledCamera)>,
+    mut screen_sprite: Query<&mut Transform, With<ScreenSprite>>,
+) {
+    let fb = emulator.core.frame_buffer();
+    let window = windows.get_primary().unwrap();
+    let sideways = config.screen_rotation.is_sideways();
+    // Measured along the axes of the unrotated screen
+    let window_size = if sideways {
+        Vec2::new(
+            window.physical_height() as f32,
+            window.physical_width() as f32,
+        )
+    } else {
+        Vec2::new(
+            window.physical_width() as f32,
+            window.physical_height() as f32,
+        )
+    };
+    if fb.width == 0 || fb.height == 0 || window_size.min_element() < 1.0 {
+        return;
+    }
+    let screen_size = Vec2::new(fb.width as f32, fb.height as f32);
+
+    let pixel_aspect = if config.aspect_ratio == AspectRatio::Stretch {
+        (window_size.x / window_size.y) / (screen_size.x / screen_size.y)
+    } else {
+        emulator.pixel_aspect(&config)
+    };
+    let display_size = Vec2::new(screen_size.x * pixel_aspect, screen_size.y);
+
+    let fit = (window_size / display_size).min_element();
+    let scale = if config.integer_scale && config.aspect_ratio != AspectRatio::Stretch && fit >= 1.0
+    {
+        fit.floor()
+    } else {
+        fit
+    };
+    let tile_count = (window_size / scale)
+        .floor()
+        .max(display_size.ceil())
+        .as_uvec2();
+    let tile_count = if sideways {
+        UVec2::new(tile_count.y, tile_count.x)
+    } else {
+        tile_count
+    };
+
+    let angle = config.screen_rotation.quarter_turns() as f32 * std::f32::consts::FRAC_PI_2;
+    for mut transform in screen_sprite.iter_mut() {
+        // A bezel moves the screen into its cutout afterwards
+        transform.scale = Vec3::new(pixel_aspect, 1.0, 1.0);
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+        transform.rotation = Quat::from_rotation_z(-angle);
+    }
+
+    let camera = camera.single();
+    if camera.1.tile_count != tile_count {
+        commands.entity(camera.0).despawn();
+        commands.spawn_bundle(
+            TiledCameraBundle::pixel_cam(tile_count.to_array()).with_pixels_per_tile([1, 1]),
+        );
+    }
+}
 
-    if emulator.prev_backup_saved_frame + 60 * 60 <= emulator.frames {
-        emulator.save_backup().unwrap();
+fn texture_filter_system(
+    config: Res<Config>,
+    screen: Res<GameScreen>,
+    mut images: ResMut<Assets<Image>>,
+    mut applied: Local<Option<(Handle<Image>, TextureFilter)>>,
+) {
+    // The screen image is re-created every time the game starts
+    let current = Some((screen.0.clone_weak(), config.texture_filter));
+    if *applied == current {
+        return;
     }
+    *applied = current;
+
+    images.get_mut(&screen.0).unwrap().sampler_descriptor = match config.texture_filter {
+        TextureFilter::Nearest => ImageSampler::nearest(),
+        TextureFilter::Linear => ImageSampler::linear(),
+    };
 }
 
 fn frame_buffer_to_image(frame_buffer: &FrameBuffer) -> Image {
@@ -626,7 +2085,169 @@ fn frame_buffer_to_image(frame_buffer: &FrameBuffer) -> Image {
     image
 }
 
-fn copy_frame_buffer(image: &mut Image, frame_buffer: &FrameBuffer) {
+/// Recolor the gray levels of monochrome games with the configured palette
+fn apply_gb_palette(image: &mut Image, core: &EmulatorEnum, config: &Config) {
+    if !core.is_monochrome() {
+        return;
+    }
+    let colors = if let Some(colors) = config.gb_palette.colors(&config.gb_custom_palette) {
+        colors
+    } else {
+        return;
+    };
+
+    for pixel in image.data.chunks_mut(4) {
+        // 0xFF, 0xAA, 0x55, 0x00 -> 0, 1, 2, 3
+        let shade = 3 - (pixel[0] as usize + 0x2A) / 0x55;
+        pixel[0..3].copy_from_slice(&colors[shade]);
+    }
+}
+
+/// Brightness gain that follows the average luminance of the screen
+pub struct AutoBrightness {
+    gain: f32,
+}
+
+impl Default for AutoBrightness {
+    fn default() -> Self {
+        Self { gain: 1.0 }
+    }
+}
+
+impl AutoBrightness {
+    const TARGET_LUMINANCE: f32 = 0.35;
+    const SAMPLE_STEP: usize = 4;
+
+    fn apply(&mut self, image: &mut Image, frame_buffer: &FrameBuffer, config: &Config) {
+        if !config.auto_brightness {
+            self.gain = 1.0;
+            return;
+        }
+
+        // Sample every few pixels, full resolution is not needed for an average
+        let mut sum = 0.0;
+        let mut count = 0;
+        for y in (0..frame_buffer.height).step_by(Self::SAMPLE_STEP) {
+            for x in (0..frame_buffer.width).step_by(Self::SAMPLE_STEP) {
+                let c = frame_buffer.pixel(x, y);
+                sum += 0.299 * c.r as f32 + 0.587 * c.g as f32 + 0.114 * c.b as f32;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return;
+        }
+        let luminance = sum / count as f32 / 255.0;
+
+        // Only brighten, never darken, and change gradually to avoid flicker
+        let max_gain = 1.0 + 3.0 * config.auto_brightness_strength as f32;
+        let target = (Self::TARGET_LUMINANCE / luminance.max(0.01)).clamp(1.0, max_gain);
+        self.gain += (target - self.gain) * 0.05;
+
+        if self.gain > 1.001 {
+            for pixel in image.data.chunks_mut(4) {
+                for c in &mut pixel[0..3] {
+                    *c = (*c as f32 * self.gain).min(255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Mixes every frame with the previous one, smoothing out flicker used for transparency
+#[derive(Default)]
+pub struct FrameBlend {
+    prev: Vec<u8>,
+}
+
+impl FrameBlend {
+    fn apply(&mut self, image: &mut Image, config: &Config) {
+        if !config.frame_blend {
+            self.prev.clear();
+            return;
+        }
+
+        // Keep the unblended frame, so that older frames don't linger as trails
+        let current = image.data.clone();
+        if self.prev.len() == current.len() {
+            for (c, p) in image.data.iter_mut().zip(&self.prev) {
+                *c = ((*c as u16 + *p as u16) / 2) as u8;
+            }
+        }
+        self.prev = current;
+    }
+}
+
+/// Length of the crossfade between the slices of pitch corrected turbo audio
+const TURBO_AUDIO_CROSSFADE: usize = 64;
+
+/// Audio of the frames run in one turbo tick, squeezed into the length of a single frame
+#[derive(Default)]
+pub struct TurboAudioBuffer {
+    input: AudioBuffer,
+}
+
+impl TurboAudioBuffer {
+    fn push(&mut self, audio_buffer: &AudioBuffer) {
+        self.input.sample_rate = audio_buffer.sample_rate;
+        self.input.channels = audio_buffer.channels;
+        self.input.samples.extend_from_slice(&audio_buffer.samples);
+    }
+
+    /// Audio to play for the `frames` frames pushed since the last call
+    fn take(&mut self, mode: TurboAudio, frames: usize) -> Option<AudioBuffer> {
+        let input = std::mem::replace(
+            &mut self.input,
+            AudioBuffer::new(self.input.sample_rate, self.input.channels),
+        );
+        if frames == 0 || input.samples.is_empty() {
+            return None;
+        }
+
+        let samples = match mode {
+            TurboAudio::Mute => return None,
+            TurboAudio::SpedUp => {
+                // Averaging rather than skipping samples keeps the aliasing down
+                input
+                    .samples
+                    .chunks(frames)
+                    .map(|chunk| {
+                        let len = chunk.len() as i32;
+                        let left = chunk.iter().map(|s| s.left as i32).sum::<i32>() / len;
+                        let right = chunk.iter().map(|s| s.right as i32).sum::<i32>() / len;
+                        AudioSample::new(left as i16, right as i16)
+                    })
+                    .collect()
+            }
+            TurboAudio::PitchCorrected => {
+                // The last frame of the tick as is. Its start is faded in from the start
+                // of the tick, which continues seamlessly from the previous slice.
+                let len = input.samples.len() / frames;
+                let mut samples = input.samples[input.samples.len() - len..].to_vec();
+                let fade = TURBO_AUDIO_CROSSFADE.min(len);
+                let heads = samples.iter_mut().zip(&input.samples).take(fade);
+                for (k, (sample, prev)) in heads.enumerate() {
+                    let w = (k + 1) as f32 / (fade + 1) as f32;
+                    let mix =
+                        |cur: i16, prev: i16| (prev as f32 * (1.0 - w) + cur as f32 * w) as i16;
+                    *sample = AudioSample::new(
+                        mix(sample.left, prev.left),
+                        mix(sample.right, prev.right),
+                    );
+                }
+                samples
+            }
+        };
+
+        Some(AudioBuffer {
+            sample_rate: input.sample_rate,
+            channels: input.channels,
+            samples,
+        })
+    }
+}
+
+pub fn copy_frame_buffer(image: &mut Image, frame_buffer: &FrameBuffer) {
     if frame_buffer.width == 0 || frame_buffer.height == 0 {
         return;
     }
@@ -0,0 +1,163 @@
+use bevy::{
+    asset::load_internal_asset,
+    prelude::*,
+    reflect::TypeUuid,
+    render::render_resource::{AsBindGroup, ShaderRef, ShaderType},
+    sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
+};
+
+use crate::{
+    app::{AppState, ScreenSprite},
+    config::{ColorFilter, Config, DisplayFilter},
+    core::GameScreen,
+};
+
+const DISPLAY_FILTER_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 0x5a3c_94e1_d07b_2f68);
+
+pub struct DisplayFilterPlugin;
+
+impl Plugin for DisplayFilterPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            DISPLAY_FILTER_SHADER_HANDLE,
+            "../assets/shaders/display_filter.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.add_plugin(Material2dPlugin::<DisplayFilterMaterial>::default())
+            .add_system_to_stage(CoreStage::PostUpdate, display_filter_system.after("bezel"))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Running).with_system(exit_display_filter_system),
+            );
+    }
+}
+
+#[derive(Clone, Default, ShaderType)]
+struct DisplayFilterParams {
+    mode: u32,
+    intensity: f32,
+    texture_size: Vec2,
+    color_mode: u32,
+}
+
+#[derive(Clone, AsBindGroup, TypeUuid)]
+#[uuid = "b6f3d0a2-4c1e-4f7a-9d52-8e0c6a1b3f74"]
+struct DisplayFilterMaterial {
+    #[uniform(0)]
+    params: DisplayFilterParams,
+    #[texture(1)]
+    #[sampler(2)]
+    texture: Handle<Image>,
+}
+
+impl Material2d for DisplayFilterMaterial {
+    fn fragment_shader() -> ShaderRef {
+        DISPLAY_FILTER_SHADER_HANDLE.typed().into()
+    }
+}
+
+/// Quad drawn over the `ScreenSprite` through the filter shader
+#[derive(Component)]
+struct FilteredScreen;
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn display_filter_system(
+    mut commands: Commands,
+    config: Res<Config>,
+    screen: Option<Res<GameScreen>>,
+    images: Res<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DisplayFilterMaterial>>,
+    screen_sprite: Query<(&Visibility, &Transform), (With<ScreenSprite>, Without<FilteredScreen>)>,
+    mut filtered: Query<
+        (
+            &Handle<DisplayFilterMaterial>,
+            &mut Visibility,
+            &mut Transform,
+        ),
+        With<FilteredScreen>,
+    >,
+) {
+    let screen = if let Some(screen) = screen {
+        screen
+    } else {
+        return;
+    };
+    let (screen_visible, screen_transform) =
+        if let Ok((visibility, transform)) = screen_sprite.get_single() {
+            (visibility.is_visible, *transform)
+        } else {
+            return;
+        };
+
+    let mode = match config.display_filter {
+        DisplayFilter::None => 0,
+        DisplayFilter::Crt => 1,
+        DisplayFilter::Scanlines => 2,
+    };
+    let color_mode = match config.color_filter {
+        ColorFilter::None => 0,
+        ColorFilter::Protanopia => 1,
+        ColorFilter::Deuteranopia => 2,
+        ColorFilter::Tritanopia => 3,
+    };
+    let active = mode != 0 || color_mode != 0;
+    let texture_size = images.get(&screen.0).unwrap().size();
+    let params = DisplayFilterParams {
+        mode,
+        intensity: config.scanline_intensity.clamp(0.0, 1.0),
+        texture_size,
+        color_mode,
+    };
+
+    let (material, mut visibility, mut transform) = if let Ok(filtered) = filtered.get_single_mut()
+    {
+        filtered
+    } else {
+        if active {
+            let material = materials.add(DisplayFilterMaterial {
+                params,
+                texture: screen.0.clone(),
+            });
+            commands
+                .spawn_bundle(MaterialMesh2dBundle {
+                    mesh: meshes.add(Mesh::from(shape::Quad::default())).into(),
+                    material,
+                    transform: Transform::from_xyz(0.0, 0.0, 0.5)
+                        .with_scale(texture_size.extend(1.0)),
+                    ..Default::default()
+                })
+                .insert(FilteredScreen);
+        }
+        return;
+    };
+
+    // Follows the screen, which is hidden while rewinding
+    visibility.is_visible = active && screen_visible;
+    if !visibility.is_visible {
+        return;
+    }
+
+    // Follows the aspect ratio correction, rotation and position of the screen
+    transform.scale = texture_size.extend(1.0) * screen_transform.scale;
+    transform.rotation = screen_transform.rotation;
+    transform.translation.x = screen_transform.translation.x;
+    transform.translation.y = screen_transform.translation.y;
+
+    // The screen texture is re-created on every update, so the material
+    // has to be touched every frame to rebuild its bind group
+    let material = materials.get_mut(material).unwrap();
+    material.params = params;
+    material.texture = screen.0.clone();
+}
+
+fn exit_display_filter_system(
+    mut commands: Commands,
+    filtered: Query<Entity, With<FilteredScreen>>,
+) {
+    for entity in filtered.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -1,13 +1,16 @@
 use anyhow::{bail, Result};
 use chrono::prelude::*;
 use log::info;
+use meru_interface::FrameBuffer;
+use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
     fs,
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
-fn atomic_write_file(file: &Path, data: &[u8]) -> Result<()> {
-    use std::io::Write;
+pub fn atomic_write_file(file: &Path, data: &[u8]) -> Result<()> {
     let mut f = tempfile::NamedTempFile::new()?;
     f.write_all(data)?;
     f.persist(file)?;
@@ -37,6 +40,36 @@ fn get_state_file_path(
     Ok(get_save_dir(core_abbrev, save_dir)?.join(format!("{name}-{slot}.state")))
 }
 
+fn get_state_meta_file_path(
+    core_abbrev: &str,
+    name: &str,
+    slot: usize,
+    save_dir: &Path,
+) -> Result<PathBuf> {
+    Ok(get_save_dir(core_abbrev, save_dir)?.join(format!("{name}-{slot}.meta.json")))
+}
+
+fn get_state_thumbnail_file_path(
+    core_abbrev: &str,
+    name: &str,
+    slot: usize,
+    save_dir: &Path,
+) -> Result<PathBuf> {
+    Ok(get_save_dir(core_abbrev, save_dir)?.join(format!("{name}-{slot}.png")))
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateMeta {
+    pub label: Option<String>,
+    pub rom_name: Option<String>,
+    /// `Emulator::rom_hash` of the ROM, telling apart revisions sharing a name
+    pub rom_hash: Option<String>,
+    pub core: Option<String>,
+    pub saved_at: Option<String>,
+    pub version: Option<String>,
+}
+
 pub fn load_backup(core_abbrev: &str, name: &str, save_dir: &Path) -> Result<Option<Vec<u8>>> {
     let path = get_backup_file_path(core_abbrev, name, save_dir)?;
 
@@ -72,6 +105,48 @@ pub fn save_state(
     )
 }
 
+pub fn delete_state(core_abbrev: &str, name: &str, slot: usize, save_dir: &Path) -> Result<()> {
+    for path in [
+        get_state_file_path(core_abbrev, name, slot, save_dir)?,
+        get_state_meta_file_path(core_abbrev, name, slot, save_dir)?,
+        get_state_thumbnail_file_path(core_abbrev, name, slot, save_dir)?,
+    ] {
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn save_state_thumbnail(
+    core_abbrev: &str,
+    name: &str,
+    slot: usize,
+    frame_buffer: &FrameBuffer,
+    save_dir: &Path,
+) -> Result<()> {
+    let mut data = std::io::Cursor::new(vec![]);
+    frame_buffer_to_rgb_image(frame_buffer).write_to(&mut data, image::ImageOutputFormat::Png)?;
+    atomic_write_file(
+        &get_state_thumbnail_file_path(core_abbrev, name, slot, save_dir)?,
+        &data.into_inner(),
+    )
+}
+
+pub fn load_state_thumbnail(
+    core_abbrev: &str,
+    name: &str,
+    slot: usize,
+    save_dir: &Path,
+) -> Result<Option<image::RgbaImage>> {
+    let path = get_state_thumbnail_file_path(core_abbrev, name, slot, save_dir)?;
+    Ok(if path.is_file() {
+        Some(image::open(path)?.to_rgba8())
+    } else {
+        None
+    })
+}
+
 pub fn load_state(core_abbrev: &str, name: &str, slot: usize, save_dir: &Path) -> Result<Vec<u8>> {
     let ret = fs::read(get_state_file_path(core_abbrev, name, slot, save_dir)?)?;
     Ok(ret)
@@ -91,3 +166,205 @@ pub fn state_date(
         Ok(None)
     }
 }
+
+pub fn load_state_meta(
+    core_abbrev: &str,
+    name: &str,
+    slot: usize,
+    save_dir: &Path,
+) -> Result<StateMeta> {
+    let path = get_state_meta_file_path(core_abbrev, name, slot, save_dir)?;
+    Ok(if path.is_file() {
+        serde_json::from_slice(&fs::read(path)?)?
+    } else {
+        StateMeta::default()
+    })
+}
+
+pub fn save_state_meta(
+    core_abbrev: &str,
+    name: &str,
+    slot: usize,
+    meta: &StateMeta,
+    save_dir: &Path,
+) -> Result<()> {
+    atomic_write_file(
+        &get_state_meta_file_path(core_abbrev, name, slot, save_dir)?,
+        &serde_json::to_vec_pretty(meta)?,
+    )
+}
+
+const COMPRESSED_STATE_MAGIC: &[u8; 8] = b"MERUZLIB";
+
+/// Compress a state, prefixed by a magic so that `decompress_state` can tell it from a raw one
+pub fn compress_state(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = flate2::write::ZlibEncoder::new(
+        COMPRESSED_STATE_MAGIC.to_vec(),
+        flate2::Compression::fast(),
+    );
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompress a state made by `compress_state`. Uncompressed states are returned as is.
+pub fn decompress_state(data: &[u8]) -> Result<Cow<[u8]>> {
+    let compressed = if let Some(compressed) = data.strip_prefix(COMPRESSED_STATE_MAGIC) {
+        compressed
+    } else {
+        return Ok(Cow::Borrowed(data));
+    };
+
+    let mut ret = vec![];
+    flate2::read::ZlibDecoder::new(compressed).read_to_end(&mut ret)?;
+    Ok(Cow::Owned(ret))
+}
+
+const STATE_HEADER_MAGIC: &[u8; 8] = b"MERUSTHD";
+
+/// Prefix a state with the core that made it and the version of its format
+pub fn tag_state(core_abbrev: &str, version: u32, data: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(STATE_HEADER_MAGIC.len() + 5 + core_abbrev.len() + data.len());
+    buf.extend_from_slice(STATE_HEADER_MAGIC);
+    buf.extend_from_slice(&version.to_le_bytes());
+    buf.push(core_abbrev.len() as u8);
+    buf.extend_from_slice(core_abbrev.as_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+/// Strip the header added by `tag_state`, checking that it matches the core.
+/// States saved before the header existed are returned as is.
+pub fn untag_state<'a>(core_abbrev: &str, version: u32, data: &'a [u8]) -> Result<&'a [u8]> {
+    let rest = if let Some(rest) = data.strip_prefix(STATE_HEADER_MAGIC) {
+        rest
+    } else {
+        return Ok(data);
+    };
+
+    if rest.len() < 5 {
+        bail!("State is corrupted");
+    }
+    let (state_version, rest) = rest.split_at(4);
+    let state_version = u32::from_le_bytes(state_version.try_into().unwrap());
+    let (abbrev_len, rest) = (rest[0] as usize, &rest[1..]);
+    if rest.len() < abbrev_len {
+        bail!("State is corrupted");
+    }
+    let (abbrev, rest) = rest.split_at(abbrev_len);
+
+    let abbrev = String::from_utf8_lossy(abbrev);
+    if abbrev != core_abbrev {
+        bail!("State is for another core: {abbrev}");
+    }
+    if state_version != version {
+        bail!("State is in an incompatible format (version {state_version}, expected {version})");
+    }
+    Ok(rest)
+}
+
+const STATE_FILE_MAGIC: &[u8; 8] = b"MERUSTAT";
+
+pub const STATE_FILE_EXTENSION: &str = "mstate";
+
+/// Write a state together with its metadata into a single file, for sharing outside the slots
+pub fn save_state_file(path: &Path, data: &[u8], meta: &StateMeta) -> Result<()> {
+    let meta = serde_json::to_vec(meta)?;
+    let mut buf = Vec::with_capacity(STATE_FILE_MAGIC.len() + 8 + meta.len() + data.len());
+    buf.extend_from_slice(STATE_FILE_MAGIC);
+    buf.extend_from_slice(&(meta.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&meta);
+    buf.extend_from_slice(data);
+    fs::write(path, buf)?;
+    info!("Saved state file: `{}`", path.display());
+    Ok(())
+}
+
+pub fn load_state_file(path: &Path) -> Result<(Vec<u8>, StateMeta)> {
+    let buf = fs::read(path)?;
+    let rest = match buf.strip_prefix(STATE_FILE_MAGIC) {
+        Some(rest) if rest.len() >= 8 => rest,
+        _ => bail!("Not a state file"),
+    };
+    let (len, rest) = rest.split_at(8);
+    let len = u64::from_le_bytes(len.try_into().unwrap()) as usize;
+    if rest.len() < len {
+        bail!("State file is truncated");
+    }
+    let (meta, data) = rest.split_at(len);
+    Ok((data.to_vec(), serde_json::from_slice(meta)?))
+}
+
+pub fn frame_buffer_to_rgb_image(frame_buffer: &FrameBuffer) -> image::RgbImage {
+    image::RgbImage::from_fn(
+        frame_buffer.width as u32,
+        frame_buffer.height as u32,
+        |x, y| {
+            let p = frame_buffer.pixel(x as usize, y as usize);
+            image::Rgb([p.r, p.g, p.b])
+        },
+    )
+}
+
+pub fn frame_buffer_to_rgba_image(frame_buffer: &FrameBuffer) -> image::RgbaImage {
+    image::RgbaImage::from_fn(
+        frame_buffer.width as u32,
+        frame_buffer.height as u32,
+        |x, y| {
+            let p = frame_buffer.pixel(x as usize, y as usize);
+            image::Rgba([p.r, p.g, p.b, 255])
+        },
+    )
+}
+
+/// Directory holding the screenshots of a game
+pub fn game_screenshot_dir(dir: &Path, game_name: &str) -> PathBuf {
+    dir.join(game_name)
+}
+
+/// Screenshots of a game, newest first
+pub fn list_screenshots(dir: &Path, game_name: &str) -> Result<Vec<PathBuf>> {
+    let dir = game_screenshot_dir(dir, game_name);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut ret = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "png") {
+            ret.push(path);
+        }
+    }
+    // The file names carry the time they were taken
+    ret.sort();
+    ret.reverse();
+    Ok(ret)
+}
+
+pub fn save_screenshot(frame_buffer: &FrameBuffer, dir: &Path) -> Result<PathBuf> {
+    if !dir.exists() {
+        fs::create_dir_all(dir)?;
+    } else if !dir.is_dir() {
+        bail!("`{}` is not a directory", dir.display());
+    }
+
+    let path = dir.join(format!("meru_{}.png", Local::now().format("%Y%m%d_%H%M%S")));
+
+    frame_buffer_to_rgb_image(frame_buffer).save(&path)?;
+
+    info!("Saved screenshot: `{}`", path.display());
+    Ok(path)
+}
+
+pub fn copy_screenshot(frame_buffer: &FrameBuffer) -> Result<()> {
+    let image = frame_buffer_to_rgba_image(frame_buffer);
+    let mut clipboard = arboard::Clipboard::new()?;
+    clipboard.set_image(arboard::ImageData {
+        width: image.width() as usize,
+        height: image.height() as usize,
+        bytes: Cow::Owned(image.into_raw()),
+    })?;
+
+    info!("Copied screenshot to clipboard");
+    Ok(())
+}
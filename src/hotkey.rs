@@ -1,57 +1,104 @@
 use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
 use enum_iterator::{all, Sequence};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 use crate::{
-    app::{AppState, ShowMessage, UiState, WindowControlEvent},
-    config::Config,
-    core::Emulator,
+    app::{AppState, ModalOpen, Muted, ShowMessage, UiState, WindowControlEvent},
+    config::{Config, PersistentState, ScalePreset, SystemKey},
+    core::{Emulator, MovieMode},
+    file::{copy_screenshot, game_screenshot_dir, save_screenshot},
+    i18n::{tr, trf},
     input::{InputState, KeyConfig},
+    recording::{AudioRecorder, GifRecorder, Recorder},
 };
 
 pub struct HotKeyPlugin;
 
 impl Plugin for HotKeyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(check_hotkey)
-            .add_system(process_hotkey)
+        app.add_system(check_hotkey.label("hotkey"))
+            .add_system(process_hotkey.label("hotkey"))
+            .add_system(turbo_ramp_system.after("hotkey"))
+            .insert_resource(None as Option<PendingReset>)
+            .add_system_set(
+                SystemSet::on_update(AppState::Running).with_system(confirm_reset_system),
+            )
             .add_event::<HotKey>()
-            .insert_resource(IsTurbo(false));
+            .insert_resource(IsTurbo(false))
+            .insert_resource(TurboRamp(0.0))
+            .insert_resource(IsSlowMotion(false))
+            .insert_resource(Paused(false))
+            .insert_resource(FrameAdvance(false))
+            .insert_resource(StepBack(false))
+            .insert_resource(Uncapped(false));
     }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, Sequence)]
 pub enum HotKey {
     Reset,
+    SoftReset,
     Turbo,
     StateSave,
     StateLoad,
     NextSlot,
     PrevSlot,
+    QuickSwap,
     Rewind,
     Menu,
     FullScreen,
     ScaleUp,
     ScaleDown,
+    CycleScale,
+    Pause,
+    FrameAdvance,
+    Screenshot,
+    CopyScreenshot,
+    SaveGif,
+    ToggleRecording,
+    ToggleAudioRecording,
+    Mute,
+    UndoLoad,
+    ToggleFrameLimiter,
+    ToggleMovieRecording,
+    SlowMotion,
+    StepBack,
 }
 
 impl Display for HotKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
             HotKey::Reset => "Reset",
+            HotKey::SoftReset => "Soft Reset",
             HotKey::Turbo => "Turbo",
             HotKey::StateSave => "State Save",
             HotKey::StateLoad => "State Load",
             HotKey::NextSlot => "State Slot Next",
             HotKey::PrevSlot => "State Slot Prev",
+            HotKey::QuickSwap => "Quick Swap A/B",
             HotKey::Rewind => "Start Rewindng",
             HotKey::Menu => "Enter/Leave Menu",
             HotKey::FullScreen => "Fullsceen",
             HotKey::ScaleUp => "Window Scale +",
             HotKey::ScaleDown => "Window Scale -",
+            HotKey::CycleScale => "Window Scale Presets",
+            HotKey::Pause => "Pause/Resume",
+            HotKey::FrameAdvance => "Frame Advance",
+            HotKey::Screenshot => "Screenshot",
+            HotKey::CopyScreenshot => "Copy Screenshot",
+            HotKey::SaveGif => "Save GIF",
+            HotKey::ToggleRecording => "Start/Stop Recording",
+            HotKey::ToggleAudioRecording => "Start/Stop Audio Recording",
+            HotKey::Mute => "Mute/Unmute",
+            HotKey::UndoLoad => "Undo State Load",
+            HotKey::ToggleFrameLimiter => "Frame Limiter On/Off",
+            HotKey::ToggleMovieRecording => "Start/Stop Movie",
+            HotKey::SlowMotion => "Slow Motion",
+            HotKey::StepBack => "Step Back",
         };
-        write!(f, "{s}")
+        write!(f, "{}", tr(s))
     }
 }
 
@@ -63,11 +110,13 @@ impl Default for HotKeys {
         use HotKey::*;
         Self(vec![
             (Reset, all![keycode!(LControl), keycode!(R)]),
+            (SoftReset, all![keycode!(LAlt), keycode!(R)]),
             (Turbo, any![keycode!(Tab), pad_button!(0, LeftTrigger2)]),
             (StateSave, all![keycode!(LControl), keycode!(S)]),
             (StateLoad, all![keycode!(LControl), keycode!(L)]),
             (NextSlot, all![keycode!(LControl), keycode!(N)]),
             (PrevSlot, all![keycode!(LControl), keycode!(P)]),
+            (QuickSwap, all![keycode!(LControl), keycode!(Q)]),
             (
                 Rewind,
                 any![
@@ -82,12 +131,91 @@ impl Default for HotKeys {
                 all![keycode!(LControl), any![keycode!(Plus), keycode!(Equals)]],
             ),
             (ScaleDown, all![keycode!(LControl), keycode!(Minus)]),
+            (CycleScale, all![keycode!(LControl), keycode!(Key0)]),
+            (Pause, keycode!(Pause)),
+            (FrameAdvance, keycode!(Backslash)),
+            (Screenshot, keycode!(F12)),
+            (CopyScreenshot, keycode!(F11)),
+            (SaveGif, keycode!(F10)),
+            (ToggleRecording, keycode!(F9)),
+            (ToggleAudioRecording, keycode!(F8)),
+            (Mute, all![keycode!(LControl), keycode!(M)]),
+            (UndoLoad, all![keycode!(LControl), keycode!(Z)]),
+            (ToggleFrameLimiter, all![keycode!(LControl), keycode!(U)]),
+            (ToggleMovieRecording, keycode!(F7)),
+            (SlowMotion, keycode!(Grave)),
+            (StepBack, keycode!(LBracket)),
         ])
     }
 }
 
 pub struct IsTurbo(pub bool);
 
+/// Progress of the fast-forward speed towards `Config::turbo_speed`, `0.0`-`1.0`.
+/// Follows `IsTurbo` over `Config::turbo_ramp_ms`.
+pub struct TurboRamp(pub f32);
+
+impl TurboRamp {
+    /// Still fast-forwarding while ramping down. Unlimited speed has no ramp.
+    pub fn is_active(&self, config: &Config) -> bool {
+        self.0 > 0.0 && config.turbo_speed > 0.0
+    }
+
+    /// Emulated frames per displayed frame at this point of the ramp
+    pub fn speed(&self, config: &Config) -> f32 {
+        1.0 + (config.turbo_speed - 1.0) * self.0
+    }
+}
+
+/// Slow motion hotkey is held
+pub struct IsSlowMotion(pub bool);
+
+/// Emulation is frozen while staying on the game screen
+pub struct Paused(pub bool);
+
+/// Set to run a single frame while paused
+pub struct FrameAdvance(pub bool);
+
+/// Set to undo a single frame while paused
+pub struct StepBack(pub bool);
+
+/// Frame limiter is off, emulation runs as fast as the host allows
+pub struct Uncapped(pub bool);
+
+/// Reset waiting for confirmation
+pub struct PendingReset {
+    hotkey: HotKey,
+    /// Pause state to go back to when the dialog closes
+    was_paused: bool,
+    /// The dialog was on screen for a frame, so the press that opened it is over
+    shown: bool,
+}
+
+const FRAME_ADVANCE_REPEAT_DELAY: f64 = 0.5;
+const FRAME_ADVANCE_REPEAT_INTERVAL: f64 = 0.1;
+
+fn turbo_ramp_system(
+    config: Res<Config>,
+    is_turbo: Res<IsTurbo>,
+    mut turbo_ramp: ResMut<TurboRamp>,
+    time: Res<Time>,
+) {
+    let step = if config.turbo_ramp_ms == 0 {
+        1.0
+    } else {
+        time.delta_seconds() * 1000.0 / config.turbo_ramp_ms as f32
+    };
+    let ramp = if is_turbo.0 {
+        (turbo_ramp.0 + step).min(1.0)
+    } else {
+        (turbo_ramp.0 - step).max(0.0)
+    };
+    if turbo_ramp.0 != ramp {
+        turbo_ramp.0 = ramp;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn check_hotkey(
     config: Res<Config>,
     input_keycode: Res<Input<KeyCode>>,
@@ -95,8 +223,21 @@ fn check_hotkey(
     input_gamepad_axis: Res<Axis<GamepadAxis>>,
     mut writer: EventWriter<HotKey>,
     mut is_turbo: ResMut<IsTurbo>,
+    mut is_slow_motion: ResMut<IsSlowMotion>,
+    modal_open: Res<ModalOpen>,
+    time: Res<Time>,
+    mut frame_advance_repeat: Local<Option<f64>>,
 ) {
-    let input_state = InputState::new(&input_keycode, &input_gamepad_button, &input_gamepad_axis);
+    if modal_open.is_open() {
+        if config.hold_for_turbo {
+            is_turbo.0 = false;
+        }
+        is_slow_motion.0 = false;
+        return;
+    }
+
+    let input_state = InputState::new(&input_keycode, &input_gamepad_button, &input_gamepad_axis)
+        .with_deadzone(config.stick_deadzone);
 
     for hotkey in all::<HotKey>() {
         if config.hotkeys.just_pressed(&hotkey, &input_state) {
@@ -104,12 +245,114 @@ fn check_hotkey(
         }
     }
 
-    is_turbo.0 = config.hotkeys.pressed(
-        &HotKey::Turbo,
-        &InputState::new(&input_keycode, &input_gamepad_button, &input_gamepad_axis),
-    );
+    // In toggle mode, `process_hotkey` flips it on each press instead
+    if config.hold_for_turbo {
+        is_turbo.0 = config.hotkeys.pressed(&HotKey::Turbo, &input_state);
+    }
+    is_slow_motion.0 = config.hotkeys.pressed(&HotKey::SlowMotion, &input_state);
+
+    // Holding frame advance repeats it after a short delay
+    if config.hotkeys.pressed(&HotKey::FrameAdvance, &input_state) {
+        let now = time.seconds_since_startup();
+        match *frame_advance_repeat {
+            None => *frame_advance_repeat = Some(now + FRAME_ADVANCE_REPEAT_DELAY),
+            Some(next) if now >= next => {
+                writer.send(HotKey::FrameAdvance);
+                *frame_advance_repeat = Some(now + FRAME_ADVANCE_REPEAT_INTERVAL);
+            }
+            _ => {}
+        }
+    } else {
+        *frame_advance_repeat = None;
+    }
+}
+
+fn reset_emulator(
+    emulator: &mut Emulator,
+    hotkey: HotKey,
+    message_event: &mut EventWriter<ShowMessage>,
+) {
+    if hotkey == HotKey::SoftReset {
+        emulator.soft_reset();
+        message_event.send(ShowMessage(if emulator.core.supports_soft_reset() {
+            tr("Soft reset").to_string()
+        } else {
+            tr("Reset machine (no soft reset on this system)").to_string()
+        }));
+    } else {
+        emulator.reset();
+        message_event.send(ShowMessage(tr("Reset machine").to_string()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn confirm_reset_system(
+    config: Res<Config>,
+    mut egui_ctx: ResMut<EguiContext>,
+    mut pending_reset: ResMut<Option<PendingReset>>,
+    mut emulator: ResMut<Emulator>,
+    mut paused: ResMut<Paused>,
+    mut modal_open: ResMut<ModalOpen>,
+    mut message_event: EventWriter<ShowMessage>,
+    input_keycode: Res<Input<KeyCode>>,
+    input_gamepad_button: Res<Input<GamepadButton>>,
+    input_gamepad_axis: Res<Axis<GamepadAxis>>,
+) {
+    let pending = if let Some(pending) = pending_reset.as_mut() {
+        pending
+    } else {
+        return;
+    };
+
+    modal_open.show();
+
+    let mut confirm = None;
+    egui::Window::new(tr("Reset"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_ctx.ctx_mut(), |ui| {
+            ui.label(tr("Reset the game? Unsaved progress will be lost."));
+            ui.horizontal(|ui| {
+                if ui.button(tr("Yes")).clicked() {
+                    confirm = Some(true);
+                }
+                if ui.button(tr("No")).clicked() {
+                    confirm = Some(false);
+                }
+            });
+        });
+
+    if pending.shown && confirm.is_none() {
+        let input_state =
+            InputState::new(&input_keycode, &input_gamepad_button, &input_gamepad_axis)
+                .with_deadzone(config.stick_deadzone);
+
+        if config.hotkeys.just_pressed(&pending.hotkey, &input_state)
+            || config
+                .system_keys
+                .just_pressed(&SystemKey::Ok, &input_state)
+        {
+            confirm = Some(true);
+        } else if config
+            .system_keys
+            .just_pressed(&SystemKey::Cancel, &input_state)
+        {
+            confirm = Some(false);
+        }
+    }
+    pending.shown = true;
+
+    if let Some(confirm) = confirm {
+        let pending = pending_reset.take().unwrap();
+        paused.0 = pending.was_paused;
+        if confirm {
+            reset_emulator(&mut emulator, pending.hotkey, &mut message_event);
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_hotkey(
     mut config: ResMut<Config>,
     mut reader: EventReader<HotKey>,
@@ -118,24 +361,64 @@ fn process_hotkey(
     mut ui_state: ResMut<UiState>,
     mut window_control_event: EventWriter<WindowControlEvent>,
     mut message_event: EventWriter<ShowMessage>,
+    mut paused: ResMut<Paused>,
+    (mut frame_advance, mut step_back): (ResMut<FrameAdvance>, ResMut<StepBack>),
+    (mut recorder, mut audio_recorder, mut gif_recorder): (
+        ResMut<Option<Recorder>>,
+        ResMut<Option<AudioRecorder>>,
+        ResMut<GifRecorder>,
+    ),
+    mut muted: ResMut<Muted>,
+    mut uncapped: ResMut<Uncapped>,
+    mut is_turbo: ResMut<IsTurbo>,
+    mut pending_reset: ResMut<Option<PendingReset>>,
+    mut persistent_state: ResMut<PersistentState>,
+    mut prev_hold_for_turbo: Local<bool>,
 ) {
+    if config.hold_for_turbo && !*prev_hold_for_turbo {
+        // Leave toggle mode without turbo stuck on
+        is_turbo.0 = false;
+    }
+    *prev_hold_for_turbo = config.hold_for_turbo;
+
+    // The slot count may have been lowered in the menu
+    if ui_state.state_save_slot >= config.state_slot_count() {
+        ui_state.state_save_slot = config.state_slot_count() - 1;
+    }
+
     for hotkey in reader.iter() {
         match hotkey {
-            HotKey::Reset => {
+            HotKey::Reset | HotKey::SoftReset if config.confirm_reset => {
+                if emulator.is_some() && pending_reset.is_none() {
+                    *pending_reset = Some(PendingReset {
+                        hotkey: *hotkey,
+                        was_paused: paused.0,
+                        shown: false,
+                    });
+                    paused.0 = true;
+                }
+            }
+            HotKey::Reset | HotKey::SoftReset => {
                 if let Some(emulator) = &mut emulator {
-                    emulator.reset();
-                    message_event.send(ShowMessage("Reset machine".to_string()));
+                    reset_emulator(emulator, *hotkey, &mut message_event);
                 }
             }
             HotKey::StateSave => {
                 if let Some(emulator) = &emulator {
-                    emulator
-                        .save_state_slot(ui_state.state_save_slot, config.as_ref())
-                        .unwrap();
-                    message_event.send(ShowMessage(format!(
-                        "State saved: #{}",
-                        ui_state.state_save_slot
-                    )));
+                    if let Err(e) =
+                        emulator.save_state_slot(ui_state.state_save_slot, config.as_ref())
+                    {
+                        message_event.send(ShowMessage(trf("Failed to save state: {}", &[&e])));
+                        error!("Failed to save state: {}", e);
+                    } else {
+                        persistent_state
+                            .set_last_state_slot(&emulator.game_name, ui_state.state_save_slot);
+                        message_event.send(ShowMessage(trf(
+                            "State saved: {}",
+                            &[&emulator
+                                .state_slot_title(ui_state.state_save_slot, config.as_ref())],
+                        )));
+                    }
                 }
             }
             HotKey::StateLoad => {
@@ -143,34 +426,84 @@ fn process_hotkey(
                     if let Err(e) =
                         emulator.load_state_slot(ui_state.state_save_slot, config.as_ref())
                     {
-                        message_event.send(ShowMessage("Failed to load state".to_string()));
+                        message_event.send(ShowMessage(trf("Failed to load state: {}", &[&e])));
                         error!("Failed to load state: {}", e);
                     } else {
-                        message_event.send(ShowMessage(format!(
-                            "State loaded: #{}",
-                            ui_state.state_save_slot
+                        message_event.send(ShowMessage(trf(
+                            "State loaded: {}",
+                            &[&emulator
+                                .state_slot_title(ui_state.state_save_slot, config.as_ref())],
                         )));
+                        if emulator.state_rom_mismatch(ui_state.state_save_slot, config.as_ref()) {
+                            message_event.send(ShowMessage(
+                                tr("Warning: state was saved with a different ROM").to_string(),
+                            ));
+                        }
+                    }
+                }
+            }
+            HotKey::UndoLoad => {
+                if let Some(emulator) = &mut emulator {
+                    match emulator.undo_load() {
+                        Ok(true) => {
+                            message_event.send(ShowMessage(tr("Reverted load").to_string()));
+                        }
+                        Ok(false) => {
+                            message_event.send(ShowMessage(tr("Nothing to undo").to_string()));
+                        }
+                        Err(e) => {
+                            message_event.send(ShowMessage(tr("Failed to undo load").to_string()));
+                            error!("Failed to undo load: {}", e);
+                        }
                     }
                 }
             }
             HotKey::NextSlot => {
-                ui_state.state_save_slot += 1;
-                message_event.send(ShowMessage(format!(
+                let count = config.state_slot_count();
+                ui_state.state_save_slot = if ui_state.state_save_slot + 1 >= count {
+                    0
+                } else {
+                    ui_state.state_save_slot + 1
+                };
+                message_event.send(ShowMessage(trf(
                     "State slot changed: #{}",
-                    ui_state.state_save_slot
+                    &[&ui_state.state_save_slot],
                 )));
             }
             HotKey::PrevSlot => {
-                ui_state.state_save_slot = ui_state.state_save_slot.saturating_sub(1);
-                message_event.send(ShowMessage(format!(
+                let count = config.state_slot_count();
+                ui_state.state_save_slot = if ui_state.state_save_slot == 0 {
+                    count - 1
+                } else {
+                    (ui_state.state_save_slot - 1).min(count - 1)
+                };
+                message_event.send(ShowMessage(trf(
                     "State slot changed: #{}",
-                    ui_state.state_save_slot
+                    &[&ui_state.state_save_slot],
                 )));
             }
+            HotKey::QuickSwap => {
+                // The active member of the pair is the current slot, so saving goes there
+                ui_state.state_save_slot = config.quick_swap_target(ui_state.state_save_slot);
+                let slot = ui_state.state_save_slot;
+                if let Some(emulator) = &mut emulator {
+                    if !emulator.has_state_slot(slot, config.as_ref()) {
+                        message_event.send(ShowMessage(trf("Quick swap: #{} (empty)", &[&slot])));
+                    } else if let Err(e) = emulator.load_state_slot(slot, config.as_ref()) {
+                        message_event.send(ShowMessage(trf("Failed to load state: {}", &[&e])));
+                        error!("Failed to load state: {}", e);
+                    } else {
+                        message_event.send(ShowMessage(trf(
+                            "Quick swap: {}",
+                            &[&emulator.state_slot_title(slot, config.as_ref())],
+                        )));
+                    }
+                }
+            }
             HotKey::Rewind => {
                 if app_state.current() == &AppState::Running {
                     let emulator = emulator.as_mut().unwrap();
-                    emulator.push_auto_save();
+                    emulator.push_auto_save(&config);
                     app_state.push(AppState::Rewinding).unwrap();
                 }
             }
@@ -185,15 +518,223 @@ fn process_hotkey(
                 window_control_event.send(WindowControlEvent::ToggleFullscreen);
             }
             HotKey::ScaleUp => {
-                config.scaling += 1;
-                window_control_event.send(WindowControlEvent::Restore);
+                window_control_event.send(WindowControlEvent::ChangeScale(config.scaling + 1));
             }
             HotKey::ScaleDown => {
-                config.scaling = (config.scaling - 1).max(1);
-                window_control_event.send(WindowControlEvent::Restore);
+                window_control_event
+                    .send(WindowControlEvent::ChangeScale((config.scaling - 1).max(1)));
+            }
+            HotKey::CycleScale => {
+                if !config.scale_presets.is_empty() {
+                    let ix = match ui_state.scale_preset {
+                        Some(ix) => ix + 1,
+                        None => config
+                            .scale_presets
+                            .iter()
+                            .position(|preset| *preset == ScalePreset::Scale(config.scaling))
+                            .map_or(0, |ix| ix + 1),
+                    } % config.scale_presets.len();
+                    ui_state.scale_preset = Some(ix);
+
+                    match config.scale_presets[ix] {
+                        ScalePreset::Scale(scale) => {
+                            window_control_event.send(WindowControlEvent::ChangeScale(scale));
+                            message_event.send(ShowMessage(trf("Scale: {}x", &[&scale])));
+                        }
+                        ScalePreset::Fit => window_control_event.send(WindowControlEvent::FitScale),
+                    }
+                }
+            }
+            HotKey::Pause => {
+                if app_state.current() == &AppState::Running {
+                    paused.0 = !paused.0;
+                    let msg = if paused.0 { "Paused" } else { "Resumed" };
+                    message_event.send(ShowMessage(tr(msg).to_string()));
+                }
+            }
+            HotKey::FrameAdvance => {
+                if app_state.current() == &AppState::Running && paused.0 {
+                    frame_advance.0 = true;
+                }
+            }
+            HotKey::StepBack => {
+                if app_state.current() == &AppState::Running && paused.0 {
+                    step_back.0 = true;
+                }
+            }
+            HotKey::Screenshot => {
+                if let Some(emulator) = &emulator {
+                    let dir = game_screenshot_dir(&config.screenshot_dir, &emulator.game_name);
+                    match save_screenshot(emulator.core.frame_buffer(), &dir) {
+                        Ok(path) => {
+                            let name = path.file_name().unwrap().to_string_lossy();
+                            message_event.send(ShowMessage(trf("Screenshot saved: {}", &[&name])));
+                        }
+                        Err(err) => {
+                            error!("Failed to save screenshot: {err}");
+                            message_event
+                                .send(ShowMessage(tr("Failed to save screenshot").to_string()));
+                        }
+                    }
+                }
+            }
+            HotKey::CopyScreenshot => {
+                if let Some(emulator) = &emulator {
+                    match copy_screenshot(emulator.core.frame_buffer()) {
+                        Ok(()) => {
+                            message_event.send(ShowMessage(tr("Copied to clipboard").to_string()));
+                        }
+                        Err(err) => {
+                            error!("Failed to copy screenshot: {err}");
+                            message_event
+                                .send(ShowMessage(trf("Failed to copy to clipboard: {}", &[&err])));
+                        }
+                    }
+                }
+            }
+            HotKey::SaveGif => {
+                if gif_recorder.is_exporting() {
+                    message_event
+                        .send(ShowMessage(tr("Still saving the previous GIF").to_string()));
+                } else if gif_recorder.is_empty() {
+                    message_event.send(ShowMessage(tr("No frames to save").to_string()));
+                } else {
+                    gif_recorder.start_export(&config.screenshot_dir);
+                    message_event.send(ShowMessage(tr("Saving GIF...").to_string()));
+                }
+            }
+            HotKey::ToggleFrameLimiter => {
+                uncapped.0 = !uncapped.0;
+                let msg = if uncapped.0 {
+                    "Frame limiter off"
+                } else {
+                    "Frame limiter on"
+                };
+                message_event.send(ShowMessage(tr(msg).to_string()));
+            }
+            HotKey::Mute => {
+                muted.0 = !muted.0;
+                message_event.send(ShowMessage(if muted.0 {
+                    tr("Muted").to_string()
+                } else {
+                    trf(
+                        "Unmuted: {}%",
+                        &[&(config.volume.clamp(0.0, 1.0) * 100.0).round()],
+                    )
+                }));
+            }
+            HotKey::ToggleRecording => {
+                if let Some(rec) = recorder.take() {
+                    match rec.finish() {
+                        Ok(path) => {
+                            message_event
+                                .send(ShowMessage(trf("Recording saved: {}", &[&path.display()])));
+                        }
+                        Err(err) => {
+                            error!("Failed to finish recording: {err}");
+                            message_event
+                                .send(ShowMessage(tr("Failed to save recording").to_string()));
+                        }
+                    }
+                } else if emulator.is_some() {
+                    match Recorder::start(&config.recording_dir) {
+                        Ok(rec) => {
+                            *recorder = Some(rec);
+                            message_event.send(ShowMessage(tr("Recording started").to_string()));
+                        }
+                        Err(err) => {
+                            error!("Failed to start recording: {err}");
+                            message_event
+                                .send(ShowMessage(tr("Failed to start recording").to_string()));
+                        }
+                    }
+                }
+            }
+            HotKey::ToggleAudioRecording => {
+                if let Some(rec) = audio_recorder.take() {
+                    match rec.finish() {
+                        Ok(path) => {
+                            message_event.send(ShowMessage(trf(
+                                "Audio recording saved: {}",
+                                &[&path.display()],
+                            )));
+                        }
+                        Err(err) => {
+                            error!("Failed to finish audio recording: {err}");
+                            message_event.send(ShowMessage(
+                                tr("Failed to save audio recording").to_string(),
+                            ));
+                        }
+                    }
+                } else if emulator.is_some() {
+                    match AudioRecorder::start(&config.recording_dir) {
+                        Ok(rec) => {
+                            message_event.send(ShowMessage(trf(
+                                "Audio recording started: {}",
+                                &[&rec.path().display()],
+                            )));
+                            *audio_recorder = Some(rec);
+                        }
+                        Err(err) => {
+                            error!("Failed to start audio recording: {err}");
+                            message_event.send(ShowMessage(
+                                tr("Failed to start audio recording").to_string(),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            HotKey::ToggleMovieRecording => {
+                if let Some(emulator) = &mut emulator {
+                    match emulator.movie_mode() {
+                        Some(MovieMode::Recording { .. }) => match emulator.stop_movie() {
+                            Ok(path) => {
+                                let name = path
+                                    .as_ref()
+                                    .and_then(|path| path.file_name())
+                                    .map(|name| name.to_string_lossy().to_string())
+                                    .unwrap_or_default();
+                                message_event.send(ShowMessage(trf("Movie saved: {}", &[&name])));
+                            }
+                            Err(err) => {
+                                error!("Failed to save movie: {err}");
+                                message_event
+                                    .send(ShowMessage(tr("Failed to save movie").to_string()));
+                            }
+                        },
+                        Some(MovieMode::Playing { .. }) => {
+                            // Playback ends without saving anything
+                            let _ = emulator.stop_movie();
+                            message_event.send(ShowMessage(tr("Movie stopped").to_string()));
+                        }
+                        None => match emulator.start_movie_recording(config.as_ref()) {
+                            Ok(()) => {
+                                message_event
+                                    .send(ShowMessage(tr("Movie recording started").to_string()));
+                            }
+                            Err(err) => {
+                                error!("Failed to start movie: {err}");
+                                message_event
+                                    .send(ShowMessage(tr("Failed to start movie").to_string()));
+                            }
+                        },
+                    }
+                }
             }
 
-            HotKey::Turbo => {}
+            HotKey::Turbo => {
+                if !config.hold_for_turbo && emulator.is_some() {
+                    is_turbo.0 = !is_turbo.0;
+                    let msg = if is_turbo.0 {
+                        "Fast-forward on"
+                    } else {
+                        "Fast-forward off"
+                    };
+                    message_event.send(ShowMessage(tr(msg).to_string()));
+                }
+            }
+            HotKey::SlowMotion => {}
         }
     }
 }
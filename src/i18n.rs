@@ -0,0 +1,74 @@
+//! Translations of the UI text.
+//!
+//! The English text itself is the key, so anything missing from a table is shown in English.
+//! Adding a language only takes a table module and an entry in `LANGUAGES`.
+
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+mod ja;
+
+pub struct Language {
+    /// Code stored in the config
+    pub code: &'static str,
+    /// Name shown in the menu, written in the language itself
+    pub name: &'static str,
+    table: &'static [(&'static str, &'static str)],
+}
+
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        name: "English",
+        table: &[],
+    },
+    Language {
+        code: "ja",
+        name: "日本語",
+        table: ja::TABLE,
+    },
+];
+
+/// Index into `LANGUAGES`. Kept global so that `Display` impls can translate too.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Switch to the language of `code`, English if it is unknown
+pub fn set_language(code: &str) {
+    let ix = LANGUAGES
+        .iter()
+        .position(|language| language.code == code)
+        .unwrap_or(0);
+    CURRENT.store(ix, Ordering::Relaxed);
+}
+
+pub fn current_language() -> &'static Language {
+    &LANGUAGES[CURRENT.load(Ordering::Relaxed)]
+}
+
+/// Translate `text` into the current language
+pub fn tr(text: &str) -> &str {
+    current_language()
+        .table
+        .iter()
+        .find(|(en, _)| *en == text)
+        .map_or(text, |(_, translated)| *translated)
+}
+
+/// Translate `template` and fill its `{}` placeholders with `args` in order
+pub fn trf(template: &str, args: &[&dyn Display]) -> String {
+    let mut ret = String::new();
+    let mut args = args.iter();
+    let mut pieces = tr(template).split("{}");
+    if let Some(first) = pieces.next() {
+        ret.push_str(first);
+    }
+    for piece in pieces {
+        if let Some(arg) = args.next() {
+            ret.push_str(&arg.to_string());
+        }
+        ret.push_str(piece);
+    }
+    ret
+}
@@ -0,0 +1,156 @@
+pub const TABLE: &[(&str, &str)] = &[
+    // Hotkeys
+    ("Reset", "リセット"),
+    ("Soft Reset", "ソフトリセット"),
+    ("Turbo", "早送り"),
+    ("State Save", "ステートセーブ"),
+    ("State Load", "ステートロード"),
+    ("State Slot Next", "次のステートスロット"),
+    ("State Slot Prev", "前のステートスロット"),
+    ("Quick Swap A/B", "スロットA/B切替"),
+    ("Start Rewindng", "巻き戻し開始"),
+    ("Enter/Leave Menu", "メニューを開く/閉じる"),
+    ("Fullsceen", "フルスクリーン"),
+    ("Window Scale +", "ウィンドウ拡大"),
+    ("Window Scale -", "ウィンドウ縮小"),
+    ("Window Scale Presets", "ウィンドウサイズ切替"),
+    ("Pause/Resume", "一時停止/再開"),
+    ("Frame Advance", "コマ送り"),
+    ("Screenshot", "スクリーンショット"),
+    ("Copy Screenshot", "スクリーンショットをコピー"),
+    ("Save GIF", "GIFを保存"),
+    ("Start/Stop Recording", "録画開始/停止"),
+    ("Start/Stop Audio Recording", "録音開始/停止"),
+    ("Mute/Unmute", "ミュート切替"),
+    ("Undo State Load", "ステートロードを取り消す"),
+    ("Frame Limiter On/Off", "フレームリミッター切替"),
+    ("Start/Stop Movie", "ムービー記録開始/停止"),
+    ("Slow Motion", "スローモーション"),
+    ("Step Back", "コマ戻し"),
+    // Menu
+    ("Main Menu", "メインメニュー"),
+    ("File", "ファイル"),
+    ("Library", "ライブラリ"),
+    ("State Save/Load", "ステートセーブ/ロード"),
+    ("State Save / Load", "ステートセーブ / ロード"),
+    ("Game Info", "ゲーム情報"),
+    ("Cheats", "チート"),
+    ("Screenshots", "スクリーンショット"),
+    ("General Setting", "全般設定"),
+    ("General Settings", "全般設定"),
+    ("Graphics", "グラフィック"),
+    ("Graphics Settings", "グラフィック設定"),
+    ("Audio", "オーディオ"),
+    ("Audio Settings", "オーディオ設定"),
+    ("Core Setting", "コア設定"),
+    ("{} Settings", "{} 設定"),
+    ("Controller Setting", "コントローラー設定"),
+    ("{} Controller Settings", "{} コントローラー設定"),
+    ("Hotkey", "ホットキー"),
+    ("Hotkey Settings", "ホットキー設定"),
+    ("System Key", "システムキー"),
+    ("System Key Settings", "システムキー設定"),
+    ("Messages", "メッセージ"),
+    ("Language:", "言語:"),
+    // Messages
+    ("Soft reset", "ソフトリセットしました"),
+    (
+        "Reset machine (no soft reset on this system)",
+        "リセットしました (このシステムはソフトリセット非対応)",
+    ),
+    ("Reset machine", "リセットしました"),
+    ("State saved: {}", "ステートを保存しました: {}"),
+    ("State loaded: {}", "ステートを読み込みました: {}"),
+    (
+        "Failed to load state: {}",
+        "ステートの読み込みに失敗しました: {}",
+    ),
+    (
+        "Failed to save state: {}",
+        "ステートの保存に失敗しました: {}",
+    ),
+    ("Resumed from slot #{}", "スロット #{} から再開しました"),
+    ("Quick swap: {}", "スロット切替: {}"),
+    ("Quick swap: #{} (empty)", "スロット切替: #{} (空)"),
+    (
+        "Warning: state was saved by {}",
+        "警告: {} で保存されたステートです",
+    ),
+    (
+        "Warning: state was saved with a different ROM",
+        "警告: 別のROMで保存されたステートです",
+    ),
+    ("Reverted load", "ロードを取り消しました"),
+    ("Nothing to undo", "取り消すものがありません"),
+    ("Failed to undo load", "ロードの取り消しに失敗しました"),
+    ("State slot changed: #{}", "ステートスロット: #{}"),
+    ("Scale: {}x", "倍率: {}x"),
+    ("Scale: fit ({}x)", "倍率: 画面に合わせる ({}x)"),
+    (
+        "Scale: {}x (minimum window size)",
+        "倍率: {}x (最小ウィンドウサイズ)",
+    ),
+    (
+        "Scale: {}x (maximum window size)",
+        "倍率: {}x (最大ウィンドウサイズ)",
+    ),
+    ("Paused", "一時停止"),
+    ("Resumed", "再開"),
+    (
+        "Screenshot saved: {}",
+        "スクリーンショットを保存しました: {}",
+    ),
+    (
+        "Failed to save screenshot",
+        "スクリーンショットの保存に失敗しました",
+    ),
+    ("Copied to clipboard", "クリップボードにコピーしました"),
+    (
+        "Failed to copy to clipboard: {}",
+        "クリップボードへのコピーに失敗しました: {}",
+    ),
+    ("Still saving the previous GIF", "前のGIFを保存中です"),
+    ("No frames to save", "保存するフレームがありません"),
+    ("Saving GIF...", "GIFを保存中..."),
+    ("GIF saved: {}", "GIFを保存しました: {}"),
+    ("Failed to save GIF", "GIFの保存に失敗しました"),
+    ("Frame limiter off", "フレームリミッター オフ"),
+    ("Frame limiter on", "フレームリミッター オン"),
+    ("Muted", "ミュート"),
+    ("Unmuted: {}%", "ミュート解除: {}%"),
+    ("Recording started", "録画を開始しました"),
+    ("Recording saved: {}", "録画を保存しました: {}"),
+    ("Failed to start recording", "録画の開始に失敗しました"),
+    ("Failed to save recording", "録画の保存に失敗しました"),
+    ("Audio recording started: {}", "録音を開始しました: {}"),
+    ("Audio recording saved: {}", "録音を保存しました: {}"),
+    (
+        "Failed to start audio recording",
+        "録音の開始に失敗しました",
+    ),
+    ("Failed to save audio recording", "録音の保存に失敗しました"),
+    ("Movie recording started", "ムービーの記録を開始しました"),
+    (
+        "Movie recording stopped and saved",
+        "ムービーの記録を停止して保存しました",
+    ),
+    ("Movie saved: {}", "ムービーを保存しました: {}"),
+    ("Movie stopped", "ムービーを停止しました"),
+    ("Failed to start movie", "ムービーの開始に失敗しました"),
+    ("Failed to save movie", "ムービーの保存に失敗しました"),
+    ("Fast-forward on", "早送り オン"),
+    ("Fast-forward off", "早送り オフ"),
+    ("Config reloaded", "設定を再読み込みしました"),
+    (
+        "Reset the game? Unsaved progress will be lost.",
+        "リセットしますか？保存していない進行状況は失われます。",
+    ),
+    ("Yes", "はい"),
+    ("No", "いいえ"),
+    ("Press any button", "ボタンを押してください"),
+    ("Link partner", "通信相手"),
+    (
+        "Dim the game behind dialogs:",
+        "ダイアログの背後のゲーム画面を暗くする:",
+    ),
+];
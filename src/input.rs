@@ -1,11 +1,28 @@
 use bevy::prelude::*;
-use meru_interface::KeyAssign;
+use meru_interface::{InputData, KeyAssign};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
+use crate::config::{ScreenRotation, SocdMode};
+
+#[derive(Clone, Copy)]
 pub struct InputState<'a> {
     keycode: &'a Input<KeyCode>,
     gamepad_button: &'a Input<GamepadButton>,
     gamepad_axis: &'a Axis<GamepadAxis>,
+    gamepad: PlayerGamepad,
+    deadzone: f32,
+}
+
+/// Which physical gamepad the gamepad keys of a player are read from
+#[derive(Clone, Copy)]
+pub enum PlayerGamepad {
+    /// The pads in the key assignment
+    Assigned,
+    /// This pad, whatever pad the key assignment names
+    Pad(usize),
+    /// The selected pad is disconnected, so gamepad keys read as released
+    Disconnected,
 }
 
 impl<'a> InputState<'a> {
@@ -18,8 +35,50 @@ impl<'a> InputState<'a> {
             keycode: input_keycode,
             gamepad_button: input_gamepad_button,
             gamepad_axis: input_gamepad_axis,
+            gamepad: PlayerGamepad::Assigned,
+            deadzone: 0.0,
         }
     }
+
+    pub fn with_deadzone(self, deadzone: f32) -> Self {
+        Self { deadzone, ..self }
+    }
+
+    pub fn with_gamepad(self, gamepad: PlayerGamepad) -> Self {
+        Self { gamepad, ..self }
+    }
+
+    fn gamepad(&self, gamepad: meru_interface::Gamepad) -> Option<meru_interface::Gamepad> {
+        match self.gamepad {
+            PlayerGamepad::Assigned => Some(gamepad),
+            PlayerGamepad::Pad(id) => Some(meru_interface::Gamepad::new(id)),
+            PlayerGamepad::Disconnected => None,
+        }
+    }
+
+    /// Stick position in `-1.0..=1.0` with the deadzone taken out
+    pub fn axis_value(&self, axis: &meru_interface::GamepadAxis) -> f32 {
+        let mut axis = *axis;
+        axis.gamepad = match self.gamepad(axis.gamepad) {
+            Some(gamepad) => gamepad,
+            None => return 0.0,
+        };
+        let value = self
+            .gamepad_axis
+            .get(ConvertInput(axis).into())
+            .unwrap_or(0.0);
+        apply_deadzone(value, self.deadzone)
+    }
+}
+
+/// Read values within `deadzone` as neutral and rescale the rest to start from 0
+pub fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    let deadzone = deadzone.clamp(0.0, 0.99);
+    if value.abs() <= deadzone {
+        0.0
+    } else {
+        value.signum() * (value.abs() - deadzone) / (1.0 - deadzone)
+    }
 }
 
 impl<'a> meru_interface::InputState for InputState<'a> {
@@ -28,13 +87,15 @@ impl<'a> meru_interface::InputState for InputState<'a> {
         match key {
             SingleKey::KeyCode(key_code) => self.keycode.pressed(ConvertInput(*key_code).into()),
             SingleKey::GamepadButton(button) => {
-                self.gamepad_button.pressed(ConvertInput(*button).into())
+                let mut button = *button;
+                button.gamepad = match self.gamepad(button.gamepad) {
+                    Some(gamepad) => gamepad,
+                    None => return false,
+                };
+                self.gamepad_button.pressed(ConvertInput(button).into())
             }
             SingleKey::GamepadAxis(axis, dir) => {
-                let value = self
-                    .gamepad_axis
-                    .get(ConvertInput(*axis).into())
-                    .unwrap_or(0.0);
+                let value = self.axis_value(axis);
                 match dir {
                     meru_interface::key_assign::GamepadAxisDir::Pos => {
                         value > bevy::input::Axis::<GamepadAxis>::MAX / 2.0
@@ -53,9 +114,15 @@ impl<'a> meru_interface::InputState for InputState<'a> {
             SingleKey::KeyCode(key_code) => {
                 self.keycode.just_pressed(ConvertInput(*key_code).into())
             }
-            SingleKey::GamepadButton(button) => self
-                .gamepad_button
-                .just_pressed(ConvertInput(*button).into()),
+            SingleKey::GamepadButton(button) => {
+                let mut button = *button;
+                button.gamepad = match self.gamepad(button.gamepad) {
+                    Some(gamepad) => gamepad,
+                    None => return false,
+                };
+                self.gamepad_button
+                    .just_pressed(ConvertInput(button).into())
+            }
             SingleKey::GamepadAxis(_, _) => todo!(),
         }
     }
@@ -397,6 +464,15 @@ impl<Key: PartialEq + Clone> KeyConfig<Key> {
         }
     }
 
+    /// Add default assignments for keys missing from a config saved by an older version
+    pub fn fill_missing(&mut self, defaults: Self) {
+        for (key, assign) in defaults.0 {
+            if self.key_assign(&key).is_none() {
+                self.0.push((key, assign));
+            }
+        }
+    }
+
     pub fn just_pressed(&self, key: &Key, input_state: &InputState<'_>) -> bool {
         self.0
             .iter()
@@ -411,3 +487,180 @@ impl<Key: PartialEq + Clone> KeyConfig<Key> {
             .map_or(false, |r| r.1.pressed(input_state))
     }
 }
+
+/// Input of all players, reading each player's gamepad keys from the pad selected for them.
+/// A player whose pad got disconnected reads as released without affecting the others.
+pub fn player_input(
+    key_config: &meru_interface::KeyConfig,
+    input_state: InputState<'_>,
+    player_gamepads: &[Option<usize>],
+    gamepads: &Gamepads,
+) -> InputData {
+    let controllers = key_config
+        .controllers
+        .iter()
+        .enumerate()
+        .map(|(player, keys)| {
+            let gamepad = match player_gamepads.get(player).copied().flatten() {
+                None => PlayerGamepad::Assigned,
+                Some(id) if gamepads.contains(&Gamepad::new(id)) => PlayerGamepad::Pad(id),
+                Some(_) => PlayerGamepad::Disconnected,
+            };
+            let input_state = input_state.with_gamepad(gamepad);
+            keys.iter()
+                .map(|(key, assign)| (key.clone(), assign.pressed(&input_state)))
+                .collect()
+        })
+        .collect();
+
+    InputData { controllers }
+}
+
+/// Copy of `template` with keyboard keys dropped and gamepad keys moved to `gamepad_id`
+pub fn gamepad_only_controller(
+    template: &[(String, KeyAssign)],
+    gamepad_id: usize,
+) -> Vec<(String, KeyAssign)> {
+    use meru_interface::{Gamepad, MultiKey, SingleKey};
+
+    template
+        .iter()
+        .map(|(name, assign)| {
+            let multi_keys = assign
+                .0
+                .iter()
+                .filter(|multi_key| {
+                    !multi_key
+                        .0
+                        .iter()
+                        .any(|key| matches!(key, SingleKey::KeyCode(_)))
+                })
+                .map(|multi_key| {
+                    MultiKey(
+                        multi_key
+                            .0
+                            .iter()
+                            .map(|key| match key.clone() {
+                                SingleKey::GamepadButton(mut button) => {
+                                    button.gamepad = Gamepad::new(gamepad_id);
+                                    SingleKey::GamepadButton(button)
+                                }
+                                SingleKey::GamepadAxis(mut axis, dir) => {
+                                    axis.gamepad = Gamepad::new(gamepad_id);
+                                    SingleKey::GamepadAxis(axis, dir)
+                                }
+                                key => key,
+                            })
+                            .collect(),
+                    )
+                })
+                .collect();
+            (name.clone(), KeyAssign(multi_keys))
+        })
+        .collect()
+}
+
+/// How long each autofire button has been held, in emulated frames.
+/// Counting emulated frames keeps the fire rate the same in fast-forward and frame skip.
+#[derive(Default)]
+pub struct AutofireState(Vec<BTreeMap<String, usize>>);
+
+impl AutofireState {
+    /// Input for the next emulated frame, with held autofire buttons pulsed at their rates.
+    /// The rates are per second of emulated time at `frame_rate`.
+    pub fn apply(
+        &mut self,
+        input: &InputData,
+        rates: &[BTreeMap<String, f32>],
+        frame_rate: f64,
+    ) -> InputData {
+        self.0.resize(input.controllers.len(), BTreeMap::new());
+
+        let controllers = input
+            .controllers
+            .iter()
+            .zip(self.0.iter_mut())
+            .enumerate()
+            .map(|(i, (keys, held_frames))| {
+                keys.iter()
+                    .map(|(key, pressed)| {
+                        let rate = rates.get(i).and_then(|rates| rates.get(key));
+                        let pressed = match rate {
+                            Some(&rate) if *pressed && rate > 0.0 => {
+                                let frames = held_frames.entry(key.clone()).or_insert(0);
+                                let phase = *frames as f32 * rate / frame_rate as f32;
+                                *frames += 1;
+                                phase.fract() < 0.5
+                            }
+                            _ => {
+                                held_frames.remove(key);
+                                *pressed
+                            }
+                        };
+                        (key.clone(), pressed)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        InputData { controllers }
+    }
+}
+
+/// Directional buttons in clockwise order
+const DIRECTIONS: [&str; 4] = ["Up", "Right", "Down", "Left"];
+
+/// Map the directions as seen on the rotated screen to the ones of the game
+pub fn rotate_directions(input: &mut InputData, rotation: ScreenRotation) {
+    let turns = rotation.quarter_turns();
+    if turns == 0 {
+        return;
+    }
+
+    for keys in input.controllers.iter_mut() {
+        let pressed = DIRECTIONS.map(|dir| keys.iter().any(|(key, on)| key == dir && *on));
+        for (key, on) in keys.iter_mut() {
+            if let Some(ix) = DIRECTIONS.iter().position(|dir| key == dir) {
+                *on = pressed[(ix + turns) % 4];
+            }
+        }
+    }
+}
+
+const SOCD_PAIRS: [(&str, &str); 2] = [("Left", "Right"), ("Up", "Down")];
+
+/// Remembers which direction of each opposing pair was pressed first.
+/// `Some(false)` is the first key of the pair, `Some(true)` is the second one.
+#[derive(Default)]
+pub struct SocdState(Vec<[Option<bool>; 2]>);
+
+pub fn resolve_socd(input: &mut InputData, mode: SocdMode, state: &mut SocdState) {
+    state.0.resize(input.controllers.len(), [None; 2]);
+
+    for (keys, firsts) in input.controllers.iter_mut().zip(state.0.iter_mut()) {
+        for ((neg, pos), first) in SOCD_PAIRS.iter().zip(firsts.iter_mut()) {
+            let neg_ix = keys.iter().position(|(key, _)| key == neg);
+            let pos_ix = keys.iter().position(|(key, _)| key == pos);
+            let (neg_ix, pos_ix) = if let (Some(neg_ix), Some(pos_ix)) = (neg_ix, pos_ix) {
+                (neg_ix, pos_ix)
+            } else {
+                continue;
+            };
+
+            match (keys[neg_ix].1, keys[pos_ix].1) {
+                (true, true) => {
+                    let keep = match mode {
+                        SocdMode::Neutral => None,
+                        SocdMode::FirstInput => *first,
+                        SocdMode::LastInput => first.map(|first| !first),
+                    };
+                    keys[neg_ix].1 = keep == Some(false);
+                    keys[pos_ix].1 = keep == Some(true);
+                }
+                (true, false) => *first = Some(false),
+                (false, true) => *first = Some(true),
+                (false, false) => *first = None,
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+use crate::{
+    app::{AppState, PixelFont},
+    config::Config,
+    core::Emulator,
+};
+
+const PRESSED_COLOR: Color = Color::WHITE;
+const RELEASED_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+pub struct InputOverlayPlugin;
+
+impl Plugin for InputOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Running).with_system(setup_input_overlay_system),
+        )
+        .add_system_set(
+            SystemSet::on_exit(AppState::Running).with_system(exit_input_overlay_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::Running)
+                .with_system(input_overlay_system)
+                .after("input"),
+        );
+    }
+}
+
+#[derive(Component)]
+struct InputOverlayText;
+
+#[derive(Component)]
+struct InputOverlayBg;
+
+fn setup_input_overlay_system(
+    mut commands: Commands,
+    pixel_font: Query<&Handle<Font>, With<PixelFont>>,
+) {
+    let pixel_font = pixel_font.single();
+
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: pixel_font.clone(),
+                    font_size: 16.0,
+                    color: RELEASED_COLOR,
+                },
+            ),
+            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(InputOverlayText);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.0, 0.0, 0.0, 0.75),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(InputOverlayBg);
+}
+
+fn exit_input_overlay_system(
+    mut commands: Commands,
+    text: Query<Entity, With<InputOverlayText>>,
+    bg: Query<Entity, With<InputOverlayBg>>,
+) {
+    commands.entity(text.single()).despawn();
+    commands.entity(bg.single()).despawn();
+}
+
+/// Short label of a button that fits the overlay
+fn button_label(name: &str) -> String {
+    match name {
+        "Up" => "^".to_string(),
+        "Down" => "v".to_string(),
+        "Left" => "<".to_string(),
+        "Right" => ">".to_string(),
+        _ => name.chars().take(2).collect::<String>().to_uppercase(),
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn input_overlay_system(
+    config: Res<Config>,
+    emulator: Option<Res<Emulator>>,
+    mut ps: ParamSet<(
+        Query<(&mut Text, &mut Visibility, &mut Transform), With<InputOverlayText>>,
+        Query<(&mut Sprite, &mut Visibility, &mut Transform), With<InputOverlayBg>>,
+    )>,
+) {
+    let emulator = if let Some(emulator) = emulator {
+        emulator
+    } else {
+        return;
+    };
+
+    let screen_width = emulator.core.frame_buffer().width as f32;
+    let screen_height = emulator.core.frame_buffer().height as f32;
+
+    let mut p0 = ps.p0();
+    let (mut text, mut visibility, mut transform) = p0.single_mut();
+    visibility.is_visible = config.input_overlay;
+    if !config.input_overlay {
+        ps.p1().single_mut().1.is_visible = false;
+        return;
+    }
+
+    // The buttons fed to the core on the last frame, with autofire applied
+    let input = emulator.fed_input();
+
+    let style = text.sections[0].style.clone();
+    let mut sections = vec![];
+    let mut width = 0;
+    for (i, keys) in input.controllers.iter().enumerate() {
+        let prefix = format!("{}{}P", if i == 0 { "" } else { "\n" }, i + 1);
+        let mut line_width = prefix.trim_start().len();
+        sections.push(TextSection::new(prefix, style.clone()));
+
+        for (key, pressed) in keys {
+            let label = format!(" {}", button_label(key));
+            line_width += label.len();
+            sections.push(TextSection::new(
+                label,
+                TextStyle {
+                    color: if *pressed {
+                        PRESSED_COLOR
+                    } else {
+                        RELEASED_COLOR
+                    },
+                    ..style.clone()
+                },
+            ));
+        }
+        width = width.max(line_width);
+    }
+    if sections.is_empty() {
+        sections.push(TextSection::new("", style));
+    }
+    text.sections = sections;
+
+    let width = (width * 6 + 2) as f32;
+    let height = (input.controllers.len().max(1) * 16) as f32;
+
+    *transform = Transform::from_xyz(
+        -screen_width / 2.0 + 2.0,
+        -screen_height / 2.0 + height,
+        2.0,
+    );
+
+    let mut p1 = ps.p1();
+    let (mut sprite, mut visibility, mut transform) = p1.single_mut();
+    visibility.is_visible = true;
+    sprite.custom_size = Some(Vec2::new(width, height));
+    *transform = Transform::from_xyz(
+        -screen_width / 2.0 + width / 2.0,
+        -screen_height / 2.0 + height / 2.0,
+        1.0,
+    );
+}
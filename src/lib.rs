@@ -1,8 +1,20 @@
 pub mod app;
+pub mod audio_viz;
+pub mod bezel;
+pub mod cheats;
 pub mod config;
 pub mod core;
+pub mod display_filter;
 pub mod file;
 pub mod hotkey;
+pub mod i18n;
 pub mod input;
+pub mod input_overlay;
+pub mod library;
+#[cfg(feature = "link-cable")]
+pub mod link;
 pub mod menu;
+pub mod movie;
+pub mod recording;
 pub mod rewinding;
+pub mod splash;
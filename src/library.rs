@@ -0,0 +1,203 @@
+use anyhow::Result;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+    time::SystemTime,
+};
+
+use crate::{
+    app::AppState,
+    config::{Config, PersistentState},
+    core::{is_archive_file, Emulator},
+};
+
+pub struct LibraryPlugin;
+
+impl Plugin for LibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LibraryScan>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Menu).with_system(start_library_scan_system),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Menu).with_system(library_scan_system));
+    }
+}
+
+/// A game found in the ROM directories
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LibraryGame {
+    pub path: PathBuf,
+    /// Abbreviations of the cores which can run it
+    pub cores: Vec<String>,
+}
+
+impl LibraryGame {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().to_string())
+    }
+
+    pub fn system_name(&self) -> String {
+        let core_infos = Emulator::core_infos();
+        self.cores
+            .iter()
+            .filter_map(|abbrev| {
+                core_infos
+                    .iter()
+                    .find(|core_info| core_info.abbrev == abbrev)
+                    .map(|core_info| core_info.system_name)
+            })
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+/// Games found in the ROM directories.
+/// The modification time of every scanned directory is kept to notice changes.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Library {
+    pub roots: Vec<PathBuf>,
+    dirs: BTreeMap<PathBuf, SystemTime>,
+    pub games: Vec<LibraryGame>,
+}
+
+impl Library {
+    fn is_up_to_date(&self, roots: &[PathBuf]) -> bool {
+        self.roots == roots
+            && self
+                .dirs
+                .iter()
+                .all(|(dir, modified)| dir_modified(dir).as_ref() == Some(modified))
+    }
+
+    fn scan(roots: &[PathBuf]) -> Library {
+        let mut ret = Library {
+            roots: roots.to_vec(),
+            ..Default::default()
+        };
+
+        let mut stack = roots.to_vec();
+        while let Some(dir) = stack.pop() {
+            if ret.dirs.contains_key(&dir) {
+                continue;
+            }
+            if let Err(err) = ret.scan_dir(&dir, &mut stack) {
+                warn!("Failed to scan `{}`: {err}", dir.display());
+            }
+        }
+
+        ret.games
+            .sort_by_cached_key(|game| game.name().to_lowercase());
+        info!("Found {} games in the library", ret.games.len());
+        ret
+    }
+
+    fn scan_dir(&mut self, dir: &Path, stack: &mut Vec<PathBuf>) -> Result<()> {
+        self.dirs
+            .insert(dir.to_owned(), fs::metadata(dir)?.modified()?);
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+
+            let cores = game_cores(&path);
+            if !cores.is_empty() {
+                self.games.push(LibraryGame { path, cores });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn dir_modified(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Cores which can run the file, looking into archives
+fn game_cores(path: &Path) -> Vec<String> {
+    let mut ret = vec![];
+
+    if is_archive_file(path) {
+        let files = fs::File::open(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut f| Ok(compress_tools::list_archive_files(&mut f)?));
+        for file in files.into_iter().flatten() {
+            for core_info in Emulator::candidate_cores(Path::new(&file)) {
+                ret.push(core_info.abbrev.to_string());
+            }
+        }
+    } else {
+        for core_info in Emulator::candidate_cores(path) {
+            ret.push(core_info.abbrev.to_string());
+        }
+    }
+
+    ret.sort();
+    ret.dedup();
+    ret
+}
+
+/// Scan of the ROM directories running in the background
+#[derive(Default)]
+pub struct LibraryScan {
+    handle: Option<JoinHandle<Library>>,
+}
+
+impl LibraryScan {
+    pub fn is_scanning(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Scan `roots` in the background, unless `cached` is still up to date or `force` is set
+    pub fn start(&mut self, roots: Vec<PathBuf>, cached: Library, force: bool) {
+        self.handle = Some(std::thread::spawn(move || {
+            if !force && cached.is_up_to_date(&roots) {
+                cached
+            } else {
+                Library::scan(&roots)
+            }
+        }));
+    }
+}
+
+fn start_library_scan_system(
+    config: Res<Config>,
+    persistent_state: Res<PersistentState>,
+    mut library_scan: ResMut<LibraryScan>,
+) {
+    if !library_scan.is_scanning() {
+        library_scan.start(
+            config.rom_dirs.clone(),
+            persistent_state.library.clone(),
+            false,
+        );
+    }
+}
+
+fn library_scan_system(
+    mut library_scan: ResMut<LibraryScan>,
+    mut persistent_state: ResMut<PersistentState>,
+) {
+    if !library_scan
+        .handle
+        .as_ref()
+        .map_or(false, |handle| handle.is_finished())
+    {
+        return;
+    }
+
+    match library_scan.handle.take().unwrap().join() {
+        Ok(library) => persistent_state.library = library,
+        Err(_) => error!("Library scan panicked"),
+    }
+}
@@ -0,0 +1,199 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_egui::{egui, EguiContext};
+use meru_interface::KeyConfig;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use crate::{
+    app::AppState,
+    config::Config,
+    core::{copy_frame_buffer, Emulator},
+    i18n::tr,
+    input::{gamepad_only_controller, InputState},
+};
+
+pub struct LinkCablePlugin;
+
+impl Plugin for LinkCablePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(None as Option<LinkCable>)
+            .add_system_set(
+                SystemSet::on_enter(AppState::Running).with_system(setup_link_cable_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Running)
+                    .with_system(link_cable_system)
+                    .after("input"),
+            );
+    }
+}
+
+/// One end of an in-process link cable
+pub struct LinkPort {
+    tx: Sender<u8>,
+    rx: Receiver<u8>,
+}
+
+impl LinkPort {
+    pub fn pair() -> (LinkPort, LinkPort) {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel();
+        (LinkPort { tx: tx1, rx: rx2 }, LinkPort { tx: tx2, rx: rx1 })
+    }
+
+    pub fn send(&self, data: &[u8]) {
+        for &b in data {
+            // The other end is owned by the same `LinkCable`, so it can't be disconnected
+            self.tx.send(b).unwrap();
+        }
+    }
+
+    pub fn recv(&self) -> Vec<u8> {
+        self.rx.try_iter().collect()
+    }
+}
+
+/// Second instance of the running core, connected to it by a link cable
+pub struct LinkCable {
+    partner: Emulator,
+    partner_path: PathBuf,
+    host_game: String,
+    host_port: LinkPort,
+    partner_port: LinkPort,
+    screen: Handle<Image>,
+    /// Host's `Emulator::core_frames` the partner has caught up with
+    frames: usize,
+}
+
+fn setup_link_cable_system(
+    config: Res<Config>,
+    emulator: Res<Emulator>,
+    mut link_cable: ResMut<Option<LinkCable>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let abbrev = emulator.core.core_info().abbrev;
+
+    let partner_path = match config.link_partners.get(&emulator.game_name) {
+        Some(path) if Emulator::supports_link_cable(abbrev) => path,
+        _ => {
+            *link_cable = None;
+            return;
+        }
+    };
+
+    if let Some(link_cable) = link_cable.as_ref() {
+        if link_cable.host_game == emulator.game_name && &link_cable.partner_path == partner_path {
+            return;
+        }
+    }
+
+    let partner = match Emulator::try_new(partner_path, &config, Some(abbrev)) {
+        Ok(partner) => partner,
+        Err(err) => {
+            error!("Failed to load link partner: {err}");
+            *link_cable = None;
+            return;
+        }
+    };
+
+    let image = Image::new(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        vec![0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    let (host_port, partner_port) = LinkPort::pair();
+
+    *link_cable = Some(LinkCable {
+        partner,
+        partner_path: partner_path.clone(),
+        host_game: emulator.game_name.clone(),
+        host_port,
+        partner_port,
+        screen: images.add(image),
+        frames: emulator.core_frames(),
+    });
+}
+
+fn link_cable_system(
+    mut config: ResMut<Config>,
+    mut emulator: ResMut<Emulator>,
+    mut link_cable: ResMut<Option<LinkCable>>,
+    mut images: ResMut<Assets<Image>>,
+    mut egui_ctx: ResMut<EguiContext>,
+    input_keycode: Res<Input<KeyCode>>,
+    input_gamepad_button: Res<Input<GamepadButton>>,
+    input_gamepad_axis: Res<Axis<GamepadAxis>>,
+) {
+    let link_cable = if let Some(link_cable) = link_cable.as_mut() {
+        link_cable
+    } else {
+        return;
+    };
+
+    // The partner is played with the next gamepads, using the host's layout
+    let key_config = config.key_config(emulator.core.core_info().abbrev);
+    let partner_key_config = KeyConfig {
+        controllers: key_config
+            .controllers
+            .iter()
+            .enumerate()
+            .map(|(i, controller)| {
+                gamepad_only_controller(controller, key_config.controllers.len() + i)
+            })
+            .collect(),
+    };
+    let input = partner_key_config.input(
+        &InputState::new(&input_keycode, &input_gamepad_button, &input_gamepad_axis)
+            .with_deadzone(config.stick_deadzone),
+    );
+
+    // The host went back in time by rewinding or stepping back
+    if link_cable.frames > emulator.core_frames() {
+        link_cable.frames = emulator.core_frames();
+    }
+
+    // Step the partner once per emulated host frame, fast-forwarded ones included,
+    // exchanging link data in between
+    while link_cable.frames < emulator.core_frames() {
+        link_cable.host_port.send(&emulator.core.link_output());
+        link_cable
+            .partner_port
+            .send(&link_cable.partner.core.link_output());
+        emulator.core.link_input(&link_cable.host_port.recv());
+        link_cable
+            .partner
+            .core
+            .link_input(&link_cable.partner_port.recv());
+
+        link_cable.partner.core.set_input(&input);
+        link_cable.partner.core.exec_frame(true);
+        link_cable.frames += 1;
+    }
+
+    let frame_buffer = link_cable.partner.core.frame_buffer();
+    let image = images.get_mut(&link_cable.screen).unwrap();
+    copy_frame_buffer(image, frame_buffer);
+
+    let size = [
+        frame_buffer.width as f32 * 2.0,
+        frame_buffer.height as f32 * 2.0,
+    ];
+    let texture_id = egui_ctx.add_image(link_cable.screen.clone_weak());
+
+    egui::Window::new(tr("Link partner"))
+        .resizable(false)
+        .show(egui_ctx.ctx_mut(), |ui| {
+            ui.image(texture_id, size);
+        });
+}
@@ -1,25 +1,53 @@
-use bevy::prelude::*;
-use bevy_egui::{egui, EguiContext};
+use bevy::{prelude::*, window::FileDragAndDrop};
+use bevy_egui::{egui, EguiContext, EguiRenderInputContainer, EguiSystem};
 use enum_iterator::all;
 use meru_interface::{MultiKey, SingleKey, Ui};
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use crate::{
-    app::{AppState, FullscreenState, ShowMessage, WindowControlEvent},
-    config::{Config, PersistentState, SystemKey, SystemKeys},
-    core::{Emulator, ARCHIVE_EXTENSIONS},
-    file::state_date,
+    app::{
+        audio_output_devices, AppState, FpsStats, FullscreenState, MessageLog, ModalOpen, Monitors,
+        Muted, ShowMessage, WindowControlEvent,
+    },
+    config::{
+        delete_profile, is_valid_profile_name, list_profiles, load_config, AspectRatio,
+        ColorFilter, Config, DisplayFilter, FpsPosition, FullscreenMode, GbPalette, MenuTheme,
+        PersistentState, RewindAudio, ScalePreset, ScreenRotation, SocdMode, SystemKey, SystemKeys,
+        TextureFilter, TurboAudio, Vsync, MAX_STATE_SLOTS,
+    },
+    core::{
+        is_archive_file, Emulator, MovieMode, RngSeed, UnknownSystem, ARCHIVE_EXTENSIONS,
+        AUTOSAVE_SLOT,
+    },
+    file::{list_screenshots, state_date, STATE_FILE_EXTENSION},
     hotkey::{HotKey, HotKeys},
-    input::ConvertInput,
+    i18n::{current_language, tr, trf, LANGUAGES},
+    input::{ConvertInput, InputState},
+    library::LibraryScan,
+    movie::MOVIE_EXTENSION,
 };
 
 pub const MENU_WIDTH: usize = 1280;
 pub const MENU_HEIGHT: usize = 720;
 
+const LARGE_REWIND_BUFFER_SECONDS: u32 = 60;
+const STATE_THUMBNAIL_HEIGHT: f32 = 48.0;
+const SCREENSHOT_THUMBNAIL_HEIGHT: f32 = 120.0;
+const DEFAULT_AUTOFIRE_RATE: f32 = 10.0;
+
 pub struct MenuPlugin;
 
 pub enum MenuEvent {
     OpenRomFile(PathBuf),
+    /// A ROM inside an archive
+    OpenArchiveEntry(PathBuf, String),
+    /// The file, the entry if it is an archive, the core to open it with,
+    /// and whether to remember the core for this ROM
+    OpenRomFileWithCore(PathBuf, Option<String>, String, bool),
 }
 
 impl Plugin for MenuPlugin {
@@ -30,7 +58,14 @@ impl Plugin for MenuPlugin {
                     .with_system(menu_system)
                     .with_system(menu_event_system),
             )
+            .add_system(file_drop_system)
             .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(menu_exit))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                menu_navigation_system
+                    .after(EguiSystem::ProcessInput)
+                    .before(EguiSystem::BeginFrame),
+            )
             .add_event::<MenuEvent>();
     }
 }
@@ -40,6 +75,29 @@ struct MenuError {
     message: String,
 }
 
+struct CoreSelection {
+    path: PathBuf,
+    /// ROM in the archive at `path`
+    entry: Option<String>,
+    candidates: Vec<(String, String)>,
+    remember: bool,
+}
+
+impl CoreSelection {
+    fn rom_path(&self) -> &Path {
+        self.entry.as_ref().map_or(self.path.as_path(), Path::new)
+    }
+}
+
+/// Asks which of the ROMs in an archive to open
+struct ArchiveSelection {
+    path: PathBuf,
+    entries: Vec<String>,
+}
+
+/// Asks whether to continue from the autosave of the game just opened
+struct ResumeAutosave;
+
 fn setup_menu_system(
     mut commands: Commands,
     mut windows: ResMut<Windows>,
@@ -52,38 +110,223 @@ fn setup_menu_system(
 
     commands.insert_resource(MenuState::default());
     commands.insert_resource(None as Option<MenuError>);
+    commands.insert_resource(None as Option<CoreSelection>);
+    commands.insert_resource(None as Option<ArchiveSelection>);
+    commands.insert_resource(None as Option<ResumeAutosave>);
 }
 
 fn menu_exit(config: Res<Config>) {
     config.save().unwrap();
 }
 
+/// Drives the menu with the system keys, through egui's keyboard focus.
+/// Up and Down move the focus, Left and Right adjust the focused widget,
+/// Ok presses it and Cancel closes popups, or goes back to the game.
+#[allow(clippy::too_many_arguments)]
+fn menu_navigation_system(
+    config: Res<Config>,
+    mut app_state: ResMut<State<AppState>>,
+    menu_state: Option<Res<MenuState>>,
+    emulator: Option<Res<Emulator>>,
+    mut egui_ctx: ResMut<EguiContext>,
+    mut egui_input: ResMut<EguiRenderInputContainer>,
+    input_keycode: Res<Input<KeyCode>>,
+    input_gamepad_button: Res<Input<GamepadButton>>,
+    input_gamepad_axis: Res<Axis<GamepadAxis>>,
+) {
+    if app_state.current() != &AppState::Menu
+        || menu_state.map_or(true, |menu_state| menu_state.is_capturing_input())
+    {
+        return;
+    }
+
+    let ctx = egui_ctx.ctx_mut();
+
+    // Keys typed into a text field are not for navigation, but the gamepad still works
+    let no_keys = Input::<KeyCode>::default();
+    let typing = ctx.wants_keyboard_input();
+    let input_state = InputState::new(
+        if typing { &no_keys } else { &input_keycode },
+        &input_gamepad_button,
+        &input_gamepad_axis,
+    )
+    .with_deadzone(config.stick_deadzone);
+    // The keyboard arrows already reach egui
+    let gamepad_state = InputState::new(&no_keys, &input_gamepad_button, &input_gamepad_axis)
+        .with_deadzone(config.stick_deadzone);
+
+    let pressed = |key: SystemKey, input_state: &InputState| {
+        config.system_keys.just_pressed(&key, input_state)
+    };
+    let key_event = |key: egui::Key, shift: bool| egui::Event::Key {
+        key,
+        pressed: true,
+        modifiers: egui::Modifiers {
+            shift,
+            ..Default::default()
+        },
+    };
+
+    let mut events = vec![];
+    if pressed(SystemKey::Up, &input_state) {
+        events.push(key_event(egui::Key::Tab, true));
+    }
+    if pressed(SystemKey::Down, &input_state) {
+        events.push(key_event(egui::Key::Tab, false));
+    }
+    if pressed(SystemKey::Left, &gamepad_state) {
+        events.push(key_event(egui::Key::ArrowLeft, false));
+    }
+    if pressed(SystemKey::Right, &gamepad_state) {
+        events.push(key_event(egui::Key::ArrowRight, false));
+    }
+    if pressed(SystemKey::Ok, &input_state) {
+        events.push(key_event(egui::Key::Enter, false));
+    }
+    if pressed(SystemKey::Cancel, &input_state) {
+        if ctx.memory().focus().is_some() {
+            events.push(key_event(egui::Key::Escape, false));
+        } else if emulator.is_some() {
+            app_state.set(AppState::Running).unwrap();
+            return;
+        }
+    }
+
+    if events.is_empty() {
+        return;
+    }
+    if let Some(input) = egui_input.0.get_mut(&bevy::window::WindowId::primary()) {
+        input.events.extend(events);
+    }
+}
+
+fn file_drop_system(
+    mut events: EventReader<FileDragAndDrop>,
+    mut app_state: ResMut<State<AppState>>,
+    mut menu_event: EventWriter<MenuEvent>,
+) {
+    let paths = events
+        .iter()
+        .filter_map(|event| match event {
+            FileDragAndDrop::DroppedFile { path_buf, .. } => Some(path_buf.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if paths.is_empty() {
+        return;
+    }
+
+    match app_state.current() {
+        AppState::Menu => {}
+        // Opened through the menu, which takes over from the running game
+        AppState::Running => app_state.set(AppState::Menu).unwrap(),
+        // Dropped while the splash screen or rewinding is in progress
+        AppState::Splash | AppState::Rewinding => return,
+    }
+    for path in paths {
+        menu_event.send(MenuEvent::OpenRomFile(path));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn menu_event_system(
     mut commands: Commands,
     mut event: EventReader<MenuEvent>,
     mut app_state: ResMut<State<AppState>>,
     mut persistent_state: ResMut<PersistentState>,
     mut error_msg: ResMut<Option<MenuError>>,
-    config: Res<Config>,
+    mut core_selection: ResMut<Option<CoreSelection>>,
+    mut archive_selection: ResMut<Option<ArchiveSelection>>,
+    mut resume_autosave: ResMut<Option<ResumeAutosave>>,
+    mut config: ResMut<Config>,
+    rng_seed: Res<RngSeed>,
 ) {
     for event in event.iter() {
-        match event {
-            MenuEvent::OpenRomFile(path) => {
-                info!("Opening file: {:?}", path);
-                match Emulator::try_new(path, &config) {
-                    Ok(emulator) => {
-                        commands.insert_resource(emulator);
-                        persistent_state.add_recent(&path);
-                        app_state.set(AppState::Running).unwrap();
+        let (path, entry, core_abbrev, remember) = match event {
+            MenuEvent::OpenRomFile(path) if is_archive_file(path) => {
+                match Emulator::archive_roms(path) {
+                    Ok(entries) if entries.len() > 1 => {
+                        *archive_selection.as_mut() = Some(ArchiveSelection {
+                            path: path.clone(),
+                            entries,
+                        });
+                        continue;
                     }
+                    Ok(entries) => match entries.into_iter().next() {
+                        Some(entry) => (path, Some(entry), None, false),
+                        None => {
+                            *error_msg.as_mut() = Some(MenuError {
+                                title: tr("Failed to open ROM").into(),
+                                message: tr("The archive contains no supported ROM").into(),
+                            });
+                            continue;
+                        }
+                    },
                     Err(err) => {
                         *error_msg.as_mut() = Some(MenuError {
-                            title: "Failed to open ROM".into(),
+                            title: tr("Failed to open ROM").into(),
                             message: err.to_string(),
                         });
+                        continue;
                     }
                 }
             }
+            MenuEvent::OpenRomFile(path) => (path, None, None, false),
+            MenuEvent::OpenArchiveEntry(path, entry) => (path, Some(entry.clone()), None, false),
+            MenuEvent::OpenRomFileWithCore(path, entry, core_abbrev, remember) => {
+                (path, entry.clone(), Some(core_abbrev.as_str()), *remember)
+            }
+        };
+
+        info!("Opening file: {:?} {:?}", path, entry);
+        let res = match &entry {
+            Some(entry) => Emulator::try_new_from_archive(path, entry, &config, core_abbrev),
+            None => Emulator::try_new(path, &config, core_abbrev),
+        };
+        match res {
+            Ok(mut emulator) => {
+                if let Some(abbrev) = core_abbrev.filter(|_| remember) {
+                    config
+                        .rom_cores
+                        .insert(emulator.rom_hash.clone(), abbrev.to_string());
+                }
+                if let Some(seed) = rng_seed.0 {
+                    emulator.reset_with_seed(seed);
+                }
+                let has_autosave =
+                    config.autosave_interval_seconds.is_some() && emulator.has_autosave(&config);
+                commands.insert_resource(emulator);
+                persistent_state.add_recent(&path);
+                if has_autosave {
+                    *resume_autosave.as_mut() = Some(ResumeAutosave);
+                } else {
+                    app_state.set(AppState::Running).unwrap();
+                }
+            }
+            Err(err) => {
+                // Let the user choose among the cores that could run it
+                if let Some(unknown) = err.downcast_ref::<UnknownSystem>() {
+                    let candidates = Emulator::core_infos()
+                        .into_iter()
+                        .filter(|info| {
+                            unknown.candidates.is_empty()
+                                || unknown.candidates.contains(&info.abbrev)
+                        })
+                        .map(|info| (info.abbrev.to_string(), info.system_name.to_string()))
+                        .collect();
+                    *core_selection.as_mut() = Some(CoreSelection {
+                        path: path.clone(),
+                        entry,
+                        candidates,
+                        remember: true,
+                    });
+                    continue;
+                }
+                *error_msg.as_mut() = Some(MenuError {
+                    title: tr("Failed to open ROM").into(),
+                    message: err.to_string(),
+                });
+            }
         }
     }
 }
@@ -91,14 +334,19 @@ fn menu_event_system(
 #[derive(PartialEq, Eq, Clone)]
 enum MenuTab {
     File,
+    Library,
     State,
     GameInfo,
+    Cheats,
+    Screenshots,
     GeneralSetting,
     CoreSetting(String),
     ControllerSetting(String),
     Graphics,
+    Audio,
     HotKey,
     SystemKey,
+    MessageLog,
 }
 
 #[derive(PartialEq, Eq)]
@@ -116,6 +364,34 @@ struct MenuState {
     constructing_hotkey: Option<Vec<SingleKey>>,
     system_key_tab: ControllerTab,
     system_key_ix: usize,
+    renaming_slot: Option<(usize, String)>,
+    state_thumbnails: BTreeMap<usize, Option<egui::TextureHandle>>,
+    audio_devices: Option<Vec<String>>,
+    profiles: Option<Vec<String>>,
+    new_profile_name: String,
+    new_cheat_code: String,
+    new_cheat_description: String,
+    library_filter: String,
+    /// Core abbreviation the library is narrowed to
+    library_system: Option<String>,
+    focus_library_filter: bool,
+    favorites_only: bool,
+    /// Screenshots of the running game, loaded when the tab is first shown
+    screenshots: Option<Vec<(PathBuf, egui::TextureHandle)>>,
+    /// Screenshot shown full-size
+    viewing_screenshot: Option<PathBuf>,
+}
+
+impl MenuState {
+    /// Whether a key assignment is waiting for a key or a button
+    fn is_capturing_input(&self) -> bool {
+        match self.tab {
+            MenuTab::ControllerSetting(_) => self.controller_button_ix != 0,
+            MenuTab::HotKey => self.hotkey_select != 0,
+            MenuTab::SystemKey => self.system_key_ix != 0,
+            _ => false,
+        }
+    }
 }
 
 impl Default for MenuState {
@@ -129,29 +405,85 @@ impl Default for MenuState {
             constructing_hotkey: None,
             system_key_tab: ControllerTab::Keyboard,
             system_key_ix: 0,
+            renaming_slot: None,
+            state_thumbnails: BTreeMap::new(),
+            audio_devices: None,
+            profiles: None,
+            new_profile_name: String::new(),
+            new_cheat_code: String::new(),
+            new_cheat_description: String::new(),
+            library_filter: String::new(),
+            library_system: None,
+            focus_library_filter: true,
+            favorites_only: false,
+            screenshots: None,
+            viewing_screenshot: None,
         }
     }
 }
 
 impl MenuState {
     fn tab_selector(&mut self, ui: &mut egui::Ui, emulator_loaded: bool) {
-        ui.heading("Main Menu");
+        ui.heading(tr("Main Menu"));
         ui.separator();
 
-        ui.selectable_value(&mut self.tab, MenuTab::File, "📁 File");
+        ui.selectable_value(&mut self.tab, MenuTab::File, format!("📁 {}", tr("File")));
+        if ui
+            .selectable_value(
+                &mut self.tab,
+                MenuTab::Library,
+                format!("📚 {}", tr("Library")),
+            )
+            .clicked()
+        {
+            self.focus_library_filter = true;
+        }
+
+        ui.add_enabled_ui(emulator_loaded, |ui| {
+            ui.selectable_value(
+                &mut self.tab,
+                MenuTab::State,
+                format!("💾 {}", tr("State Save/Load")),
+            );
+        });
+
+        ui.add_enabled_ui(emulator_loaded, |ui| {
+            ui.selectable_value(
+                &mut self.tab,
+                MenuTab::GameInfo,
+                format!("ℹ {}", tr("Game Info")),
+            );
+        });
 
         ui.add_enabled_ui(emulator_loaded, |ui| {
-            ui.selectable_value(&mut self.tab, MenuTab::State, "💾 State Save/Load");
+            ui.selectable_value(
+                &mut self.tab,
+                MenuTab::Cheats,
+                format!("💡 {}", tr("Cheats")),
+            );
         });
 
         ui.add_enabled_ui(emulator_loaded, |ui| {
-            ui.selectable_value(&mut self.tab, MenuTab::GameInfo, "ℹ Game Info");
+            ui.selectable_value(
+                &mut self.tab,
+                MenuTab::Screenshots,
+                format!("📷 {}", tr("Screenshots")),
+            );
         });
 
-        ui.selectable_value(&mut self.tab, MenuTab::GeneralSetting, "🔧 General Setting");
-        ui.selectable_value(&mut self.tab, MenuTab::Graphics, "🖼 Graphics");
+        ui.selectable_value(
+            &mut self.tab,
+            MenuTab::GeneralSetting,
+            format!("🔧 {}", tr("General Setting")),
+        );
+        ui.selectable_value(
+            &mut self.tab,
+            MenuTab::Graphics,
+            format!("🖼 {}", tr("Graphics")),
+        );
+        ui.selectable_value(&mut self.tab, MenuTab::Audio, format!("🔊 {}", tr("Audio")));
 
-        ui.collapsing("⚙ Core Setting", |ui| {
+        ui.collapsing(format!("⚙ {}", tr("Core Setting")), |ui| {
             for core_info in Emulator::core_infos() {
                 ui.selectable_value(
                     &mut self.tab,
@@ -160,7 +492,7 @@ impl MenuState {
                 );
             }
         });
-        ui.collapsing("🎮 Controller Setting", |ui| {
+        ui.collapsing(format!("🎮 {}", tr("Controller Setting")), |ui| {
             for core_info in Emulator::core_infos() {
                 ui.selectable_value(
                     &mut self.tab,
@@ -170,8 +502,21 @@ impl MenuState {
             }
         });
 
-        ui.selectable_value(&mut self.tab, MenuTab::HotKey, "⌨ Hotkey");
-        ui.selectable_value(&mut self.tab, MenuTab::SystemKey, "💻 System Key");
+        ui.selectable_value(
+            &mut self.tab,
+            MenuTab::HotKey,
+            format!("⌨ {}", tr("Hotkey")),
+        );
+        ui.selectable_value(
+            &mut self.tab,
+            MenuTab::SystemKey,
+            format!("💻 {}", tr("System Key")),
+        );
+        ui.selectable_value(
+            &mut self.tab,
+            MenuTab::MessageLog,
+            format!("📜 {}", tr("Messages")),
+        );
     }
 
     fn tab_controller(
@@ -181,6 +526,7 @@ impl MenuState {
         core: &str,
         key_code_input: &Input<KeyCode>,
         gamepad_button_input: &Input<GamepadButton>,
+        gamepads: &Gamepads,
     ) {
         let mut key_config = config.key_config(core).clone();
 
@@ -190,21 +536,54 @@ impl MenuState {
 
         ui.horizontal(|ui| {
             for i in 0..key_config.controllers.len() {
-                let resp = ui.selectable_value(&mut self.controller_ix, i, format!("Pad{}", i + 1));
+                let resp =
+                    ui.selectable_value(&mut self.controller_ix, i, trf("Pad{}", &[&(i + 1)]));
                 if resp.clicked() {
                     self.controller_button_ix = 0;
                 }
             }
         });
 
+        ui.horizontal(|ui| {
+            ui.label(tr("Gamepad:"));
+
+            let player = self.controller_ix;
+            let mut gamepad = config.player_gamepad(player);
+            let gamepad_text = |gamepad: Option<usize>| match gamepad {
+                None => tr("As assigned below").to_string(),
+                Some(id) if gamepads.contains(&Gamepad::new(id)) => trf("Gamepad {}", &[&id]),
+                Some(id) => trf("Gamepad {} (disconnected)", &[&id]),
+            };
+
+            egui::ComboBox::from_id_source("player_gamepad")
+                .selected_text(gamepad_text(gamepad))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut gamepad, None, gamepad_text(None));
+                    for pad in gamepads.iter() {
+                        ui.selectable_value(&mut gamepad, Some(pad.id), gamepad_text(Some(pad.id)));
+                    }
+                });
+
+            if gamepad != config.player_gamepad(player) {
+                config.set_player_gamepad(player, gamepad);
+            }
+        })
+        .response
+        .on_hover_text(tr(
+            "Read this player's gamepad buttons from the selected gamepad",
+        ));
+
         ui.horizontal(|ui| {
             let mut resp = ui.selectable_value(
                 &mut self.controller_tab,
                 ControllerTab::Keyboard,
-                "Keyboard",
+                tr("Keyboard"),
+            );
+            resp |= ui.selectable_value(
+                &mut self.controller_tab,
+                ControllerTab::Gamepad,
+                tr("Gamepad"),
             );
-            resp |=
-                ui.selectable_value(&mut self.controller_tab, ControllerTab::Gamepad, "Gamepad");
             if resp.clicked() {
                 self.controller_button_ix = 0;
             }
@@ -217,8 +596,8 @@ impl MenuState {
                 .striped(true);
 
             grid.show(ui, |ui| {
-                ui.label("Button");
-                ui.label("Assignment");
+                ui.label(tr("Button"));
+                ui.label(tr("Assignment"));
                 ui.end_row();
 
                 ui.separator();
@@ -239,8 +618,15 @@ impl MenuState {
                                 .extract_keycode()
                                 .map_or_else(|| "".to_string(), |k| format!("{k:?}"));
 
+                            let used_by = assign.extract_keycode().map_or_else(String::new, |k| {
+                                hotkeys_using_text(&config.hotkeys, &[SingleKey::KeyCode(k)], None)
+                            });
+
                             ui.selectable_value(&mut self.controller_button_ix, ix, assign_str)
-                                .on_hover_text("Click and type the key you want to assign");
+                                .on_hover_text(format!(
+                                    "{}\n{used_by}",
+                                    tr("Click and type the key you want to assign")
+                                ));
 
                             if self.controller_button_ix == ix {
                                 if let Some(kc) = key_code_input.get_just_pressed().next() {
@@ -265,8 +651,19 @@ impl MenuState {
                                 .extract_gamepad()
                                 .map_or_else(|| "".to_string(), |k| k.to_string());
 
+                            let used_by = assign.extract_gamepad().map_or_else(String::new, |b| {
+                                hotkeys_using_text(
+                                    &config.hotkeys,
+                                    &[SingleKey::GamepadButton(b)],
+                                    None,
+                                )
+                            });
+
                             ui.selectable_value(&mut self.controller_button_ix, ix, assign_str)
-                                .on_hover_text("Click and press the button you want to assign");
+                                .on_hover_text(format!(
+                                    "{}\n{used_by}",
+                                    tr("Click and press the button you want to assign")
+                                ));
 
                             if self.controller_button_ix == ix {
                                 if let Some(button) = gamepad_button_input.get_just_pressed().next()
@@ -288,7 +685,36 @@ impl MenuState {
             });
         });
 
-        if ui.button("Reset to default").clicked() {
+        ui.group(|ui| {
+            ui.label(tr("Autofire"))
+                .on_hover_text(tr("Held buttons are pressed repeatedly at the set rate"));
+
+            egui::Grid::new("autofire")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for (name, _) in &key_config.controllers[self.controller_ix] {
+                        let rate = config.autofire_rate(core, self.controller_ix, name);
+                        let mut enabled = rate.is_some();
+                        let mut value = rate.unwrap_or(DEFAULT_AUTOFIRE_RATE);
+
+                        ui.checkbox(&mut enabled, name.as_str());
+                        ui.add_enabled(
+                            enabled,
+                            egui::Slider::new(&mut value, 1.0..=30.0).suffix("/s"),
+                        );
+                        ui.end_row();
+
+                        let new_rate = enabled.then(|| value);
+                        if new_rate != rate {
+                            config.set_autofire_rate(core, self.controller_ix, name, new_rate);
+                        }
+                    }
+                });
+        });
+
+        if ui.button(tr("Reset to default")).clicked() {
             let default_key_config = Emulator::default_key_config(core);
             self.controller_ix = 0;
             self.controller_button_ix = 0;
@@ -304,8 +730,8 @@ impl MenuState {
         gamepad_button_input: &Input<GamepadButton>,
     ) {
         let grid = |ui: &mut egui::Ui| {
-            ui.label("HotKey");
-            ui.label("Assignment");
+            ui.label(tr("HotKey"));
+            ui.label(tr("Assignment"));
             ui.end_row();
 
             ui.separator();
@@ -314,6 +740,7 @@ impl MenuState {
 
             let mut ix = 1;
             let mut hotkey_determined = false;
+            let mut editing_hotkey = None;
 
             if self.hotkey_select != 0 {
                 let mut current_pushed = vec![];
@@ -355,6 +782,7 @@ impl MenuState {
                     let key_assign = config.hotkeys.key_assign_mut(&hotkey).unwrap();
                     for i in 0..key_assign.0.len() {
                         let key_str = if self.hotkey_select == ix {
+                            editing_hotkey = Some(hotkey);
                             if hotkey_determined {
                                 self.hotkey_select = 0;
                                 key_assign.0[i] =
@@ -373,7 +801,7 @@ impl MenuState {
 
                         if ui
                             .selectable_value(&mut self.hotkey_select, ix, key_str)
-                            .on_hover_text("Click to change\nRight click to remove")
+                            .on_hover_text(tr("Click to change\nRight click to remove"))
                             .clicked_by(egui::PointerButton::Secondary)
                         {
                             key_assign.0.remove(i);
@@ -383,6 +811,7 @@ impl MenuState {
                     }
 
                     let key_str = if self.hotkey_select == ix {
+                        editing_hotkey = Some(hotkey);
                         if hotkey_determined {
                             self.hotkey_select = 0;
                             key_assign
@@ -401,21 +830,35 @@ impl MenuState {
                     };
 
                     ui.selectable_value(&mut self.hotkey_select, ix, key_str)
-                        .on_hover_text("Add new key assignment");
+                        .on_hover_text(tr("Add new key assignment"));
                     ix += 1;
                 });
 
                 ui.end_row();
             }
+
+            editing_hotkey
         };
-        ui.group(|ui| {
-            egui::Grid::new("key_config")
-                .num_columns(2)
-                .spacing([40.0, 4.0])
-                .striped(true)
-                .show(ui, grid);
-        });
-        if ui.button("Reset to default").clicked() {
+        let editing_hotkey = ui
+            .group(|ui| {
+                egui::Grid::new("key_config")
+                    .num_columns(2)
+                    .spacing([40.0, 4.0])
+                    .striped(true)
+                    .show(ui, grid)
+                    .inner
+            })
+            .inner;
+
+        if let (Some(hotkey), Some(keys)) = (editing_hotkey, &self.constructing_hotkey) {
+            ui.label(format!(
+                "{}: {}",
+                MultiKey(keys.clone()),
+                hotkeys_using_text(&config.hotkeys, keys, Some(hotkey))
+            ));
+        }
+
+        if ui.button(tr("Reset to default")).clicked() {
             config.hotkeys = HotKeys::default();
         }
     }
@@ -431,10 +874,13 @@ impl MenuState {
             let mut resp = ui.selectable_value(
                 &mut self.system_key_tab,
                 ControllerTab::Keyboard,
-                "Keyboard",
+                tr("Keyboard"),
+            );
+            resp |= ui.selectable_value(
+                &mut self.system_key_tab,
+                ControllerTab::Gamepad,
+                tr("Gamepad"),
             );
-            resp |=
-                ui.selectable_value(&mut self.system_key_tab, ControllerTab::Gamepad, "Gamepad");
             if resp.clicked() {
                 self.system_key_ix = 0;
             }
@@ -447,8 +893,8 @@ impl MenuState {
                 .striped(true);
 
             grid.show(ui, |ui| {
-                ui.label("Button");
-                ui.label("Assignment");
+                ui.label(tr("Button"));
+                ui.label(tr("Assignment"));
                 ui.end_row();
 
                 ui.separator();
@@ -471,7 +917,7 @@ impl MenuState {
                                 .map_or_else(|| "".to_string(), |k| format!("{k:?}"));
 
                             ui.selectable_value(&mut self.system_key_ix, ix, assign_str)
-                                .on_hover_text("Click and type the key you want to assign");
+                                .on_hover_text(tr("Click and type the key you want to assign"));
 
                             if self.system_key_ix == ix {
                                 if let Some(kc) = key_code_input.get_just_pressed().next() {
@@ -499,7 +945,7 @@ impl MenuState {
                                 .map_or_else(|| "".to_string(), |k| k.to_string());
 
                             ui.selectable_value(&mut self.system_key_ix, ix, assign_str)
-                                .on_hover_text("Click and type the key you want to assign");
+                                .on_hover_text(tr("Click and type the key you want to assign"));
 
                             if self.system_key_ix == ix {
                                 if let Some(button) = gamepad_button_input.get_just_pressed().next()
@@ -522,27 +968,67 @@ impl MenuState {
             });
         });
 
-        if ui.button("Reset to default").clicked() {
+        if ui.button(tr("Reset to default")).clicked() {
             config.system_keys = SystemKeys::default();
         }
     }
 }
 
+fn hotkeys_using(hotkeys: &HotKeys, keys: &[SingleKey]) -> Vec<HotKey> {
+    hotkeys
+        .0
+        .iter()
+        .filter(|(_, assign)| {
+            assign.0.iter().any(|MultiKey(mk)| {
+                mk.len() == keys.len() && keys.iter().all(|key| mk.contains(key))
+            })
+        })
+        .map(|(hotkey, _)| *hotkey)
+        .collect()
+}
+
+fn hotkeys_using_text(hotkeys: &HotKeys, keys: &[SingleKey], exclude: Option<HotKey>) -> String {
+    let used_by = hotkeys_using(hotkeys, keys)
+        .into_iter()
+        .filter(|hotkey| Some(*hotkey) != exclude)
+        .map(|hotkey| hotkey.to_string())
+        .collect::<Vec<_>>();
+
+    if used_by.is_empty() {
+        tr("Not used by any hotkey").to_string()
+    } else {
+        trf("Used by hotkey: {}", &[&used_by.join(", ")])
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn menu_system(
     mut config: ResMut<Config>,
-    persistent_state: Res<PersistentState>,
+    (mut persistent_state, mut library_scan): (ResMut<PersistentState>, ResMut<LibraryScan>),
     mut egui_ctx: ResMut<EguiContext>,
     mut app_state: ResMut<State<AppState>>,
     mut menu_state: ResMut<MenuState>,
     mut emulator: Option<ResMut<Emulator>>,
     mut menu_event: EventWriter<MenuEvent>,
-    mut message_event: EventWriter<ShowMessage>,
-    mut window_control_event: EventWriter<WindowControlEvent>,
-    mut menu_error: ResMut<Option<MenuError>>,
-    key_code_input: Res<Input<KeyCode>>,
-    gamepad_button_input: Res<Input<GamepadButton>>,
-    fullscreen_state: Res<FullscreenState>,
+    (mut message_event, mut message_log): (EventWriter<ShowMessage>, ResMut<MessageLog>),
+    (mut window_control_event, mut hotkey_event): (
+        EventWriter<WindowControlEvent>,
+        EventWriter<HotKey>,
+    ),
+    (mut menu_error, mut core_selection, mut archive_selection, mut resume_autosave): (
+        ResMut<Option<MenuError>>,
+        ResMut<Option<CoreSelection>>,
+        ResMut<Option<ArchiveSelection>>,
+        ResMut<Option<ResumeAutosave>>,
+    ),
+    (key_code_input, gamepad_button_input, gamepads): (
+        Res<Input<KeyCode>>,
+        Res<Input<GamepadButton>>,
+        Res<Gamepads>,
+    ),
+    (fullscreen_state, monitors): (Res<FullscreenState>, Res<Monitors>),
+    (mut fps_stats, mut muted): (ResMut<FpsStats>, ResMut<Muted>),
+    mut modal_open: ResMut<ModalOpen>,
 ) {
     // let MenuState {
     //     tab,
@@ -556,6 +1042,7 @@ fn menu_system(
     // } = menu_state.as_mut();
 
     if let Some(error) = menu_error.as_ref() {
+        modal_open.show();
         let mut open = true;
         let mut clicked = false;
         egui::Window::new(&error.title)
@@ -566,7 +1053,7 @@ fn menu_system(
 
                 ui.with_layout(layout, |ui| {
                     ui.label(&error.message);
-                    if ui.button("OK").clicked() {
+                    if ui.button(tr("OK")).clicked() {
                         clicked = true;
                     }
                 });
@@ -577,13 +1064,138 @@ fn menu_system(
         }
     }
 
+    if let Some(selection) = core_selection.as_mut() {
+        modal_open.show();
+        let mut open = true;
+        let mut selected = None;
+        egui::Window::new(tr("Select core"))
+            .open(&mut open)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(egui_ctx.ctx_mut(), |ui| {
+                let layout = egui::Layout::top_down_justified(egui::Align::Center);
+
+                ui.with_layout(layout, |ui| {
+                    ui.label(trf(
+                        "Cannot tell which system `{}` is for",
+                        &[&selection.rom_path().display()],
+                    ));
+                    for (abbrev, system_name) in &selection.candidates {
+                        if ui.button(system_name).clicked() {
+                            selected = Some(abbrev.clone());
+                        }
+                    }
+                    let label = if selection.rom_path().extension().is_some() {
+                        "Remember for this file type"
+                    } else {
+                        "Remember for this file"
+                    };
+                    ui.checkbox(&mut selection.remember, tr(label));
+                });
+            });
+
+        if let Some(abbrev) = selected {
+            if selection.remember {
+                if let Some(ext) = selection.rom_path().extension() {
+                    config
+                        .core_selection
+                        .insert(ext.to_string_lossy().to_string(), abbrev.clone());
+                }
+            }
+            menu_event.send(MenuEvent::OpenRomFileWithCore(
+                selection.path.clone(),
+                selection.entry.clone(),
+                abbrev,
+                selection.remember,
+            ));
+        }
+
+        if !open || selected.is_some() {
+            *core_selection.as_mut() = None;
+        }
+    }
+
+    if let Some(selection) = archive_selection.as_ref() {
+        modal_open.show();
+        let mut open = true;
+        let mut selected = None;
+        egui::Window::new(tr("Select ROM"))
+            .open(&mut open)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(egui_ctx.ctx_mut(), |ui| {
+                let layout = egui::Layout::top_down_justified(egui::Align::Center);
+
+                ui.with_layout(layout, |ui| {
+                    ui.label(trf(
+                        "`{}` contains multiple ROMs",
+                        &[&selection.path.display()],
+                    ));
+                    for entry in &selection.entries {
+                        if ui.button(entry).clicked() {
+                            selected = Some(entry.clone());
+                        }
+                    }
+                });
+            });
+
+        let close = !open || selected.is_some();
+        if let Some(entry) = selected {
+            menu_event.send(MenuEvent::OpenArchiveEntry(selection.path.clone(), entry));
+        }
+        if close {
+            *archive_selection.as_mut() = None;
+        }
+    }
+
+    if resume_autosave.is_some() {
+        modal_open.show();
+        let mut resume = None;
+        egui::Window::new(tr("Resume"))
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(egui_ctx.ctx_mut(), |ui| {
+                let layout = egui::Layout::top_down_justified(egui::Align::Center);
+
+                ui.with_layout(layout, |ui| {
+                    ui.label(tr("An autosave of this game was found"));
+                    if ui.button(tr("Resume from autosave")).clicked() {
+                        resume = Some(true);
+                    }
+                    if ui.button(tr("Start from the beginning")).clicked() {
+                        resume = Some(false);
+                    }
+                });
+            });
+
+        // The emulator is inserted by a command, so it can be missing on the first frame
+        if let (Some(resume), Some(emulator)) = (resume, emulator.as_deref_mut()) {
+            if resume {
+                if let Err(err) = emulator.load_state_slot(AUTOSAVE_SLOT, config.as_ref()) {
+                    error!("Failed to load autosave: {err}");
+                }
+            }
+            *resume_autosave.as_mut() = None;
+            app_state.set(AppState::Running).unwrap();
+        }
+    }
+
     let old_config = config.clone();
 
     egui::CentralPanel::default().show(egui_ctx.ctx_mut(), |ui| {
+        // Keep the menu from reacting to input meant for the modal
+        ui.set_enabled(!modal_open.is_open());
+
         let width = ui.available_width();
 
         let frame = egui::Frame::default();
 
+        if emulator.is_some() {
+            egui::TopBottomPanel::top("quick_actions")
+                .frame(frame)
+                .show_inside(ui, |ui| {
+                    quick_actions(ui, &mut hotkey_event);
+                });
+        }
+
         let left_panel = egui::SidePanel::left("left_panel").frame(frame);
         left_panel.show_inside(ui, |ui| {
             ui.set_width(width / 4.0);
@@ -599,7 +1211,18 @@ fn menu_system(
                     ui,
                     emulator.as_ref().map(|r| r.as_ref()),
                     app_state.as_mut(),
-                    persistent_state.as_ref(),
+                    persistent_state.as_mut(),
+                    &mut menu_state.favorites_only,
+                    &mut menu_event,
+                );
+            }
+            MenuTab::Library => {
+                tab_library(
+                    ui,
+                    menu_state.as_mut(),
+                    config.as_mut(),
+                    persistent_state.as_mut(),
+                    library_scan.as_mut(),
                     &mut menu_event,
                 );
             }
@@ -610,36 +1233,180 @@ fn menu_system(
                         emulator,
                         config.as_ref(),
                         app_state.as_mut(),
+                        persistent_state.as_mut(),
                         &mut message_event,
+                        &mut menu_state.renaming_slot,
+                        &mut menu_state.state_thumbnails,
                     );
                 }
             }
             MenuTab::GameInfo => {
                 if let Some(emulator) = emulator.as_deref() {
-                    tab_game_info(ui, emulator);
+                    tab_game_info(ui, emulator, config.as_mut(), &mut message_event);
+                }
+            }
+            MenuTab::Cheats => {
+                if let Some(emulator) = emulator.as_deref_mut() {
+                    tab_cheats(ui, emulator, menu_state.as_mut(), &mut message_event);
+                }
+            }
+            MenuTab::Screenshots => {
+                if let Some(emulator) = emulator.as_deref() {
+                    tab_screenshots(
+                        ui,
+                        emulator,
+                        config.as_ref(),
+                        menu_state.as_mut(),
+                        &mut message_event,
+                    );
                 }
             }
             MenuTab::GeneralSetting => {
-                ui.heading("General Settings");
+                ui.heading(tr("General Settings"));
+                ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
+                    ui.group(|ui| {
+                        profile_setting(
+                            ui,
+                            &mut config,
+                            persistent_state.as_mut(),
+                            menu_state.as_mut(),
+                            &mut message_event,
+                        );
+                    });
+                    ui.group(|ui| {
+                        tab_general_setting(ui, &mut config, &mut message_event);
+                    });
+                });
+            }
+            MenuTab::Audio => {
+                ui.heading(tr("Audio Settings"));
                 ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
                     ui.group(|ui| {
-                        tab_general_setting(ui, &mut config);
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Volume:"));
+                            let mut percent = (config.volume.clamp(0.0, 1.0) * 100.0).round();
+                            if ui
+                                .add_enabled(
+                                    !muted.0,
+                                    egui::Slider::new(&mut percent, 0.0..=100.0).suffix("%"),
+                                )
+                                .changed()
+                            {
+                                config.volume = percent / 100.0;
+                            }
+                        });
+                        ui.checkbox(&mut muted.0, tr("Mute"));
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Output device:"));
+
+                            // Enumerating devices is slow, only do it on demand
+                            let devices = menu_state
+                                .audio_devices
+                                .get_or_insert_with(audio_output_devices);
+                            egui::ComboBox::from_id_source("audio_device")
+                                .selected_text(
+                                    config.audio_device.as_deref().unwrap_or(tr("Default")),
+                                )
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(
+                                        &mut config.audio_device,
+                                        None,
+                                        tr("Default"),
+                                    );
+                                    for device in devices.iter() {
+                                        ui.selectable_value(
+                                            &mut config.audio_device,
+                                            Some(device.clone()),
+                                            device,
+                                        );
+                                    }
+                                });
+                            if ui.button(tr("Refresh")).clicked() {
+                                menu_state.audio_devices = None;
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Latency:"));
+                            ui.add(
+                                egui::Slider::new(&mut config.audio_latency_ms, 17..=250)
+                                    .suffix("ms"),
+                            )
+                            .on_hover_text(tr(
+                                "Lower values reduce lag but may crackle on slow machines",
+                            ));
+                        });
                     });
                 });
             }
             MenuTab::Graphics => {
-                ui.heading("Gaphics Settings");
+                ui.heading(tr("Graphics Settings"));
                 ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
                     ui.group(|ui| {
-                        ui.checkbox(&mut config.show_fps, "Display FPS");
+                        ui.checkbox(&mut config.show_fps, tr("Display FPS"));
+                        ui.add_enabled_ui(config.show_fps, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(
+                                    &mut config.show_fps_stats,
+                                    tr("Display FPS statistics (min/avg/max)"),
+                                );
+                                if ui.button(tr("Reset")).clicked() {
+                                    fps_stats.reset();
+                                }
+                            });
+                            ui.checkbox(
+                                &mut config.show_emulation_speed,
+                                tr("Display emulation speed"),
+                            )
+                            .on_hover_text(tr("Percentage of the speed of the original hardware"));
+                            ui.horizontal(|ui| {
+                                ui.label(tr("Position:"));
+                                for (position, text) in [
+                                    (FpsPosition::TopLeft, tr("Top left")),
+                                    (FpsPosition::TopRight, tr("Top right")),
+                                    (FpsPosition::BottomLeft, tr("Bottom left")),
+                                    (FpsPosition::BottomRight, tr("Bottom right")),
+                                ] {
+                                    ui.radio_value(&mut config.fps_position, position, text);
+                                }
+                                ui.label(tr("Color:"));
+                                ui.color_edit_button_srgb(&mut config.fps_color);
+                            });
+                        });
+                        ui.checkbox(&mut config.show_audio_viz, tr("Display audio visualizer"));
+                        ui.checkbox(&mut config.input_overlay, tr("Display pressed buttons"));
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Message duration:"));
+                            ui.add(
+                                egui::Slider::new(&mut config.message_duration_secs, 1.0..=15.0)
+                                    .suffix("s"),
+                            );
+                            ui.label(tr("Message size:"));
+                            ui.add(egui::Slider::new(&mut config.message_font_size, 8.0..=48.0));
+                        });
 
                         let mut fullscreen = fullscreen_state.0;
-                        if ui.checkbox(&mut fullscreen, "Full Screen").changed() {
+                        if ui.checkbox(&mut fullscreen, tr("Full Screen")).changed() {
                             window_control_event.send(WindowControlEvent::ToggleFullscreen);
                         }
 
                         ui.horizontal(|ui| {
-                            ui.label("Window Scale:");
+                            ui.checkbox(
+                                &mut config.double_click_fullscreen,
+                                tr("Double-click toggles full screen"),
+                            );
+                            ui.add_enabled(
+                                config.double_click_fullscreen,
+                                egui::Slider::new(&mut config.double_click_seconds, 0.1..=1.0)
+                                    .suffix("s"),
+                            )
+                            .on_hover_text(tr("Longest interval between the clicks"));
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Window Scale:"));
 
                             if ui
                                 .add(egui::Slider::new(&mut config.scaling, 1..=8))
@@ -649,48 +1416,351 @@ fn menu_system(
                                     .send(WindowControlEvent::ChangeScale(config.scaling));
                             }
                         });
-                    });
-                });
-            }
-            MenuTab::CoreSetting(core) => {
-                let core_info = Emulator::core_infos()
-                    .into_iter()
-                    .find(|c| c.abbrev == core)
-                    .unwrap();
 
-                ui.heading(format!("{} Settings", core_info.system_name));
-                ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
-                    ui.group(|ui| {
-                        Emulator::config_ui(&mut EguiUi(ui), core_info.abbrev, config.as_mut());
-                    });
-                });
-            }
-            MenuTab::ControllerSetting(core) => {
-                let core_info = Emulator::core_infos()
-                    .into_iter()
-                    .find(|c| c.abbrev == core)
-                    .unwrap();
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Minimum window size:"));
+                            ui.add(
+                                egui::DragValue::new(&mut config.min_window_size[0])
+                                    .clamp_range(0..=7680),
+                            );
+                            ui.label("x");
+                            ui.add(
+                                egui::DragValue::new(&mut config.min_window_size[1])
+                                    .clamp_range(0..=4320),
+                            );
+                        });
 
-                ui.heading(format!("{} Controller Settings", core_info.system_name));
-                menu_state.tab_controller(
-                    ui,
-                    config.as_mut(),
-                    &core,
-                    key_code_input.as_ref(),
-                    gamepad_button_input.as_ref(),
-                );
-            }
-            MenuTab::HotKey => {
-                ui.heading("Hotkey Settings");
-                menu_state.tab_hotkey(
-                    ui,
-                    config.as_mut(),
-                    key_code_input.as_ref(),
-                    gamepad_button_input.as_ref(),
-                );
-            }
+                        ui.horizontal(|ui| {
+                            let mut limited = config.max_window_size.is_some();
+                            if ui
+                                .checkbox(&mut limited, tr("Maximum window size:"))
+                                .on_hover_text(tr("Otherwise limited by the monitor"))
+                                .changed()
+                            {
+                                config.max_window_size = limited.then(|| [1920, 1080]);
+                            }
+                            if let Some(size) = config.max_window_size.as_mut() {
+                                ui.add(egui::DragValue::new(&mut size[0]).clamp_range(1..=7680));
+                                ui.label("x");
+                                ui.add(egui::DragValue::new(&mut size[1]).clamp_range(1..=4320));
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Scale presets:"));
+                            let mut remove = None;
+                            for (ix, preset) in config.scale_presets.iter_mut().enumerate() {
+                                match preset {
+                                    ScalePreset::Scale(scale) => {
+                                        ui.add(
+                                            egui::DragValue::new(scale)
+                                                .clamp_range(1..=8)
+                                                .suffix("x"),
+                                        );
+                                    }
+                                    ScalePreset::Fit => {
+                                        ui.label(tr("Fit"));
+                                    }
+                                }
+                                if ui.small_button("🗙").clicked() {
+                                    remove = Some(ix);
+                                }
+                            }
+                            if let Some(ix) = remove {
+                                config.scale_presets.remove(ix);
+                            }
+                            if ui.button("+").clicked() {
+                                config
+                                    .scale_presets
+                                    .push(ScalePreset::Scale(config.scaling));
+                            }
+                            if ui.button(tr("+ Fit")).clicked() {
+                                config.scale_presets.push(ScalePreset::Fit);
+                            }
+                        })
+                        .response
+                        .on_hover_text(tr("Chosen in turn by the scale presets hotkey"));
+
+                        ui.checkbox(&mut config.integer_scale, tr("Integer scaling"))
+                            .on_hover_text(tr("Scale by whole numbers only for crisp pixels"));
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Aspect ratio:"));
+                            ui.radio_value(
+                                &mut config.aspect_ratio,
+                                AspectRatio::Pixel,
+                                tr("Square pixels"),
+                            );
+                            ui.radio_value(
+                                &mut config.aspect_ratio,
+                                AspectRatio::Core,
+                                tr("Original display"),
+                            );
+                            ui.radio_value(
+                                &mut config.aspect_ratio,
+                                AspectRatio::Stretch,
+                                tr("Stretch"),
+                            );
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Rotation:"));
+                            for (rotation, label) in [
+                                (ScreenRotation::Deg0, "0°"),
+                                (ScreenRotation::Deg90, "90°"),
+                                (ScreenRotation::Deg180, "180°"),
+                                (ScreenRotation::Deg270, "270°"),
+                            ] {
+                                ui.radio_value(&mut config.screen_rotation, rotation, label);
+                            }
+                        });
+                        ui.checkbox(&mut config.rotate_input, tr("Rotate directional input"))
+                            .on_hover_text(tr("Up on the pad moves up on the rotated screen"));
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("VSync:"));
+                            ui.radio_value(&mut config.vsync, Vsync::Fifo, tr("On"));
+                            ui.radio_value(&mut config.vsync, Vsync::Mailbox, tr("Mailbox"))
+                                .on_hover_text(tr("Low latency without tearing, if supported"));
+                            ui.radio_value(&mut config.vsync, Vsync::Immediate, tr("Off"))
+                                .on_hover_text(tr("Lowest latency, may tear"));
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Texture filter:"));
+                            ui.radio_value(
+                                &mut config.texture_filter,
+                                TextureFilter::Nearest,
+                                tr("Nearest"),
+                            );
+                            ui.radio_value(
+                                &mut config.texture_filter,
+                                TextureFilter::Linear,
+                                tr("Bilinear"),
+                            );
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Fullscreen:"));
+                            ui.radio_value(
+                                &mut config.fullscreen_mode,
+                                FullscreenMode::Borderless,
+                                tr("Borderless"),
+                            );
+                            ui.radio_value(
+                                &mut config.fullscreen_mode,
+                                FullscreenMode::Exclusive,
+                                tr("Exclusive"),
+                            )
+                            .on_hover_text(tr(
+                                "Uses the highest resolution and refresh rate of the monitor",
+                            ));
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Fullscreen monitor:"));
+                            let monitor_text = |ix: Option<usize>| match ix {
+                                None => tr("Primary").to_string(),
+                                Some(ix) => monitors
+                                    .0
+                                    .get(ix)
+                                    .cloned()
+                                    .unwrap_or_else(|| trf("{}: Disconnected", &[&(ix + 1)])),
+                            };
+                            egui::ComboBox::from_id_source("fullscreen_monitor")
+                                .selected_text(monitor_text(config.fullscreen_monitor))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(
+                                        &mut config.fullscreen_monitor,
+                                        None,
+                                        monitor_text(None),
+                                    );
+                                    for ix in 0..monitors.0.len() {
+                                        ui.selectable_value(
+                                            &mut config.fullscreen_monitor,
+                                            Some(ix),
+                                            monitor_text(Some(ix)),
+                                        );
+                                    }
+                                });
+                        });
+
+                        ui.checkbox(&mut config.resizable_window, tr("Resizable window"));
+                        ui.add_enabled_ui(config.resizable_window, |ui| {
+                            ui.checkbox(
+                                &mut config.lock_aspect_on_resize,
+                                tr("Keep aspect ratio when resizing"),
+                            );
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Display filter:"));
+                            ui.radio_value(
+                                &mut config.display_filter,
+                                DisplayFilter::None,
+                                tr("None"),
+                            );
+                            ui.radio_value(
+                                &mut config.display_filter,
+                                DisplayFilter::Crt,
+                                tr("CRT"),
+                            );
+                            ui.radio_value(
+                                &mut config.display_filter,
+                                DisplayFilter::Scanlines,
+                                tr("Scanlines"),
+                            );
+                        });
+                        ui.add_enabled_ui(
+                            config.display_filter == DisplayFilter::Scanlines,
+                            |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(tr("Scanline intensity:"));
+                                    ui.add(egui::Slider::new(
+                                        &mut config.scanline_intensity,
+                                        0.0..=1.0,
+                                    ));
+                                });
+                            },
+                        );
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Color correction:"));
+                            for (filter, label) in [
+                                (ColorFilter::None, tr("None")),
+                                (ColorFilter::Protanopia, tr("Protanopia")),
+                                (ColorFilter::Deuteranopia, tr("Deuteranopia")),
+                                (ColorFilter::Tritanopia, tr("Tritanopia")),
+                            ] {
+                                ui.radio_value(&mut config.color_filter, filter, label);
+                            }
+                        })
+                        .response
+                        .on_hover_text(tr(
+                            "Shifts colors that are hard to tell apart into visible ones",
+                        ));
+
+                        file_field(
+                            ui,
+                            tr("Bezel image:"),
+                            &mut config.bezel_image,
+                            &[(tr("PNG image"), &["png"])],
+                            true,
+                        );
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Background color:"));
+                            ui.color_edit_button_srgb(&mut config.background_color);
+                            if ui.button(tr("Reset")).clicked() {
+                                config.background_color = [0, 0, 0];
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr("Game Boy palette:"));
+                            egui::ComboBox::from_id_source("gb_palette")
+                                .selected_text(format!("{:?}", config.gb_palette))
+                                .show_ui(ui, |ui| {
+                                    for palette in [
+                                        GbPalette::Original,
+                                        GbPalette::Grayscale,
+                                        GbPalette::Green,
+                                        GbPalette::Pocket,
+                                        GbPalette::Custom,
+                                    ] {
+                                        ui.selectable_value(
+                                            &mut config.gb_palette,
+                                            palette,
+                                            format!("{palette:?}"),
+                                        );
+                                    }
+                                });
+                            if config.gb_palette == GbPalette::Custom {
+                                for color in &mut config.gb_custom_palette {
+                                    ui.color_edit_button_srgb(color);
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(tr("Only applies to monochrome games"));
+
+                        ui.checkbox(&mut config.auto_brightness, tr("Auto brightness"))
+                            .on_hover_text(tr("Brighten dark scenes automatically"));
+                        ui.add_enabled_ui(config.auto_brightness, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("Strength:"));
+                                ui.add(egui::Slider::new(
+                                    &mut config.auto_brightness_strength,
+                                    0.0..=1.0,
+                                ));
+                            });
+                        });
+
+                        ui.checkbox(&mut config.frame_blend, tr("Frame blending"))
+                            .on_hover_text(tr(
+                                "Smooths games that flicker sprites for transparency",
+                            ));
+                    });
+                });
+            }
+            MenuTab::CoreSetting(core) => {
+                let core_info = Emulator::core_infos()
+                    .into_iter()
+                    .find(|c| c.abbrev == core)
+                    .unwrap();
+
+                ui.heading(trf("{} Settings", &[&core_info.system_name]));
+                ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
+                    ui.group(|ui| {
+                        Emulator::config_ui(&mut EguiUi(ui), core_info.abbrev, config.as_mut());
+                    });
+
+                    if Emulator::supports_clock_multiplier(core_info.abbrev) {
+                        ui.group(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("CPU clock:"));
+                                let mut multiplier = config.clock_multiplier(core_info.abbrev);
+                                if ui
+                                    .add(egui::Slider::new(&mut multiplier, 0.5..=2.0).suffix("x"))
+                                    .changed()
+                                {
+                                    config.set_clock_multiplier(core_info.abbrev, multiplier);
+                                }
+                            });
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                tr("⚠ Changing the CPU clock may break some games"),
+                            );
+                        });
+                    }
+                });
+            }
+            MenuTab::ControllerSetting(core) => {
+                let core_info = Emulator::core_infos()
+                    .into_iter()
+                    .find(|c| c.abbrev == core)
+                    .unwrap();
+
+                ui.heading(trf("{} Controller Settings", &[&core_info.system_name]));
+                menu_state.tab_controller(
+                    ui,
+                    config.as_mut(),
+                    &core,
+                    key_code_input.as_ref(),
+                    gamepad_button_input.as_ref(),
+                    gamepads.as_ref(),
+                );
+            }
+            MenuTab::HotKey => {
+                ui.heading(tr("Hotkey Settings"));
+                menu_state.tab_hotkey(
+                    ui,
+                    config.as_mut(),
+                    key_code_input.as_ref(),
+                    gamepad_button_input.as_ref(),
+                );
+            }
             MenuTab::SystemKey => {
-                ui.heading("System Key Settings");
+                ui.heading(tr("System Key Settings"));
                 menu_state.tab_system_key(
                     ui,
                     config.as_mut(),
@@ -698,6 +1768,9 @@ fn menu_system(
                     gamepad_button_input.as_ref(),
                 );
             }
+            MenuTab::MessageLog => {
+                tab_message_log(ui, message_log.as_mut());
+            }
         });
     });
 
@@ -709,25 +1782,73 @@ fn menu_system(
     }
 }
 
+/// Buttons doing the same as the hotkeys of the most common actions
+fn quick_actions(ui: &mut egui::Ui, hotkey_event: &mut EventWriter<HotKey>) {
+    ui.horizontal(|ui| {
+        for (hotkey, icon, label) in [
+            (HotKey::Menu, "▶", "Resume"),
+            (HotKey::StateSave, "💾", "Save State"),
+            (HotKey::StateLoad, "📂", "Load State"),
+            (HotKey::Reset, "🔄", "Reset"),
+            (HotKey::Screenshot, "📷", "Screenshot"),
+        ] {
+            if ui.button(format!("{icon} {}", tr(label))).clicked() {
+                hotkey_event.send(hotkey);
+            }
+        }
+    });
+    ui.separator();
+}
+
+fn tab_message_log(ui: &mut egui::Ui, message_log: &mut MessageLog) {
+    ui.horizontal(|ui| {
+        ui.heading(tr("Messages"));
+        ui.add_enabled_ui(!message_log.is_empty(), |ui| {
+            if ui.button(tr("Clear log")).clicked() {
+                message_log.clear();
+            }
+        });
+    });
+
+    if message_log.is_empty() {
+        ui.label(tr("No messages yet"));
+        return;
+    }
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("message_log")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for (time, msg) in message_log.iter().rev() {
+                    ui.colored_label(egui::Color32::GRAY, time.format("%H:%M:%S").to_string());
+                    ui.label(msg);
+                    ui.end_row();
+                }
+            });
+    });
+}
+
 fn tab_file(
     ui: &mut egui::Ui,
     emulator: Option<&Emulator>,
     app_state: &mut State<AppState>,
-    persistent_state: &PersistentState,
+    persistent_state: &mut PersistentState,
+    favorites_only: &mut bool,
     menu_event: &mut EventWriter<MenuEvent>,
 ) {
     egui::ScrollArea::vertical().show(ui, |ui| {
         ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
             if let Some(emulator) = &emulator {
-                ui.label(format!("Running `{}`", emulator.game_name));
-                if ui.button("Resume").clicked() {
+                ui.label(trf("Running `{}`", &[&emulator.game_name]));
+                if ui.button(tr("Resume")).clicked() {
                     app_state.set(AppState::Running).unwrap();
                 }
                 ui.separator();
             }
 
-            ui.label("Load ROM");
-            if ui.button("Open File").clicked() {
+            ui.label(tr("Load ROM"));
+            if ui.button(tr("Open File")).clicked() {
                 let mut fd = rfd::FileDialog::new();
 
                 for (name, exts) in file_dialog_filters() {
@@ -743,36 +1864,302 @@ fn tab_file(
             }
 
             ui.separator();
-            ui.label("Recent Files");
+            ui.horizontal(|ui| {
+                ui.label(tr("Recent Files"));
+                ui.add_enabled_ui(!persistent_state.recent.is_empty(), |ui| {
+                    if ui.button(tr("Clear recent")).clicked() {
+                        // Keep the favorites
+                        let favorites = persistent_state.favorites.clone();
+                        persistent_state
+                            .recent
+                            .retain(|p| favorites.iter().any(|f| *f == p.to_string_lossy()));
+                    }
+                });
+                ui.checkbox(favorites_only, tr("Favorites only"));
+            });
 
-            for recent in &persistent_state.recent {
-                if ui
-                    .button(recent.file_name().unwrap().to_string_lossy().to_string())
-                    .clicked()
-                {
-                    menu_event.send(MenuEvent::OpenRomFile(recent.clone()));
+            let mut recents = persistent_state
+                .recent
+                .iter()
+                .filter(|p| !*favorites_only || persistent_state.is_favorite(p))
+                .collect::<Vec<_>>();
+            recents.sort_by_key(|p| !persistent_state.is_favorite(p));
+
+            let mut remove = None;
+            let mut toggle_favorite = None;
+            for recent in recents {
+                let exists = recent.is_file();
+                let name = recent.file_name().unwrap().to_string_lossy().to_string();
+
+                ui.horizontal(|ui| {
+                    if ui.small_button("✖").on_hover_text(tr("Remove")).clicked() {
+                        remove = Some(recent.clone());
+                    }
+                    if favorite_button(ui, persistent_state.is_favorite(recent)) {
+                        toggle_favorite = Some(recent.clone());
+                    }
+                    ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
+                        let button = ui
+                            .add_enabled(exists, egui::Button::new(name))
+                            .on_hover_text(recent.display().to_string())
+                            .on_disabled_hover_text(trf(
+                                "`{}` does not exist",
+                                &[&recent.display()],
+                            ));
+                        if button.clicked() {
+                            menu_event.send(MenuEvent::OpenRomFile(recent.clone()));
+                        }
+                    });
+                });
+            }
+            if let Some(path) = remove {
+                persistent_state.remove_recent(path);
+            }
+            if let Some(path) = toggle_favorite {
+                persistent_state.toggle_favorite(path);
+            }
+        });
+    });
+}
+
+/// Star toggling a favorite, returns whether it was clicked
+fn favorite_button(ui: &mut egui::Ui, favorite: bool) -> bool {
+    let (text, hover) = if favorite {
+        ("★", tr("Remove from favorites"))
+    } else {
+        ("☆", tr("Add to favorites"))
+    };
+    ui.small_button(text).on_hover_text(hover).clicked()
+}
+
+fn tab_library(
+    ui: &mut egui::Ui,
+    menu_state: &mut MenuState,
+    config: &mut Config,
+    persistent_state: &mut PersistentState,
+    library_scan: &mut LibraryScan,
+    menu_event: &mut EventWriter<MenuEvent>,
+) {
+    ui.heading(tr("Library"));
+
+    let mut rescan = false;
+    let mut remove = None;
+
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(tr("ROM folders"));
+            if ui.button(tr("Add folder")).clicked() {
+                if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                    if !config.rom_dirs.contains(&dir) {
+                        config.rom_dirs.push(dir);
+                        rescan = true;
+                    }
                 }
             }
+            ui.add_enabled_ui(
+                !library_scan.is_scanning() && !config.rom_dirs.is_empty(),
+                |ui| {
+                    if ui.button(tr("Rescan")).clicked() {
+                        library_scan.start(
+                            config.rom_dirs.clone(),
+                            persistent_state.library.clone(),
+                            true,
+                        );
+                    }
+                },
+            );
+            if library_scan.is_scanning() {
+                ui.label(tr("Scanning..."));
+            }
+        });
+
+        for (i, dir) in config.rom_dirs.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text(tr("Remove")).clicked() {
+                    remove = Some(i);
+                }
+                ui.label(dir.display().to_string());
+            });
+        }
+    });
+
+    if let Some(i) = remove {
+        config.rom_dirs.remove(i);
+        rescan = true;
+    }
+    if rescan {
+        library_scan.start(
+            config.rom_dirs.clone(),
+            persistent_state.library.clone(),
+            false,
+        );
+    }
+
+    let games = &persistent_state.library.games;
+    if games.is_empty() {
+        ui.label(if config.rom_dirs.is_empty() {
+            tr("Add a folder to build the library")
+        } else {
+            tr("No games found")
         });
+        return;
+    }
+
+    let mut launch_top = false;
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        let resp = ui.add(
+            egui::TextEdit::singleline(&mut menu_state.library_filter).hint_text(tr("Filter")),
+        );
+        if std::mem::take(&mut menu_state.focus_library_filter) {
+            resp.request_focus();
+        }
+        launch_top = resp.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+
+        let system_text = |abbrev: Option<&str>| match abbrev {
+            Some(abbrev) => Emulator::core_infos()
+                .into_iter()
+                .find(|core_info| core_info.abbrev == abbrev)
+                .map_or(abbrev.to_string(), |core_info| {
+                    core_info.system_name.to_string()
+                }),
+            None => tr("All systems").to_string(),
+        };
+        egui::ComboBox::from_id_source("library_system")
+            .selected_text(system_text(menu_state.library_system.as_deref()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut menu_state.library_system, None, system_text(None));
+                for core_info in Emulator::core_infos() {
+                    ui.selectable_value(
+                        &mut menu_state.library_system,
+                        Some(core_info.abbrev.to_string()),
+                        core_info.system_name,
+                    );
+                }
+            });
+        ui.checkbox(&mut menu_state.favorites_only, tr("Favorites only"));
+    });
+
+    let filter = menu_state.library_filter.to_lowercase();
+    let mut games = games
+        .iter()
+        .filter(|game| {
+            menu_state
+                .library_system
+                .as_ref()
+                .map_or(true, |abbrev| game.cores.contains(abbrev))
+                && (!menu_state.favorites_only || persistent_state.is_favorite(&game.path))
+                && game.name().to_lowercase().contains(&filter)
+        })
+        .collect::<Vec<_>>();
+    games.sort_by_key(|game| !persistent_state.is_favorite(&game.path));
+
+    if launch_top {
+        if let Some(game) = games.first() {
+            menu_event.send(MenuEvent::OpenRomFile(game.path.clone()));
+        }
+    }
+
+    let mut toggle_favorite = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("library")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for game in games {
+                    if favorite_button(ui, persistent_state.is_favorite(&game.path)) {
+                        toggle_favorite = Some(game.path.clone());
+                    }
+                    if ui
+                        .button(game.name())
+                        .on_hover_text(game.path.display().to_string())
+                        .clicked()
+                    {
+                        menu_event.send(MenuEvent::OpenRomFile(game.path.clone()));
+                    }
+                    ui.label(game.system_name());
+                    ui.end_row();
+                }
+            });
     });
+    if let Some(path) = toggle_favorite {
+        persistent_state.toggle_favorite(path);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn tab_state(
     ui: &mut egui::Ui,
     emulator: &mut Emulator,
     config: &Config,
     app_state: &mut State<AppState>,
+    persistent_state: &mut PersistentState,
     message_event: &mut EventWriter<ShowMessage>,
+    renaming_slot: &mut Option<(usize, String)>,
+    thumbnails: &mut BTreeMap<usize, Option<egui::TextureHandle>>,
 ) {
-    ui.heading("State Save / Load");
+    ui.heading(tr("State Save / Load"));
 
     ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
         ui.group(|ui| {
-            ui.label("Slot");
+            ui.label(tr("Slot"));
 
             let grid = |ui: &mut egui::Ui| {
-                for i in 0..10 {
-                    ui.label(format!("{}", i));
+                for i in 0..config.state_slot_count() {
+                    let meta = emulator.state_meta(i, config).unwrap_or_default();
+
+                    let thumbnail = thumbnails.entry(i).or_insert_with(|| {
+                        let image = emulator.state_thumbnail(i, config).ok().flatten()?;
+                        let size = [image.width() as usize, image.height() as usize];
+                        Some(ui.ctx().load_texture(
+                            format!("state_thumbnail_{i}"),
+                            egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()),
+                        ))
+                    });
+                    let thumbnail_size =
+                        egui::vec2(STATE_THUMBNAIL_HEIGHT * 4.0 / 3.0, STATE_THUMBNAIL_HEIGHT);
+                    if let Some(thumbnail) = thumbnail {
+                        let size = thumbnail.size_vec2();
+                        ui.image(thumbnail.id(), size * (STATE_THUMBNAIL_HEIGHT / size.y));
+                    } else {
+                        let (rect, _) =
+                            ui.allocate_exact_size(thumbnail_size, egui::Sense::hover());
+                        ui.painter()
+                            .rect_filled(rect, 0.0, egui::Color32::from_gray(32));
+                        ui.painter().text(
+                            rect.center(),
+                            egui::Align2::CENTER_CENTER,
+                            tr("No image"),
+                            egui::FontId::default(),
+                            egui::Color32::GRAY,
+                        );
+                    }
+
+                    match renaming_slot {
+                        Some((slot, label)) if *slot == i => {
+                            let resp = ui.text_edit_singleline(label);
+                            if resp.lost_focus() {
+                                let label = label.trim();
+                                let label = (!label.is_empty()).then(|| label.to_string());
+                                if let Err(e) = emulator.set_state_label(i, label, config) {
+                                    error!("Failed to rename state: {}", e);
+                                }
+                                *renaming_slot = None;
+                            } else {
+                                resp.request_focus();
+                            }
+                        }
+                        _ => {
+                            let name = meta.label.clone().unwrap_or_else(|| trf("Slot {}", &[&i]));
+                            if ui
+                                .selectable_label(false, name)
+                                .on_hover_text(tr("Click to rename"))
+                                .clicked()
+                            {
+                                *renaming_slot = Some((i, meta.label.unwrap_or_default()));
+                            }
+                        }
+                    }
 
                     let date = state_date(
                         emulator.core.core_info().abbrev,
@@ -782,48 +2169,195 @@ fn tab_state(
                     )
                     .unwrap();
 
-                    if ui.button("Save").clicked() {
-                        emulator.save_state_slot(i, config).unwrap();
-                        message_event.send(ShowMessage(format!("State saved: #{}", i)));
+                    if ui.button(tr("Save")).clicked() {
+                        match emulator.save_state_slot(i, config) {
+                            Ok(()) => {
+                                persistent_state.set_last_state_slot(&emulator.game_name, i);
+                                message_event.send(ShowMessage(trf(
+                                    "State saved: {}",
+                                    &[&emulator.state_slot_title(i, config)],
+                                )));
+                            }
+                            Err(e) => {
+                                message_event
+                                    .send(ShowMessage(trf("Failed to save state: {}", &[&e])));
+                                error!("Failed to save state: {}", e);
+                            }
+                        }
+                        thumbnails.remove(&i);
                     }
                     ui.add_enabled_ui(date.is_some(), |ui| {
-                        if ui.button("Load").clicked() {
+                        if ui.button(tr("Load")).clicked() {
                             match emulator.load_state_slot(i, config) {
                                 Ok(_) => {
-                                    message_event
-                                        .send(ShowMessage(format!("State loaded: #{}", i)));
+                                    message_event.send(ShowMessage(trf(
+                                        "State loaded: {}",
+                                        &[&emulator.state_slot_title(i, config)],
+                                    )));
+                                    if emulator.state_rom_mismatch(i, config) {
+                                        message_event.send(ShowMessage(
+                                            tr("Warning: state was saved with a different ROM")
+                                                .to_string(),
+                                        ));
+                                    }
                                 }
                                 Err(e) => {
                                     message_event
-                                        .send(ShowMessage("Failed to load state".to_string()));
+                                        .send(ShowMessage(trf("Failed to load state: {}", &[&e])));
                                     error!("Failed to load state: {}", e);
                                 }
                             }
                             app_state.set(AppState::Running).unwrap();
                         }
+                        if ui.button(tr("Delete")).clicked() {
+                            if let Err(e) = emulator.delete_state_slot(i, config) {
+                                error!("Failed to delete state: {}", e);
+                            }
+                            thumbnails.remove(&i);
+                        }
                     });
 
-                    ui.label(date.map_or_else(
-                        || "---".to_string(),
-                        |date| date.format("%Y/%m/%d %H:%M:%S").to_string(),
-                    ));
+                    if let Some(date) = date {
+                        let saved_at = meta
+                            .saved_at
+                            .clone()
+                            .unwrap_or_else(|| date.format("%Y-%m-%d %H:%M").to_string());
+                        let info = match &meta.rom_name {
+                            Some(rom_name) => format!("{rom_name} — {saved_at}"),
+                            None => saved_at,
+                        };
+                        let resp = ui.label(info);
+                        if let (Some(core), Some(version)) = (&meta.core, &meta.version) {
+                            resp.on_hover_text(format!("{core} (meru {version})"));
+                        }
+                    } else {
+                        ui.colored_label(egui::Color32::GRAY, tr("Empty"));
+                    }
                     ui.end_row();
                 }
             };
 
             egui::Grid::new("state_save")
-                .num_columns(4)
+                .num_columns(5)
                 .spacing([40.0, 4.0])
                 .striped(true)
                 .show(ui, grid);
         });
+
+        ui.group(|ui| {
+            ui.label(tr("State file"));
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("Save State As…")).clicked() {
+                    let file = rfd::FileDialog::new()
+                        .add_filter(tr("State file"), &[STATE_FILE_EXTENSION])
+                        .set_file_name(&format!("{}.{STATE_FILE_EXTENSION}", emulator.game_name))
+                        .save_file();
+                    if let Some(file) = file {
+                        match emulator.save_state_file(&file) {
+                            Ok(()) => {
+                                message_event.send(ShowMessage(tr("State saved").to_string()))
+                            }
+                            Err(err) => {
+                                error!("Failed to save state: {err}");
+                                message_event
+                                    .send(ShowMessage(trf("Failed to save state: {}", &[&err])));
+                            }
+                        }
+                    }
+                }
+
+                if ui.button(tr("Load State From…")).clicked() {
+                    let file = rfd::FileDialog::new()
+                        .add_filter(tr("State file"), &[STATE_FILE_EXTENSION])
+                        .pick_file();
+                    if let Some(file) = file {
+                        match emulator.load_state_file(&file) {
+                            Ok(rom_mismatch) => {
+                                message_event.send(ShowMessage(tr("State loaded").to_string()));
+                                if let Some(rom_name) = rom_mismatch {
+                                    message_event.send(ShowMessage(trf(
+                                        "Warning: state was saved by {}",
+                                        &[&rom_name],
+                                    )));
+                                }
+                                app_state.set(AppState::Running).unwrap();
+                            }
+                            Err(err) => {
+                                error!("Failed to load state: {err}");
+                                message_event
+                                    .send(ShowMessage(trf("Failed to load state: {}", &[&err])));
+                            }
+                        }
+                    }
+                }
+            });
+        });
+
+        ui.group(|ui| {
+            ui.label(tr("Movie"));
+
+            ui.horizontal(|ui| {
+                match emulator.movie_mode() {
+                    Some(MovieMode::Recording { movie, .. }) => {
+                        ui.label(trf("Recording: {} frames", &[&movie.frames()]));
+                    }
+                    Some(MovieMode::Playing { movie, frame, .. }) => {
+                        ui.label(trf("Playing: {} / {} frames", &[&frame, &movie.frames()]));
+                    }
+                    None => {
+                        ui.label(tr("Not running"));
+                    }
+                }
+
+                if ui.button(tr("Play movie")).clicked() {
+                    let file = rfd::FileDialog::new()
+                        .add_filter(tr("Movie file"), &[MOVIE_EXTENSION])
+                        .set_directory(&config.recording_dir)
+                        .pick_file();
+                    if let Some(file) = file {
+                        match emulator.play_movie(&file) {
+                            Ok(()) => app_state.set(AppState::Running).unwrap(),
+                            Err(err) => {
+                                error!("Failed to play movie: {err}");
+                                message_event
+                                    .send(ShowMessage(trf("Failed to play movie: {}", &[&err])));
+                            }
+                        }
+                    }
+                }
+
+                if ui
+                    .add_enabled(
+                        emulator.movie_mode().is_some(),
+                        egui::Button::new(tr("Stop")),
+                    )
+                    .clicked()
+                {
+                    match emulator.stop_movie() {
+                        Ok(Some(path)) => message_event
+                            .send(ShowMessage(trf("Movie saved: {}", &[&path.display()]))),
+                        Ok(None) => {}
+                        Err(err) => {
+                            error!("Failed to save movie: {err}");
+                            message_event.send(ShowMessage(tr("Failed to save movie").to_string()));
+                        }
+                    }
+                }
+            });
+        });
     });
 }
 
-fn tab_game_info(ui: &mut egui::Ui, emulator: &Emulator) {
+fn tab_game_info(
+    ui: &mut egui::Ui,
+    emulator: &Emulator,
+    config: &mut Config,
+    message_event: &mut EventWriter<ShowMessage>,
+) {
     let info = emulator.core.game_info();
 
-    ui.heading("Game Info");
+    ui.heading(tr("Game Info"));
 
     egui::Grid::new("key_config")
         .num_columns(2)
@@ -836,28 +2370,647 @@ fn tab_game_info(ui: &mut egui::Ui, emulator: &Emulator) {
                 ui.end_row();
             }
         });
+
+    if Emulator::has_bios_menu(emulator.core.core_info().abbrev) {
+        ui.separator();
+
+        let mut boot_to_bios = config.boot_to_bios_games.contains(&emulator.game_name);
+        if ui
+            .checkbox(&mut boot_to_bios, tr("Boot into BIOS menu for this game"))
+            .changed()
+        {
+            if boot_to_bios {
+                config.boot_to_bios_games.insert(emulator.game_name.clone());
+            } else {
+                config.boot_to_bios_games.remove(&emulator.game_name);
+            }
+        }
+    }
+
+    if let Some(count) = Emulator::multitap_controllers(emulator.core.core_info().abbrev) {
+        ui.separator();
+
+        let mut multitap = config.multitap_games.contains(&emulator.game_name);
+        if ui
+            .checkbox(
+                &mut multitap,
+                trf("Connect multitap for this game ({} players)", &[&count]),
+            )
+            .on_hover_text(tr("Takes effect after reloading the game"))
+            .changed()
+        {
+            if multitap {
+                config.multitap_games.insert(emulator.game_name.clone());
+            } else {
+                config.multitap_games.remove(&emulator.game_name);
+            }
+        }
+    }
+
+    #[cfg(feature = "link-cable")]
+    if Emulator::supports_link_cable(emulator.core.core_info().abbrev) {
+        ui.separator();
+
+        let mut partner = config.link_partners.get(&emulator.game_name).cloned();
+        let filter = Emulator::core_infos()
+            .into_iter()
+            .find(|info| info.abbrev == emulator.core.core_info().abbrev)
+            .unwrap()
+            .file_extensions;
+        if file_field(
+            ui,
+            tr("Link cable partner ROM (experimental):"),
+            &mut partner,
+            &[(tr("ROM file"), filter)],
+            true,
+        ) {
+            if let Some(partner) = partner {
+                config
+                    .link_partners
+                    .insert(emulator.game_name.clone(), partner);
+            } else {
+                config.link_partners.remove(&emulator.game_name);
+            }
+        }
+    }
+
+    ui.separator();
+
+    ui.label(if config.has_game_override() {
+        tr("This game has its own settings")
+    } else {
+        tr("This game uses the profile's settings")
+    })
+    .on_hover_text(tr(
+        "Scaling, display, palette and key settings can be saved per game",
+    ));
+    ui.horizontal(|ui| {
+        if ui
+            .button(tr("Save current settings for this game"))
+            .clicked()
+        {
+            match config.save_game_override() {
+                Ok(()) => message_event.send(ShowMessage(tr("Game settings saved").to_string())),
+                Err(err) => {
+                    error!("Failed to save game settings: {err}");
+                    message_event.send(ShowMessage(tr("Failed to save game settings").to_string()));
+                }
+            }
+        }
+        if ui
+            .add_enabled(config.has_game_override(), egui::Button::new(tr("Remove")))
+            .clicked()
+        {
+            if let Err(err) = config.delete_game_override() {
+                error!("Failed to remove game settings: {err}");
+                message_event.send(ShowMessage(
+                    tr("Failed to remove game settings").to_string(),
+                ));
+            }
+        }
+    });
 }
 
-fn tab_general_setting(ui: &mut egui::Ui, config: &mut ResMut<Config>) {
+fn tab_screenshots(
+    ui: &mut egui::Ui,
+    emulator: &Emulator,
+    config: &Config,
+    menu_state: &mut MenuState,
+    message_event: &mut EventWriter<ShowMessage>,
+) {
+    ui.heading(tr("Screenshots"));
+
+    let screenshots = menu_state.screenshots.get_or_insert_with(|| {
+        let paths =
+            list_screenshots(&config.screenshot_dir, &emulator.game_name).unwrap_or_else(|err| {
+                error!("Failed to list screenshots: {err}");
+                vec![]
+            });
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let image = image::open(&path).ok()?.to_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                let texture = ui.ctx().load_texture(
+                    path.to_string_lossy(),
+                    egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()),
+                );
+                Some((path, texture))
+            })
+            .collect()
+    });
+
+    if screenshots.is_empty() {
+        ui.label(tr("No screenshots of this game yet"));
+        return;
+    }
+
+    let mut deleted = None;
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            for (path, texture) in screenshots.iter() {
+                ui.vertical(|ui| {
+                    let size = texture.size_vec2();
+                    let thumbnail_size = size * (SCREENSHOT_THUMBNAIL_HEIGHT / size.y);
+                    if ui
+                        .add(egui::ImageButton::new(texture.id(), thumbnail_size))
+                        .on_hover_text(path.file_name().unwrap().to_string_lossy().to_string())
+                        .clicked()
+                    {
+                        menu_state.viewing_screenshot = Some(path.clone());
+                    }
+                    if ui.button(tr("Delete")).clicked() {
+                        deleted = Some(path.clone());
+                    }
+                });
+            }
+        });
+    });
+
+    if let Some(path) = &menu_state.viewing_screenshot {
+        let mut open = true;
+        if let Some((_, texture)) = screenshots.iter().find(|(p, _)| p == path) {
+            egui::Window::new(path.file_name().unwrap().to_string_lossy().to_string())
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.image(texture.id(), texture.size_vec2() * 2.0);
+                });
+        } else {
+            open = false;
+        }
+        if !open {
+            menu_state.viewing_screenshot = None;
+        }
+    }
+
+    if let Some(path) = deleted {
+        match fs::remove_file(&path) {
+            Ok(()) => message_event.send(ShowMessage(tr("Screenshot deleted").to_string())),
+            Err(err) => {
+                error!("Failed to delete screenshot: {err}");
+                message_event.send(ShowMessage(tr("Failed to delete screenshot").to_string()));
+            }
+        }
+        if menu_state.viewing_screenshot.as_ref() == Some(&path) {
+            menu_state.viewing_screenshot = None;
+        }
+        // Reloaded on the next frame
+        menu_state.screenshots = None;
+    }
+}
+
+fn tab_cheats(
+    ui: &mut egui::Ui,
+    emulator: &mut Emulator,
+    menu_state: &mut MenuState,
+    message_event: &mut EventWriter<ShowMessage>,
+) {
+    ui.heading(tr("Cheats"));
+
+    if !emulator.core.supports_cheats() {
+        ui.label(tr("Cheats are not supported for this system"));
+        return;
+    }
+
+    let mut toggled = None;
+    let mut removed = None;
+
+    egui::Grid::new("cheats")
+        .num_columns(3)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for (i, cheat) in emulator.cheats().iter().enumerate() {
+                let mut enabled = cheat.enabled;
+                if ui.checkbox(&mut enabled, &cheat.code).changed() {
+                    toggled = Some((i, enabled));
+                }
+                ui.label(&cheat.description);
+                if ui.button("✖").on_hover_text(tr("Remove")).clicked() {
+                    removed = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    let result = if let Some((i, enabled)) = toggled {
+        emulator.set_cheat_enabled(i, enabled)
+    } else if let Some(i) = removed {
+        emulator.remove_cheat(i)
+    } else {
+        Ok(())
+    };
+    if let Err(err) = result {
+        error!("Failed to save cheats: {err}");
+        message_event.send(ShowMessage(tr("Failed to save cheats").to_string()));
+    }
+
+    ui.separator();
+
+    egui::Grid::new("new_cheat")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .show(ui, |ui| {
+            ui.label(tr("Code:"));
+            ui.text_edit_singleline(&mut menu_state.new_cheat_code);
+            ui.end_row();
+
+            ui.label(tr("Description:"));
+            ui.text_edit_singleline(&mut menu_state.new_cheat_description);
+            ui.end_row();
+        });
+
+    if ui
+        .add_enabled(
+            !menu_state.new_cheat_code.trim().is_empty(),
+            egui::Button::new(tr("Add cheat")),
+        )
+        .clicked()
+    {
+        match emulator.add_cheat(
+            &menu_state.new_cheat_code,
+            &menu_state.new_cheat_description,
+        ) {
+            Ok(()) => {
+                menu_state.new_cheat_code.clear();
+                menu_state.new_cheat_description.clear();
+            }
+            Err(err) => message_event.send(ShowMessage(err.to_string())),
+        }
+    }
+}
+
+fn profile_setting(
+    ui: &mut egui::Ui,
+    config: &mut ResMut<Config>,
+    persistent_state: &mut PersistentState,
+    menu_state: &mut MenuState,
+    message_event: &mut EventWriter<ShowMessage>,
+) {
+    let profiles = menu_state
+        .profiles
+        .get_or_insert_with(|| {
+            list_profiles().unwrap_or_else(|err| {
+                error!("Failed to list profiles: {err}");
+                vec![]
+            })
+        })
+        .clone();
+
+    let mut switch_to = None;
+    let mut save_current = true;
+
     ui.horizontal(|ui| {
-        ui.label("Frame skip on turbo:");
+        ui.label(tr("Profile:"));
+        egui::ComboBox::from_id_source("profile")
+            .selected_text(config.profile.as_deref().unwrap_or(tr("Default")))
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(config.profile.is_none(), tr("Default"))
+                    .clicked()
+                {
+                    switch_to = Some(None);
+                }
+                for profile in &profiles {
+                    if ui
+                        .selectable_label(config.profile.as_ref() == Some(profile), profile)
+                        .clicked()
+                    {
+                        switch_to = Some(Some(profile.clone()));
+                    }
+                }
+            });
 
-        ui.add(egui::Slider::new(&mut config.frame_skip_on_turbo, 1..=10));
+        // The default profile can't be deleted
+        if let Some(profile) = config.profile.clone() {
+            if ui.button(tr("Delete")).clicked() {
+                match delete_profile(&profile) {
+                    Ok(()) => {
+                        menu_state.profiles = None;
+                        switch_to = Some(None);
+                        save_current = false;
+                    }
+                    Err(err) => {
+                        error!("Failed to delete profile: {err}");
+                        message_event.send(ShowMessage(tr("Failed to delete profile").to_string()));
+                    }
+                }
+            }
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("New profile:"));
+        ui.text_edit_singleline(&mut menu_state.new_profile_name)
+            .on_hover_text(tr("Letters, digits, spaces, `-` and `_`"));
+
+        let name = menu_state.new_profile_name.clone();
+        let valid = is_valid_profile_name(&name) && !profiles.contains(&name);
+        if ui
+            .add_enabled(valid, egui::Button::new(tr("Create from current")))
+            .clicked()
+        {
+            let mut new_config = (**config).clone();
+            new_config.profile = Some(name.clone());
+            match new_config.save() {
+                Ok(()) => {
+                    menu_state.profiles = None;
+                    menu_state.new_profile_name.clear();
+                    switch_to = Some(Some(name));
+                }
+                Err(err) => {
+                    error!("Failed to create profile: {err}");
+                    message_event.send(ShowMessage(tr("Failed to create profile").to_string()));
+                }
+            }
+        }
+    });
+
+    let profile = match switch_to {
+        Some(profile) if profile != config.profile || !save_current => profile,
+        _ => return,
+    };
+
+    if save_current {
+        if let Err(err) = config.save() {
+            error!("Failed to save config: {err}");
+        }
+    }
+
+    match load_config(profile.as_deref()) {
+        Ok(new_config) => {
+            message_event.send(ShowMessage(trf(
+                "Profile: {}",
+                &[&profile.as_deref().unwrap_or(tr("Default"))],
+            )));
+            persistent_state.active_profile = profile;
+            // Everything else follows the new config on the next frame
+            **config = new_config;
+        }
+        Err(err) => {
+            error!("Failed to load profile: {err}");
+            message_event.send(ShowMessage(tr("Failed to load profile").to_string()));
+        }
+    }
+}
+
+fn tab_general_setting(
+    ui: &mut egui::Ui,
+    config: &mut ResMut<Config>,
+    message_event: &mut EventWriter<ShowMessage>,
+) {
+    ui.horizontal(|ui| {
+        ui.label(tr("Language:"));
+        egui::ComboBox::from_id_source("language")
+            .selected_text(current_language().name)
+            .show_ui(ui, |ui| {
+                for language in LANGUAGES {
+                    ui.selectable_value(
+                        &mut config.language,
+                        language.code.to_string(),
+                        language.name,
+                    );
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Menu theme:"));
+        ui.radio_value(&mut config.menu_theme, MenuTheme::Dark, tr("Dark"));
+        ui.radio_value(&mut config.menu_theme, MenuTheme::Light, tr("Light"));
+        ui.radio_value(&mut config.menu_theme, MenuTheme::Custom, tr("Custom"));
+        ui.add_enabled_ui(config.menu_theme == MenuTheme::Custom, |ui| {
+            ui.color_edit_button_srgb(&mut config.menu_accent_color);
+        });
+    });
+
+    ui.checkbox(&mut config.show_splash, tr("Show splash screen on startup"));
+    ui.checkbox(
+        &mut config.boot_to_bios,
+        tr("Boot into BIOS menu (if the system has one)"),
+    );
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Fast-forward speed:"));
+
+        let choices = [
+            ("x1.5", 1.5),
+            ("x2", 2.0),
+            ("x3", 3.0),
+            ("x4", 4.0),
+            ("x8", 8.0),
+            (tr("Unlimited"), 0.0),
+        ];
+        let selected_text = choices
+            .iter()
+            .find(|(_, speed)| *speed == config.turbo_speed)
+            .map_or_else(
+                || format!("x{}", config.turbo_speed),
+                |(name, _)| name.to_string(),
+            );
+
+        egui::ComboBox::from_id_source("turbo_speed")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for (name, speed) in choices {
+                    ui.selectable_value(&mut config.turbo_speed, speed, name);
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Fast-forward ramp:"));
+        ui.add(egui::Slider::new(&mut config.turbo_ramp_ms, 0..=1000).suffix("ms"))
+            .on_hover_text(tr("Time to speed up to the fast-forward speed and back"));
+    });
+
+    ui.checkbox(
+        &mut config.hold_for_turbo,
+        tr("Hold the turbo key to fast-forward"),
+    )
+    .on_hover_text(tr("Otherwise the key toggles fast-forward on and off"));
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Audio while fast-forwarding:"));
+        ui.radio_value(&mut config.turbo_audio, TurboAudio::Mute, tr("Mute"));
+        ui.radio_value(&mut config.turbo_audio, TurboAudio::SpedUp, tr("Sped up"));
+        ui.radio_value(
+            &mut config.turbo_audio,
+            TurboAudio::PitchCorrected,
+            tr("Pitch corrected"),
+        );
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Slow motion speed:"));
+
+        let mut percent = (config.slow_motion_speed.clamp(0.05, 1.0) * 100.0).round();
+        if ui
+            .add(egui::Slider::new(&mut percent, 5.0..=90.0).suffix("%"))
+            .changed()
+        {
+            config.slow_motion_speed = percent / 100.0;
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Input delay:"));
+
+        ui.add(egui::Slider::new(&mut config.input_delay_frames, 0..=10).suffix("Frames"));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Opposite directions:"));
+        ui.radio_value(&mut config.socd_mode, SocdMode::Neutral, tr("Neutral"));
+        ui.radio_value(&mut config.socd_mode, SocdMode::LastInput, tr("Last input"));
+        ui.radio_value(
+            &mut config.socd_mode,
+            SocdMode::FirstInput,
+            tr("First input"),
+        );
+    });
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut config.rumble_enabled, tr("Rumble"));
+        ui.add_enabled(
+            config.rumble_enabled,
+            egui::Slider::new(&mut config.rumble_strength, 0.0..=1.0).text(tr("Strength")),
+        );
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Stick deadzone:"));
+        let mut percent = (config.stick_deadzone.clamp(0.0, 0.9) * 100.0).round();
+        if ui
+            .add(egui::Slider::new(&mut percent, 0.0..=90.0).suffix("%"))
+            .on_hover_text(tr("Stick movement below this is ignored, to avoid drift"))
+            .changed()
+        {
+            config.stick_deadzone = percent / 100.0;
+        }
+    });
+
+    ui.checkbox(
+        &mut config.pause_on_modal,
+        tr("Pause emulation while a dialog is open"),
+    );
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Dim the game behind dialogs:"));
+        let mut percent = (config.modal_dim.clamp(0.0, 1.0) * 100.0).round();
+        if ui
+            .add(egui::Slider::new(&mut percent, 0.0..=100.0).suffix("%"))
+            .changed()
+        {
+            config.modal_dim = percent / 100.0;
+        }
     });
 
+    ui.checkbox(&mut config.confirm_reset, tr("Confirm before resetting"))
+        .on_hover_text(tr("Pressing the reset hotkey again also confirms"));
+
+    ui.add_enabled_ui(
+        !config.core_selection.is_empty() || !config.rom_cores.is_empty(),
+        |ui| {
+            if ui.button(tr("Forget remembered core selections")).clicked() {
+                config.core_selection.clear();
+                config.rom_cores.clear();
+            }
+        },
+    );
+
     ui.separator();
 
     let mut save_dir = Some(config.save_dir.clone());
-    if file_field(ui, "Save file directory:", &mut save_dir, &[], false) {
+    if file_field(ui, tr("Save file directory:"), &mut save_dir, &[], false) {
         config.save_dir = save_dir.unwrap();
     }
 
+    let mut screenshot_dir = Some(config.screenshot_dir.clone());
+    if file_field(
+        ui,
+        tr("Screenshot directory:"),
+        &mut screenshot_dir,
+        &[],
+        false,
+    ) {
+        config.screenshot_dir = screenshot_dir.unwrap();
+    }
+
+    let mut recording_dir = Some(config.recording_dir.clone());
+    if file_field(
+        ui,
+        tr("Recording directory:"),
+        &mut recording_dir,
+        &[],
+        false,
+    ) {
+        config.recording_dir = recording_dir.unwrap();
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(tr("GIF clip length:"));
+        ui.add(egui::Slider::new(&mut config.gif_duration_secs, 0.0..=15.0).suffix(" s"))
+            .on_hover_text(tr("Saved to the screenshot directory"));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("State slots:"));
+        ui.add(egui::Slider::new(
+            &mut config.state_slot_count,
+            1..=MAX_STATE_SLOTS,
+        ))
+        .on_hover_text(tr("Saves in hidden slots are kept"));
+    });
+
+    ui.checkbox(
+        &mut config.keep_state_labels,
+        tr("Keep slot labels when overwriting"),
+    );
+    ui.checkbox(&mut config.compress_states, tr("Compress state slots"));
+    ui.checkbox(
+        &mut config.resume_on_load,
+        tr("Resume from the last saved slot when opening a game"),
+    );
+
+    ui.horizontal(|ui| {
+        let mut autosave = config.autosave_interval_seconds.is_some();
+        if ui.checkbox(&mut autosave, tr("Autosave every")).changed() {
+            config.autosave_interval_seconds = autosave.then(|| 60);
+        }
+        let mut interval = config.autosave_interval_seconds.unwrap_or(60);
+        if ui
+            .add_enabled(
+                autosave,
+                egui::Slider::new(&mut interval, 10..=600)
+                    .logarithmic(true)
+                    .suffix("s"),
+            )
+            .changed()
+        {
+            config.autosave_interval_seconds = Some(interval);
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Quick swap slots:"));
+        let max_slot = config.state_slot_count() - 1;
+        for slot in config.quick_swap_slots.iter_mut() {
+            ui.add(egui::DragValue::new(slot).clamp_range(0..=max_slot));
+        }
+    });
+
     ui.separator();
 
-    ui.label("Rewinding:");
+    ui.label(tr("Rewinding:"));
 
     ui.horizontal(|ui| {
-        ui.label("Memory budget for rewinding:");
+        ui.label(tr("Memory budget for rewinding:"));
         let mut rate_in_kb = config.auto_state_save_rate / 1024;
         ui.add(
             egui::Slider::new(&mut rate_in_kb, 0..=8192)
@@ -868,7 +3021,7 @@ fn tab_general_setting(ui: &mut egui::Ui, config: &mut ResMut<Config>) {
     });
 
     ui.horizontal(|ui| {
-        ui.label("Maximum memory amount for rewinding:");
+        ui.label(tr("Maximum memory amount for rewinding:"));
         let mut amount_in_mb = config.auto_state_save_limit / (1024 * 1024);
         ui.add(
             egui::Slider::new(&mut amount_in_mb, 0..=8192)
@@ -878,8 +3031,35 @@ fn tab_general_setting(ui: &mut egui::Ui, config: &mut ResMut<Config>) {
         config.auto_state_save_limit = amount_in_mb * 1024 * 1024;
     });
 
+    ui.checkbox(
+        &mut config.compress_rewind_states,
+        tr("Compress rewind snapshots"),
+    )
+    .on_hover_text(tr(
+        "Fits more history in the memory budget, at some CPU cost",
+    ));
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Rewind buffer length:"));
+        let resp =
+            ui.add(egui::Slider::new(&mut config.rewind_buffer_seconds, 5..=120).suffix("s"));
+
+        if config.rewind_buffer_seconds > LARGE_REWIND_BUFFER_SECONDS {
+            let estimate = (config.auto_state_save_rate * config.rewind_buffer_seconds as usize)
+                .min(config.auto_state_save_limit);
+            let warning = trf(
+                "Rewind buffer may use up to {} MiB of memory",
+                &[&(estimate / (1024 * 1024))],
+            );
+            if resp.drag_released() || resp.lost_focus() {
+                message_event.send(ShowMessage(warning.clone()));
+            }
+            ui.colored_label(egui::Color32::YELLOW, warning);
+        }
+    });
+
     ui.horizontal(|ui| {
-        ui.label("Minimum auto save span:");
+        ui.label(tr("Minimum auto save span:"));
         ui.add(
             egui::Slider::new(&mut config.minimum_auto_save_span, 1..=300)
                 .logarithmic(true)
@@ -887,14 +3067,54 @@ fn tab_general_setting(ui: &mut egui::Ui, config: &mut ResMut<Config>) {
         );
     });
 
+    ui.horizontal(|ui| {
+        ui.label(tr("Audio while rewinding:"));
+        ui.radio_value(&mut config.rewind_audio, RewindAudio::Mute, tr("Mute"));
+        ui.radio_value(
+            &mut config.rewind_audio,
+            RewindAudio::Reverse,
+            tr("Reverse"),
+        );
+    });
+
+    ui.checkbox(
+        &mut config.no_rewind_during_turbo,
+        tr("Don't record rewind states during turbo"),
+    );
+
+    ui.checkbox(
+        &mut config.hold_for_rewind,
+        tr("Hold the rewind key while rewinding"),
+    )
+    .on_hover_text(tr("Releasing the key resumes from the selected point.\n\
+             Otherwise pressing the key again does."));
+
     // FIXME: reset auto save timing state when changed rewinding setting
+
+    ui.separator();
+
+    ui.label(tr("Debugging:"));
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut config.log_state_hashes, tr("Log state hashes every"));
+        ui.add_enabled(
+            config.log_state_hashes,
+            egui::DragValue::new(&mut config.state_hash_interval_frames)
+                .clamp_range(1..=3600)
+                .suffix(" frames"),
+        );
+    })
+    .response
+    .on_hover_text(tr(
+        "Hashes of the machine state are logged at debug level, to compare runs",
+    ));
 }
 
 fn file_dialog_filters() -> Vec<(String, Vec<String>)> {
-    let mut ret = vec![("All files".into(), vec!["*".to_string()])];
+    let mut ret = vec![(tr("All files").into(), vec!["*".to_string()])];
 
     for info in Emulator::core_infos() {
-        let name = format!("{} file", info.abbrev);
+        let name = trf("{} file", &[&info.abbrev]);
         let exts = info
             .file_extensions
             .iter()
@@ -917,7 +3137,7 @@ pub fn file_field(
     let mut ret = false;
     ui.horizontal(|ui| {
         ui.label(label);
-        if ui.button("Change").clicked() {
+        if ui.button(tr("Change")).clicked() {
             let fd = rfd::FileDialog::new();
             let fd = if let Some(path) = path {
                 fd.set_directory(path)
@@ -938,7 +3158,7 @@ pub fn file_field(
                 ret = true;
             }
         }
-        if has_clear && ui.button("Clear").clicked() {
+        if has_clear && ui.button(tr("Clear")).clicked() {
             *path = None;
             ret = true;
         }
@@ -946,7 +3166,7 @@ pub fn file_field(
     ui.indent("", |ui| {
         let s = path
             .as_ref()
-            .map_or_else(|| "None".to_string(), |r| r.display().to_string());
+            .map_or_else(|| tr("None").to_string(), |r| r.display().to_string());
         ui.add(egui::TextEdit::singleline(&mut s.as_ref()));
     });
     ret
@@ -0,0 +1,219 @@
+use anyhow::{bail, Result};
+use chrono::Local;
+use meru_interface::InputData;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+pub const MOVIE_EXTENSION: &str = "mru";
+
+const MAGIC: &[u8; 8] = b"MERUMOV1";
+
+/// Frames between the state hashes used to detect desyncs
+const HASH_INTERVAL: usize = 60;
+
+#[derive(Serialize, Deserialize)]
+struct MovieHeader {
+    core: String,
+    game_name: String,
+    version: String,
+    /// Button names of each controller, in the bit order of the recorded inputs
+    buttons: Vec<Vec<String>>,
+    frames: usize,
+    /// Hash of the state after every `HASH_INTERVAL` frames
+    hashes: Vec<u64>,
+}
+
+/// Inputs of every frame, starting from a save state
+pub struct Movie {
+    header: MovieHeader,
+    /// Tagged with the core and its state format version
+    initial_state: Vec<u8>,
+    /// Pressed buttons of each controller as bit sets, per frame
+    inputs: Vec<Vec<u32>>,
+}
+
+impl Movie {
+    pub fn new(core: &str, game_name: &str, initial_state: Vec<u8>) -> Self {
+        Self {
+            header: MovieHeader {
+                core: core.to_string(),
+                game_name: game_name.to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                buttons: vec![],
+                frames: 0,
+                hashes: vec![],
+            },
+            initial_state,
+            inputs: vec![],
+        }
+    }
+
+    pub fn core(&self) -> &str {
+        &self.header.core
+    }
+
+    pub fn game_name(&self) -> &str {
+        &self.header.game_name
+    }
+
+    pub fn initial_state(&self) -> &[u8] {
+        &self.initial_state
+    }
+
+    pub fn frames(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn push_input(&mut self, input: &InputData) -> Result<()> {
+        if self.header.buttons.is_empty() {
+            self.header.buttons = input
+                .controllers
+                .iter()
+                .map(|keys| keys.iter().map(|(key, _)| key.clone()).collect())
+                .collect();
+            if self.header.buttons.iter().any(|buttons| buttons.len() > 32) {
+                bail!("Too many buttons to record");
+            }
+        }
+
+        let bits = self
+            .header
+            .buttons
+            .iter()
+            .zip(input.controllers.iter())
+            .map(|(buttons, keys)| {
+                buttons
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, button)| {
+                        keys.iter().any(|(key, pressed)| key == *button && *pressed)
+                    })
+                    .fold(0, |bits, (i, _)| bits | 1 << i)
+            })
+            .collect();
+        self.inputs.push(bits);
+        self.header.frames = self.inputs.len();
+        Ok(())
+    }
+
+    pub fn input(&self, frame: usize) -> Option<InputData> {
+        let bits = self.inputs.get(frame)?;
+        let controllers = self
+            .header
+            .buttons
+            .iter()
+            .zip(bits.iter())
+            .map(|(buttons, bits)| {
+                buttons
+                    .iter()
+                    .enumerate()
+                    .map(|(i, button)| (button.clone(), bits & 1 << i != 0))
+                    .collect()
+            })
+            .collect();
+        Some(InputData { controllers })
+    }
+
+    /// Record the state after `frame` frames if a hash is due
+    pub fn push_hash(&mut self, frame: usize, state: impl FnOnce() -> Vec<u8>) {
+        if frame % HASH_INTERVAL == 0 && self.header.hashes.len() < frame / HASH_INTERVAL {
+            self.header.hashes.push(state_hash(&state()));
+        }
+    }
+
+    /// Whether the state after `frame` frames differs from the recording.
+    /// `None` if no hash is recorded for the frame.
+    pub fn check_hash(&self, frame: usize, state: impl FnOnce() -> Vec<u8>) -> Option<bool> {
+        if frame == 0 || frame % HASH_INTERVAL != 0 {
+            return None;
+        }
+        let expected = self.header.hashes.get(frame / HASH_INTERVAL - 1)?;
+        Some(state_hash(&state()) != *expected)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut f = BufWriter::new(fs::File::create(path)?);
+        f.write_all(MAGIC)?;
+        let header = serde_json::to_vec(&self.header)?;
+        f.write_all(&(header.len() as u64).to_le_bytes())?;
+        f.write_all(&header)?;
+        f.write_all(&(self.initial_state.len() as u64).to_le_bytes())?;
+        f.write_all(&self.initial_state)?;
+        for bits in &self.inputs {
+            for bits in bits {
+                f.write_all(&bits.to_le_bytes())?;
+            }
+        }
+        f.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path)?;
+        // Lengths in the file can't be trusted for allocations past its size
+        let file_len = file.metadata()?.len();
+        let mut f = BufReader::new(file);
+
+        let mut magic = [0; 8];
+        f.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            bail!("Not a movie file");
+        }
+
+        let header: MovieHeader = serde_json::from_slice(&read_chunk(&mut f, file_len)?)?;
+        let initial_state = read_chunk(&mut f, file_len)?;
+
+        let inputs_len = header.frames.checked_mul(header.buttons.len() * 4);
+        if inputs_len.map_or(true, |len| len as u64 > file_len) {
+            bail!("Movie file is corrupted");
+        }
+
+        let mut inputs = Vec::with_capacity(header.frames);
+        for _ in 0..header.frames {
+            let mut bits = vec![];
+            for _ in 0..header.buttons.len() {
+                let mut buf = [0; 4];
+                f.read_exact(&mut buf)?;
+                bits.push(u32::from_le_bytes(buf));
+            }
+            inputs.push(bits);
+        }
+
+        Ok(Self {
+            header,
+            initial_state,
+            inputs,
+        })
+    }
+}
+
+fn read_chunk(f: &mut impl Read, max_len: u64) -> Result<Vec<u8>> {
+    let mut len = [0; 8];
+    f.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+    if len > max_len {
+        bail!("Movie file is corrupted");
+    }
+    let mut data = vec![0; len as usize];
+    f.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// FNV-1a, which stays the same across builds unlike `DefaultHasher`
+pub fn state_hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn movie_file_path(game_name: &str, dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    Ok(dir.join(format!(
+        "{game_name}_{}.{MOVIE_EXTENSION}",
+        Local::now().format("%Y%m%d_%H%M%S")
+    )))
+}
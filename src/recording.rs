@@ -0,0 +1,443 @@
+use anyhow::{anyhow, bail, Result};
+use bevy::prelude::*;
+use chrono::prelude::*;
+use meru_interface::{AudioBuffer, FrameBuffer};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Sender},
+    thread::JoinHandle,
+};
+
+use crate::{
+    app::{AppState, PixelFont, ShowMessage},
+    config::Config,
+    core::{Emulator, EmulatorEnum, GameScreen},
+    file::{frame_buffer_to_rgb_image, frame_buffer_to_rgba_image},
+    i18n::{tr, trf},
+};
+
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(None as Option<Recorder>)
+            .insert_resource(None as Option<AudioRecorder>)
+            .init_resource::<GifRecorder>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Running).with_system(setup_rec_indicator_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Running)
+                    .with_system(rec_indicator_system)
+                    .with_system(gif_capture_system),
+            )
+            .add_system(gif_export_system)
+            .add_system_set(
+                SystemSet::on_exit(AppState::Running).with_system(exit_rec_indicator_system),
+            );
+    }
+}
+
+/// Writes every emulated frame as a PNG and the audio as a WAV file into one directory
+pub struct Recorder {
+    dir: PathBuf,
+    frames: usize,
+    /// Frame rate of the recorded core
+    frame_rate: f64,
+    frame_tx: Option<Sender<(usize, image::RgbImage)>>,
+    frame_writer: Option<JoinHandle<Result<()>>>,
+    audio: Option<hound::WavWriter<BufWriter<fs::File>>>,
+}
+
+impl Recorder {
+    pub fn start(base_dir: &Path) -> Result<Self> {
+        let dir = base_dir.join(format!("meru_{}", Local::now().format("%Y%m%d_%H%M%S")));
+        fs::create_dir_all(&dir)?;
+
+        // PNG encoding is too slow to do on the emulation thread
+        let (frame_tx, frame_rx) = channel::<(usize, image::RgbImage)>();
+        let frame_dir = dir.clone();
+        let frame_writer = std::thread::spawn(move || -> Result<()> {
+            for (ix, image) in frame_rx {
+                image.save(frame_dir.join(format!("frame_{ix:06}.png")))?;
+            }
+            Ok(())
+        });
+
+        info!("Start recording: `{}`", dir.display());
+
+        Ok(Self {
+            dir,
+            frames: 0,
+            frame_rate: 60.0,
+            frame_tx: Some(frame_tx),
+            frame_writer: Some(frame_writer),
+            audio: None,
+        })
+    }
+
+    fn push_frame(
+        &mut self,
+        frame_buffer: &FrameBuffer,
+        audio_buffer: &AudioBuffer,
+        frame_rate: f64,
+    ) -> Result<()> {
+        self.frame_rate = frame_rate;
+        let image = frame_buffer_to_rgb_image(frame_buffer);
+        self.frame_tx
+            .as_ref()
+            .unwrap()
+            .send((self.frames, image))
+            .map_err(|_| anyhow!("Frame writer stopped"))?;
+        self.frames += 1;
+
+        if self.audio.is_none() {
+            let spec = hound::WavSpec {
+                channels: 2,
+                sample_rate: audio_buffer.sample_rate,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            self.audio = Some(hound::WavWriter::create(self.dir.join("audio.wav"), spec)?);
+        }
+        let audio = self.audio.as_mut().unwrap();
+        for sample in &audio_buffer.samples {
+            audio.write_sample(sample.left)?;
+            audio.write_sample(sample.right)?;
+        }
+
+        Ok(())
+    }
+
+    /// Finish writing all files and return the path of the recording
+    pub fn finish(mut self) -> Result<PathBuf> {
+        self.finalize()
+    }
+
+    fn finalize(&mut self) -> Result<PathBuf> {
+        drop(self.frame_tx.take());
+        if let Some(frame_writer) = self.frame_writer.take() {
+            frame_writer
+                .join()
+                .map_err(|_| anyhow!("Frame writer panicked"))??;
+        }
+        if let Some(audio) = self.audio.take() {
+            audio.finalize()?;
+        }
+
+        info!(
+            "Finish recording: {} frames to `{}`",
+            self.frames,
+            self.dir.display()
+        );
+
+        #[cfg(feature = "ffmpeg")]
+        match mux_mp4(&self.dir, self.frame_rate) {
+            Ok(path) => return Ok(path),
+            Err(err) => error!("Failed to make MP4, raw frames are kept: {err}"),
+        }
+
+        Ok(self.dir.clone())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if self.frame_writer.is_some() {
+            if let Err(err) = self.finalize() {
+                error!("Failed to finish recording: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+fn mux_mp4(dir: &Path, frame_rate: f64) -> Result<PathBuf> {
+    let output = dir.with_extension("mp4");
+    let status = std::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-framerate"])
+        .arg(frame_rate.to_string())
+        .arg("-i")
+        .arg(dir.join("frame_%06d.png"))
+        .arg("-i")
+        .arg(dir.join("audio.wav"))
+        .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", "-c:a", "aac"])
+        .arg(&output)
+        .status()?;
+    if !status.success() {
+        anyhow::bail!("ffmpeg failed: {status}");
+    }
+    Ok(output)
+}
+
+/// Record the current frame of `core` if recording is active
+pub fn record_frame(recorder: &mut Option<Recorder>, core: &EmulatorEnum) {
+    let result = if let Some(recorder) = recorder.as_mut() {
+        recorder.push_frame(core.frame_buffer(), core.audio_buffer(), core.frame_rate())
+    } else {
+        return;
+    };
+
+    if let Err(err) = result {
+        error!("Recording stopped: {err}");
+        *recorder = None;
+    }
+}
+
+/// Writes the audio sent to the output device into a WAV file
+pub struct AudioRecorder {
+    path: PathBuf,
+    sample_rate: Option<u32>,
+    writer: Option<hound::WavWriter<BufWriter<fs::File>>>,
+}
+
+impl AudioRecorder {
+    pub fn start(base_dir: &Path) -> Result<Self> {
+        fs::create_dir_all(base_dir)?;
+        let path = base_dir.join(format!("meru_{}.wav", Local::now().format("%Y%m%d_%H%M%S")));
+
+        info!("Start audio recording: `{}`", path.display());
+
+        Ok(Self {
+            path,
+            sample_rate: None,
+            writer: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn push_audio(&mut self, audio_buffer: &AudioBuffer) -> Result<()> {
+        // The file is created at the core's native rate once it is known
+        if self.writer.is_none() {
+            let spec = hound::WavSpec {
+                channels: 2,
+                sample_rate: audio_buffer.sample_rate,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            self.writer = Some(hound::WavWriter::create(&self.path, spec)?);
+            self.sample_rate = Some(audio_buffer.sample_rate);
+        }
+        if self.sample_rate != Some(audio_buffer.sample_rate) {
+            bail!("Sample rate changed");
+        }
+
+        let writer = self.writer.as_mut().unwrap();
+        for sample in &audio_buffer.samples {
+            writer.write_sample(sample.left)?;
+            writer.write_sample(sample.right)?;
+        }
+        Ok(())
+    }
+
+    /// Finish writing the file and return its path
+    pub fn finish(mut self) -> Result<PathBuf> {
+        self.finalize()?;
+        Ok(self.path.clone())
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.finalize()?;
+            info!("Finish audio recording: `{}`", self.path.display());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AudioRecorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.finalize() {
+            error!("Failed to finish audio recording: {err}");
+        }
+    }
+}
+
+/// Record the audio of `audio_buffer` if audio recording is active
+pub fn record_audio(audio_recorder: &mut Option<AudioRecorder>, audio_buffer: &AudioBuffer) {
+    let result = if let Some(audio_recorder) = audio_recorder.as_mut() {
+        audio_recorder.push_audio(audio_buffer)
+    } else {
+        return;
+    };
+
+    if let Err(err) = result {
+        error!("Audio recording stopped: {err}");
+        *audio_recorder = None;
+    }
+}
+
+/// Frames kept for GIF clips, one every this many emulated frames
+const GIF_FRAME_INTERVAL: usize = 3;
+/// Frames wider than this are shrunk by an integer factor
+const GIF_MAX_WIDTH: u32 = 320;
+
+/// Recent frames of the game, to save the last few seconds as a GIF
+#[derive(Default)]
+pub struct GifRecorder {
+    frames: VecDeque<image::RgbaImage>,
+    last_frame: usize,
+    /// Frame rate of the core the frames come from
+    frame_rate: f64,
+    export: Option<JoinHandle<Result<PathBuf>>>,
+}
+
+impl GifRecorder {
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn is_exporting(&self) -> bool {
+        self.export.is_some()
+    }
+
+    fn push_frame(&mut self, frame_buffer: &FrameBuffer, duration_secs: f32, frame_rate: f64) {
+        self.frame_rate = frame_rate;
+        let mut image = frame_buffer_to_rgba_image(frame_buffer);
+        let factor = (image.width() + GIF_MAX_WIDTH - 1) / GIF_MAX_WIDTH;
+        if factor > 1 {
+            image = image::imageops::resize(
+                &image,
+                image.width() / factor,
+                image.height() / factor,
+                image::imageops::FilterType::Triangle,
+            );
+        }
+        self.frames.push_back(image);
+
+        let limit = (duration_secs as f64 * frame_rate / GIF_FRAME_INTERVAL as f64).ceil() as usize;
+        while self.frames.len() > limit {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Encode the kept frames into `dir` in the background
+    pub fn start_export(&mut self, dir: &Path) {
+        let frames = self.frames.clone();
+        let dir = dir.to_owned();
+        let frame_rate = self.frame_rate;
+        self.export = Some(std::thread::spawn(move || {
+            save_gif(frames, &dir, frame_rate)
+        }));
+    }
+}
+
+fn save_gif(frames: VecDeque<image::RgbaImage>, dir: &Path, frame_rate: f64) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("meru_{}.gif", Local::now().format("%Y%m%d_%H%M%S")));
+
+    let file = BufWriter::new(fs::File::create(&path)?);
+    let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(file, 10);
+    encoder.set_repeat(image::codecs::gif::Repeat::Infinite)?;
+    // In thousandths of a frame to keep fractional frame rates
+    let delay = image::Delay::from_numer_denom_ms(
+        1_000_000 * GIF_FRAME_INTERVAL as u32,
+        ((frame_rate * 1000.0).round() as u32).max(1),
+    );
+    encoder.encode_frames(
+        frames
+            .into_iter()
+            .map(|image| image::Frame::from_parts(image, 0, 0, delay)),
+    )?;
+
+    info!("Saved GIF: `{}`", path.display());
+    Ok(path)
+}
+
+fn gif_capture_system(
+    config: Res<Config>,
+    emulator: Res<Emulator>,
+    mut gif_recorder: ResMut<GifRecorder>,
+) {
+    if emulator.is_added() || config.gif_duration_secs <= 0.0 {
+        gif_recorder.frames.clear();
+        gif_recorder.last_frame = emulator.frames();
+        return;
+    }
+
+    let frames = emulator.frames();
+    if frames / GIF_FRAME_INTERVAL != gif_recorder.last_frame / GIF_FRAME_INTERVAL {
+        gif_recorder.push_frame(
+            emulator.core.frame_buffer(),
+            config.gif_duration_secs,
+            emulator.core.frame_rate(),
+        );
+    }
+    gif_recorder.last_frame = frames;
+}
+
+fn gif_export_system(
+    mut gif_recorder: ResMut<GifRecorder>,
+    mut message_event: EventWriter<ShowMessage>,
+) {
+    if !gif_recorder
+        .export
+        .as_ref()
+        .map_or(false, |handle| handle.is_finished())
+    {
+        return;
+    }
+
+    match gif_recorder.export.take().unwrap().join() {
+        Ok(Ok(path)) => {
+            let name = path.file_name().unwrap().to_string_lossy();
+            message_event.send(ShowMessage(trf("GIF saved: {}", &[&name])));
+        }
+        Ok(Err(err)) => {
+            error!("Failed to save GIF: {err}");
+            message_event.send(ShowMessage(tr("Failed to save GIF").to_string()));
+        }
+        Err(_) => error!("GIF export panicked"),
+    }
+}
+
+#[derive(Component)]
+struct RecText;
+
+fn setup_rec_indicator_system(
+    mut commands: Commands,
+    pixel_font: Query<&Handle<Font>, With<PixelFont>>,
+) {
+    let pixel_font = pixel_font.single();
+
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                "REC",
+                TextStyle {
+                    font: pixel_font.clone(),
+                    font_size: 16.0,
+                    color: Color::RED,
+                },
+            ),
+            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(RecText);
+}
+
+fn exit_rec_indicator_system(mut commands: Commands, rec_text: Query<Entity, With<RecText>>) {
+    commands.entity(rec_text.single()).despawn();
+}
+
+fn rec_indicator_system(
+    time: Res<Time>,
+    recorder: Res<Option<Recorder>>,
+    screen: Res<GameScreen>,
+    images: Res<Assets<Image>>,
+    mut rec_text: Query<(&mut Visibility, &mut Transform), With<RecText>>,
+) {
+    let (mut visibility, mut transform) = rec_text.single_mut();
+
+    visibility.is_visible = recorder.is_some() && time.seconds_since_startup() % 1.0 < 0.5;
+
+    let image_size = images.get(&screen.0).unwrap().size();
+    *transform = Transform::from_xyz(-image_size[0] / 2.0 + 2.0, image_size[1] / 2.0, 2.0);
+}
@@ -3,9 +3,10 @@ use bevy_easings::*;
 use std::time::Duration;
 
 use crate::{
-    app::{AppState, ScreenSprite},
-    config::{self, SystemKey},
-    core::Emulator,
+    app::{AppState, PixelFont, ScreenSprite},
+    config::{self, RewindAudio, SystemKey},
+    core::{AudioSource, Emulator},
+    hotkey::HotKey,
     input::InputState,
 };
 
@@ -13,11 +14,17 @@ use crate::{
 pub struct AutoSavedState {
     pub thumbnail: Image,
     pub data: Vec<u8>,
+    /// Interleaved audio samples played since the previous snapshot
+    pub audio: Vec<i16>,
+    /// Frame number the snapshot was taken at
+    pub frame: usize,
+    /// Frames run by the core by then, fast-forwarded ones included
+    pub core_frame: usize,
 }
 
 impl AutoSavedState {
     pub fn size(&self) -> usize {
-        self.data.len() + self.thumbnail.data.len()
+        self.data.len() + self.thumbnail.data.len() + self.audio.len() * 2
     }
 }
 
@@ -25,6 +32,8 @@ pub struct RewindingState {
     pos: usize,
     load_pos: Option<usize>,
     exit: bool,
+    /// The rewind key was released since entering, so a press toggles rewinding off
+    key_released: bool,
 }
 
 pub struct RewindingPlugin;
@@ -34,7 +43,11 @@ impl Plugin for RewindingPlugin {
         app.add_system_set(
             SystemSet::on_enter(AppState::Rewinding).with_system(enter_rewinding_system),
         )
-        .add_system_set(SystemSet::on_update(AppState::Rewinding).with_system(rewinding_system))
+        .add_system_set(
+            SystemSet::on_update(AppState::Rewinding)
+                .with_system(rewinding_system)
+                .with_system(rewind_indicator_system),
+        )
         .add_system_set(SystemSet::on_exit(AppState::Rewinding).with_system(exit_rewinding_system));
     }
 }
@@ -48,12 +61,31 @@ struct Preview;
 #[derive(Component)]
 struct Thumbnail(usize);
 
+/// Everything that belongs to the position indicator
+#[derive(Component)]
+struct RewindIndicator;
+
+#[derive(Component)]
+struct RewindBarFill;
+
+#[derive(Component)]
+struct RewindOffsetText;
+
+const REWIND_BAR_HEIGHT: f32 = 3.0;
+
 fn enter_rewinding_system(
     mut commands: Commands,
     emulator: ResMut<Emulator>,
     mut images: ResMut<Assets<Image>>,
     mut screen_visibility: Query<&mut Visibility, With<ScreenSprite>>,
+    config: Res<config::Config>,
+    audio_sink: Res<rodio::Sink>,
+    pixel_font: Query<&Handle<Font>, With<PixelFont>>,
 ) {
+    if config.rewind_audio == RewindAudio::Mute {
+        audio_sink.pause();
+    }
+
     let screen_width = emulator.core.frame_buffer().width as f32;
     let screen_height = emulator.core.frame_buffer().height as f32;
 
@@ -121,10 +153,58 @@ fn enter_rewinding_system(
         }
     }
 
+    let bar_y = screen_height / 2.0 - REWIND_BAR_HEIGHT / 2.0;
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.0, 0.0, 0.0, 0.75),
+                custom_size: Some(Vec2::new(screen_width, REWIND_BAR_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, bar_y, 2.0),
+            ..Default::default()
+        })
+        .insert(RewindIndicator);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE,
+                custom_size: Some(Vec2::new(screen_width, REWIND_BAR_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, bar_y, 3.0),
+            ..Default::default()
+        })
+        .insert(RewindIndicator)
+        .insert(RewindBarFill);
+
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: pixel_font.single().clone(),
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                },
+            ),
+            transform: Transform::from_xyz(
+                -screen_width / 2.0 + 2.0,
+                screen_height / 2.0 - REWIND_BAR_HEIGHT,
+                3.0,
+            ),
+            ..Default::default()
+        })
+        .insert(RewindIndicator)
+        .insert(RewindOffsetText);
+
     commands.insert_resource(RewindingState {
         pos: state_num - 1,
         load_pos: None,
         exit: false,
+        key_released: false,
     });
 }
 
@@ -142,6 +222,7 @@ fn rewinding_system(
     input_gamepad_button: Res<Input<GamepadButton>>,
     input_gamepad_axis: Res<Axis<GamepadAxis>>,
     easing: Query<&EasingComponent<Transform>>,
+    audio_sink: Res<rodio::Sink>,
 ) {
     let screen_width = emulator.core.frame_buffer().width as f32;
     let screen_height = emulator.core.frame_buffer().height as f32;
@@ -180,7 +261,7 @@ fn rewinding_system(
                 duration: Duration::from_millis(200),
             },
         ));
-        emulator.core.load_state(&state.data).unwrap();
+        emulator.load_auto_saved_state(&state).unwrap();
         rewinding_state.exit = true;
         return;
     }
@@ -235,6 +316,26 @@ fn rewinding_system(
             do_move = true;
         }
 
+        if do_move && config.rewind_audio == RewindAudio::Reverse && audio_sink.len() < 2 {
+            let audio_buffer = emulator.core.audio_buffer();
+            let channels = audio_buffer.channels.max(1);
+            let data = if left {
+                // Play the audio between the previous and the current position backwards
+                emulator.auto_saved_states[rewinding_state.pos + 1]
+                    .audio
+                    .chunks(channels as usize)
+                    .rev()
+                    .flatten()
+                    .copied()
+                    .collect()
+            } else {
+                emulator.auto_saved_states[rewinding_state.pos]
+                    .audio
+                    .clone()
+            };
+            audio_sink.append(AudioSource::new(audio_buffer.sample_rate, channels, data));
+        }
+
         if do_move {
             let dx = if left { 1.0 } else { -1.0 } * screen_width / 4.0;
             for (entity, trans) in thumbnails.iter() {
@@ -255,9 +356,20 @@ fn rewinding_system(
         }
     }
 
-    if config
-        .system_keys
-        .just_pressed(&SystemKey::Ok, &input_state)
+    let rewind_pressed = config.hotkeys.pressed(&HotKey::Rewind, &input_state);
+    let rewind_done = if config.hold_for_rewind {
+        !rewind_pressed
+    } else {
+        rewinding_state.key_released && rewind_pressed
+    };
+    if !rewind_pressed {
+        rewinding_state.key_released = true;
+    }
+
+    if rewind_done
+        || config
+            .system_keys
+            .just_pressed(&SystemKey::Ok, &input_state)
     {
         rewinding_state.load_pos = Some(rewinding_state.pos);
     } else if config
@@ -268,13 +380,49 @@ fn rewinding_system(
     }
 }
 
+fn rewind_indicator_system(
+    emulator: Res<Emulator>,
+    rewinding_state: Res<RewindingState>,
+    mut bar_fill: Query<(&mut Sprite, &mut Transform), With<RewindBarFill>>,
+    mut offset_text: Query<&mut Text, With<RewindOffsetText>>,
+) {
+    let states = &emulator.auto_saved_states;
+    if states.is_empty() {
+        return;
+    }
+    let pos = rewinding_state.pos.min(states.len() - 1);
+
+    let screen_width = emulator.core.frame_buffer().width as f32;
+    let ratio = if states.len() > 1 {
+        pos as f32 / (states.len() - 1) as f32
+    } else {
+        1.0
+    };
+    let fill_width = screen_width * ratio;
+
+    for (mut sprite, mut transform) in bar_fill.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(fill_width, REWIND_BAR_HEIGHT));
+        transform.translation.x = -screen_width / 2.0 + fill_width / 2.0;
+    }
+
+    let offset =
+        (states.back().unwrap().frame - states[pos].frame) as f64 / emulator.core.frame_rate();
+    for mut text in offset_text.iter_mut() {
+        text.sections[0].value = format!("-{offset:.1}s");
+    }
+}
+
 fn exit_rewinding_system(
     mut commands: Commands,
     bg_color: Query<Entity, With<BgColor>>,
     preview: Query<Entity, With<Preview>>,
     thumbnails: Query<Entity, With<Thumbnail>>,
+    indicator: Query<Entity, With<RewindIndicator>>,
     mut screen_visibility: Query<&mut Visibility, With<ScreenSprite>>,
+    audio_sink: Res<rodio::Sink>,
 ) {
+    audio_sink.play();
+
     for mut visibility in screen_visibility.iter_mut() {
         visibility.is_visible = true;
     }
@@ -283,6 +431,7 @@ fn exit_rewinding_system(
         .iter()
         .chain(preview.iter())
         .chain(thumbnails.iter())
+        .chain(indicator.iter())
     {
         commands.entity(entity).despawn();
     }
@@ -0,0 +1,91 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    app::{AppState, PixelFont},
+    i18n::tr,
+};
+
+pub struct SplashPlugin;
+
+impl Plugin for SplashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Splash).with_system(setup_splash_system))
+            .add_system_set(SystemSet::on_update(AppState::Splash).with_system(splash_system))
+            .add_system_set(SystemSet::on_exit(AppState::Splash).with_system(exit_splash_system));
+    }
+}
+
+#[derive(Component)]
+struct Splash;
+
+fn setup_splash_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    pixel_font: Query<&Handle<Font>, With<PixelFont>>,
+) {
+    const LOGO_DATA: &[u8] = include_bytes!("../assets/meru.ico");
+    const LOGO_SIZE: u32 = 64;
+
+    let logo = image::load_from_memory_with_format(LOGO_DATA, image::ImageFormat::Ico)
+        .unwrap()
+        .resize(LOGO_SIZE, LOGO_SIZE, image::imageops::FilterType::Lanczos3)
+        .into_rgba8();
+
+    let logo = Image::new(
+        Extent3d {
+            width: logo.width(),
+            height: logo.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        logo.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: images.add(logo),
+            transform: Transform::from_xyz(0.0, 24.0, 1.0),
+            ..Default::default()
+        })
+        .insert(Splash);
+
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                tr("Press any button"),
+                TextStyle {
+                    font: pixel_font.single().clone(),
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_xyz(0.0, -32.0, 1.0),
+            ..Default::default()
+        })
+        .insert(Splash);
+}
+
+fn splash_system(
+    mut app_state: ResMut<State<AppState>>,
+    input_keycode: Res<Input<KeyCode>>,
+    input_gamepad_button: Res<Input<GamepadButton>>,
+    input_mouse_button: Res<Input<MouseButton>>,
+) {
+    if input_keycode.get_just_pressed().next().is_some()
+        || input_gamepad_button.get_just_pressed().next().is_some()
+        || input_mouse_button.get_just_pressed().next().is_some()
+    {
+        app_state.set(AppState::Menu).unwrap();
+    }
+}
+
+fn exit_splash_system(mut commands: Commands, splash: Query<Entity, With<Splash>>) {
+    for entity in splash.iter() {
+        commands.entity(entity).despawn();
+    }
+}